@@ -9,7 +9,7 @@ use romio::TcpStream;
 
 fn main() -> io::Result<()> {
     executor::block_on(async {
-        let mut stream = await!(TcpStream::connect(&"127.0.0.1:7878".parse().unwrap()))?;
+        let mut stream = await!(TcpStream::connect("127.0.0.1:7878"))?;
         let mut stdout = AllowStdIo::new(io::stdout());
         await!(stream.copy_into(&mut stdout))?;
         Ok(())
@@ -0,0 +1,383 @@
+//! Socket activation, adopting the listening sockets passed by systemd.
+//!
+//! When a service is started by a systemd socket unit, the sockets it listens
+//! on are created by systemd and inherited by the service, starting at file
+//! descriptor 3. The environment of the service describes them:
+//!
+//! - `LISTEN_PID` is the process ID of the service, so that child processes
+//!   which inherit the environment ignore the sockets.
+//! - `LISTEN_FDS` is the number of sockets passed.
+//! - `LISTEN_FDNAMES` optionally holds the names of the sockets, as set with
+//!   `FileDescriptorName=`, separated by colons.
+//!
+//! [`listen_fds`] parses these variables and returns a [`ListenFd`] for each
+//! socket, which can be checked and converted into a romio listener or
+//! socket.
+//!
+//! [`listen_fds`]: fn.listen_fds.html
+//! [`ListenFd`]: struct.ListenFd.html
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use romio::activation::{self, SocketKind};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+//! for fd in activation::listen_fds()? {
+//!     match fd.kind()? {
+//!         SocketKind::TcpListener => {
+//!             let listener = fd.into_tcp_listener()?;
+//!             // ...
+//!         }
+//!         kind => eprintln!("ignoring unexpected socket {:?}", kind),
+//!     }
+//! }
+//! # Ok(())}
+//! ```
+
+use std::env;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+use libc;
+use mio;
+use mio_uds;
+
+use crate::reactor::Handle;
+use crate::tcp::TcpListener;
+use crate::udp::UdpSocket;
+use crate::uds::{UnixDatagram, UnixListener};
+
+/// The first file descriptor passed by systemd.
+const LISTEN_FDS_START: RawFd = 3;
+
+/// A socket inherited from systemd.
+///
+/// The file descriptor is owned by the `ListenFd`, and closed when it is
+/// dropped unless it is converted first.
+pub struct ListenFd {
+    fd: RawFd,
+    name: Option<String>,
+}
+
+/// The kind of a socket inherited from systemd.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SocketKind {
+    /// A listening TCP socket, set with `ListenStream=` and an IP address or
+    /// port.
+    TcpListener,
+
+    /// A UDP socket, set with `ListenDatagram=` and an IP address or port.
+    UdpSocket,
+
+    /// A listening Unix stream socket, set with `ListenStream=` and a path.
+    UnixListener,
+
+    /// A Unix datagram socket, set with `ListenDatagram=` and a path.
+    UnixDatagram,
+
+    /// Any other file descriptor, such as a FIFO or an accepted connection.
+    Other,
+}
+
+/// Returns the sockets passed to this process by systemd.
+///
+/// If `LISTEN_PID` is not set, or is not the ID of this process, no sockets
+/// have been passed and the result is empty. An error is returned if the
+/// variables are malformed, if there are not as many names as sockets, or if
+/// one of the sockets isn't an open file descriptor.
+///
+/// The variables are removed from the environment, so that the sockets can
+/// only be adopted once and aren't seen by child processes. The sockets are
+/// set to close on exec.
+pub fn listen_fds() -> io::Result<Vec<ListenFd>> {
+    let pid = env::var("LISTEN_PID").ok();
+    let fds = env::var("LISTEN_FDS").ok();
+    let names = env::var("LISTEN_FDNAMES").ok();
+
+    env::remove_var("LISTEN_PID");
+    env::remove_var("LISTEN_FDS");
+    env::remove_var("LISTEN_FDNAMES");
+
+    let pid = match pid {
+        Some(pid) => parse("LISTEN_PID", &pid)?,
+        None => return Ok(Vec::new()),
+    };
+    if pid != unsafe { libc::getpid() } as u32 {
+        return Ok(Vec::new());
+    }
+
+    let count = match fds {
+        Some(fds) => parse("LISTEN_FDS", &fds)?,
+        None => return Ok(Vec::new()),
+    };
+    // the file descriptors must all be in the range of `RawFd`
+    if count > (RawFd::max_value() - LISTEN_FDS_START) as u32 {
+        return Err(invalid(format!("too many file descriptors in LISTEN_FDS: {}", count)));
+    }
+
+    let names = match names {
+        Some(names) => {
+            let names: Vec<_> = names.split(':').map(|name| Some(name.to_string())).collect();
+            if names.len() != count as usize {
+                return Err(invalid(format!(
+                    "LISTEN_FDNAMES has {} names for {} file descriptors",
+                    names.len(),
+                    count
+                )));
+            }
+            names
+        }
+        None => vec![None; count as usize],
+    };
+
+    // Check that every file descriptor is open before owning any of them, so
+    // that none is closed on behalf of another part of the process.
+    for i in 0..count {
+        let fd = LISTEN_FDS_START + i as RawFd;
+        if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+            let err = io::Error::last_os_error();
+            return Err(io::Error::new(
+                err.kind(),
+                format!("file descriptor {} in LISTEN_FDS: {}", fd, err),
+            ));
+        }
+    }
+
+    // Take ownership of every socket first, so that they are all closed if
+    // one of them fails.
+    let fds: Vec<_> = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| ListenFd {
+            fd: LISTEN_FDS_START + i as RawFd,
+            name,
+        })
+        .collect();
+
+    for fd in &fds {
+        if unsafe { libc::fcntl(fd.fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(fds)
+}
+
+fn parse(var: &str, value: &str) -> io::Result<u32> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid value of {}: {:?}", var, value)))
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+impl ListenFd {
+    /// Returns the name of the socket, set with `FileDescriptorName=` in the
+    /// socket unit.
+    ///
+    /// This is `None` if systemd is too old to pass names. Otherwise, the
+    /// name defaults to the name of the socket unit.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|name| &name[..])
+    }
+
+    /// Returns the kind of the socket, checked with `fstat` and
+    /// `getsockopt`.
+    pub fn kind(&self) -> io::Result<SocketKind> {
+        let mut stat: libc::stat = unsafe { mem::zeroed() };
+        if unsafe { libc::fstat(self.fd, &mut stat) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        if stat.st_mode & libc::S_IFMT != libc::S_IFSOCK {
+            return Ok(SocketKind::Other);
+        }
+
+        let ty = getsockopt(self.fd, libc::SO_TYPE)?;
+        let listening = getsockopt(self.fd, libc::SO_ACCEPTCONN)? != 0;
+
+        let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of_val(&addr) as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockname(self.fd, &mut addr as *mut _ as *mut libc::sockaddr, &mut len)
+        };
+        if ret == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let kind = match (addr.ss_family as libc::c_int, ty, listening) {
+            (libc::AF_INET, libc::SOCK_STREAM, true) => SocketKind::TcpListener,
+            (libc::AF_INET6, libc::SOCK_STREAM, true) => SocketKind::TcpListener,
+            (libc::AF_INET, libc::SOCK_DGRAM, _) => SocketKind::UdpSocket,
+            (libc::AF_INET6, libc::SOCK_DGRAM, _) => SocketKind::UdpSocket,
+            (libc::AF_UNIX, libc::SOCK_STREAM, true) => SocketKind::UnixListener,
+            (libc::AF_UNIX, libc::SOCK_DGRAM, _) => SocketKind::UnixDatagram,
+            _ => SocketKind::Other,
+        };
+        Ok(kind)
+    }
+
+    /// Converts the socket into a `TcpListener`.
+    ///
+    /// An error of kind `InvalidInput` is returned if it is not a listening
+    /// TCP socket, in which case the file descriptor is closed.
+    pub fn into_tcp_listener(self) -> io::Result<TcpListener> {
+        let listener = self.into_mio(SocketKind::TcpListener, mio::net::TcpListener::from_std)?;
+        Ok(TcpListener::new(listener))
+    }
+
+    /// Converts the socket into a `TcpListener` associated with the reactor
+    /// referenced by `handle`.
+    ///
+    /// This is the same as [`into_tcp_listener`], except that the listener is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`into_tcp_listener`]: #method.into_tcp_listener
+    pub fn into_tcp_listener_with_handle(self, handle: &Handle) -> io::Result<TcpListener> {
+        let listener = self.into_mio(SocketKind::TcpListener, mio::net::TcpListener::from_std)?;
+        TcpListener::new_with_handle(listener, handle)
+    }
+
+    /// Converts the socket into a `UdpSocket`.
+    ///
+    /// An error of kind `InvalidInput` is returned if it is not a UDP socket,
+    /// in which case the file descriptor is closed.
+    pub fn into_udp_socket(self) -> io::Result<UdpSocket> {
+        let socket = self.into_mio(SocketKind::UdpSocket, mio::net::UdpSocket::from_socket)?;
+        Ok(UdpSocket::new(socket))
+    }
+
+    /// Converts the socket into a `UdpSocket` associated with the reactor
+    /// referenced by `handle`.
+    ///
+    /// This is the same as [`into_udp_socket`], except that the socket is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`into_udp_socket`]: #method.into_udp_socket
+    pub fn into_udp_socket_with_handle(self, handle: &Handle) -> io::Result<UdpSocket> {
+        let socket = self.into_mio(SocketKind::UdpSocket, mio::net::UdpSocket::from_socket)?;
+        UdpSocket::new_with_handle(socket, handle)
+    }
+
+    /// Converts the socket into a `UnixListener`.
+    ///
+    /// An error of kind `InvalidInput` is returned if it is not a listening
+    /// Unix stream socket, in which case the file descriptor is closed.
+    pub fn into_unix_listener(self) -> io::Result<UnixListener> {
+        let listener =
+            self.into_mio(SocketKind::UnixListener, mio_uds::UnixListener::from_listener)?;
+        Ok(UnixListener::new(listener))
+    }
+
+    /// Converts the socket into a `UnixListener` associated with the reactor
+    /// referenced by `handle`.
+    ///
+    /// This is the same as [`into_unix_listener`], except that the listener
+    /// is registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`into_unix_listener`]: #method.into_unix_listener
+    pub fn into_unix_listener_with_handle(self, handle: &Handle) -> io::Result<UnixListener> {
+        let listener =
+            self.into_mio(SocketKind::UnixListener, mio_uds::UnixListener::from_listener)?;
+        UnixListener::new_with_handle(listener, handle)
+    }
+
+    /// Converts the socket into a `UnixDatagram`.
+    ///
+    /// An error of kind `InvalidInput` is returned if it is not a Unix
+    /// datagram socket, in which case the file descriptor is closed.
+    pub fn into_unix_datagram(self) -> io::Result<UnixDatagram> {
+        let socket =
+            self.into_mio(SocketKind::UnixDatagram, mio_uds::UnixDatagram::from_datagram)?;
+        Ok(UnixDatagram::new(socket))
+    }
+
+    /// Converts the socket into a `UnixDatagram` associated with the reactor
+    /// referenced by `handle`.
+    ///
+    /// This is the same as [`into_unix_datagram`], except that the socket is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`into_unix_datagram`]: #method.into_unix_datagram
+    pub fn into_unix_datagram_with_handle(self, handle: &Handle) -> io::Result<UnixDatagram> {
+        let socket =
+            self.into_mio(SocketKind::UnixDatagram, mio_uds::UnixDatagram::from_datagram)?;
+        UnixDatagram::new_with_handle(socket, handle)
+    }
+
+    /// Checks that the socket is of the `expected` kind, then converts it into
+    /// a `std` socket and from there into a non-blocking `mio` socket.
+    fn into_mio<S, M>(self, expected: SocketKind, f: fn(S) -> io::Result<M>) -> io::Result<M>
+    where
+        S: FromRawFd,
+    {
+        let kind = self.kind()?;
+        if kind != expected {
+            return Err(invalid(format!(
+                "file descriptor {} is not a {:?} but a {:?}",
+                self.fd, expected, kind
+            )));
+        }
+
+        let socket = unsafe { S::from_raw_fd(self.into_raw_fd()) };
+        f(socket)
+    }
+}
+
+fn getsockopt(fd: RawFd, opt: libc::c_int) -> io::Result<libc::c_int> {
+    let mut val: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            opt,
+            &mut val as *mut _ as *mut libc::c_void,
+            &mut len,
+        )
+    };
+
+    if ret == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(val)
+    }
+}
+
+impl AsRawFd for ListenFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for ListenFd {
+    fn into_raw_fd(mut self) -> RawFd {
+        let fd = self.fd;
+        drop(self.name.take());
+        mem::forget(self);
+        fd
+    }
+}
+
+impl Drop for ListenFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl fmt::Debug for ListenFd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ListenFd")
+            .field("fd", &self.fd)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
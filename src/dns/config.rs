@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::time::Duration;
+
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const HOSTS: &str = "/etc/hosts";
+
+/// The configuration of a [`DnsResolver`].
+///
+/// The configuration is usually read from the system's `/etc/resolv.conf` and
+/// `/etc/hosts` by [`system`], but can also be built by hand, e.g. to query a
+/// specific name server.
+///
+/// The following `resolv.conf` settings are supported: `nameserver`, `search`,
+/// `domain`, and the `ndots`, `timeout` and `attempts` options.
+///
+/// [`DnsResolver`]: struct.DnsResolver.html
+/// [`system`]: #method.system
+///
+/// # Examples
+///
+/// ```rust
+/// use romio::dns::Config;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let mut config = Config::new();
+/// config
+///     .add_nameserver("10.0.0.53:53".parse()?)
+///     .add_search_domain("internal")
+///     .set_timeout(Duration::from_secs(1));
+/// # Ok(())}
+/// ```
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub(super) nameservers: Vec<SocketAddr>,
+    pub(super) search: Vec<String>,
+    pub(super) ndots: usize,
+    pub(super) timeout: Duration,
+    pub(super) attempts: usize,
+
+    /// Addresses from the hosts file, by lowercase name.
+    pub(super) hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+impl Config {
+    /// Creates an empty configuration, without name servers nor hosts.
+    ///
+    /// The options have the same defaults as in `resolv.conf`: `ndots` is 1,
+    /// the timeout is 5 seconds, and 2 attempts are made.
+    pub fn new() -> Config {
+        Config {
+            nameservers: Vec::new(),
+            search: Vec::new(),
+            ndots: 1,
+            timeout: Duration::from_secs(5),
+            attempts: 2,
+            hosts: HashMap::new(),
+        }
+    }
+
+    /// Reads the system's configuration from `/etc/resolv.conf` and
+    /// `/etc/hosts`.
+    pub fn system() -> io::Result<Config> {
+        Config::from_files(RESOLV_CONF, HOSTS)
+    }
+
+    /// Reads the configuration from the given `resolv.conf` and hosts files.
+    ///
+    /// Missing files are treated as empty. As with the system's resolver, the
+    /// name server on the local host is used if no name server is configured.
+    pub fn from_files(
+        resolv_conf: impl AsRef<Path>,
+        hosts: impl AsRef<Path>,
+    ) -> io::Result<Config> {
+        let mut config = Config::new();
+        config.parse_resolv_conf(&read_optional(resolv_conf.as_ref())?);
+        config.parse_hosts(&read_optional(hosts.as_ref())?);
+
+        if config.nameservers.is_empty() {
+            let localhost = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 53);
+            config.nameservers.push(localhost);
+        }
+
+        Ok(config)
+    }
+
+    /// Adds a name server to query, in addition to the ones already
+    /// configured.
+    pub fn add_nameserver(&mut self, addr: SocketAddr) -> &mut Self {
+        self.nameservers.push(addr);
+        self
+    }
+
+    /// Adds a domain to the search list, which is used to qualify host names
+    /// with fewer dots than `ndots`.
+    pub fn add_search_domain(&mut self, domain: &str) -> &mut Self {
+        self.search.push(domain.trim_end_matches('.').to_string());
+        self
+    }
+
+    /// Adds an address for `name`, as if it were listed in the hosts file.
+    pub fn add_host(&mut self, name: &str, addr: IpAddr) -> &mut Self {
+        self.hosts
+            .entry(name.trim_end_matches('.').to_lowercase())
+            .or_insert_with(Vec::new)
+            .push(addr);
+        self
+    }
+
+    /// Sets the number of dots a name must contain to be looked up as is,
+    /// before trying the search list.
+    pub fn set_ndots(&mut self, ndots: usize) -> &mut Self {
+        self.ndots = ndots;
+        self
+    }
+
+    /// Sets how long to wait for the answer of a name server.
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many times each name server is queried before giving up.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `attempts` is 0.
+    pub fn set_attempts(&mut self, attempts: usize) -> &mut Self {
+        assert!(attempts > 0, "at least one attempt must be made");
+        self.attempts = attempts;
+        self
+    }
+
+    fn parse_resolv_conf(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let mut words = line.split(|c| c == '#' || c == ';').next().unwrap().split_whitespace();
+
+            match words.next() {
+                Some("nameserver") => {
+                    if let Some(ip) = words.next().and_then(|ip| ip.parse().ok()) {
+                        self.nameservers.push(SocketAddr::new(ip, 53));
+                    }
+                }
+                // The last `search` or `domain` line wins.
+                Some("search") | Some("domain") => {
+                    self.search.clear();
+                    for domain in words {
+                        self.add_search_domain(domain);
+                    }
+                }
+                Some("options") => {
+                    for option in words {
+                        self.parse_option(option);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_option(&mut self, option: &str) {
+        let mut parts = option.splitn(2, ':');
+        let name = parts.next().unwrap();
+        let value = match parts.next().and_then(|value| value.parse().ok()) {
+            Some(value) => value,
+            None => return,
+        };
+
+        match name {
+            "ndots" => self.ndots = value,
+            "timeout" => self.timeout = Duration::from_secs(value as u64),
+            "attempts" if value > 0 => self.attempts = value,
+            _ => {}
+        }
+    }
+
+    fn parse_hosts(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let mut words = line.split('#').next().unwrap().split_whitespace();
+
+            let ip = match words.next().and_then(|ip| ip.parse().ok()) {
+                Some(ip) => ip,
+                None => continue,
+            };
+
+            for name in words {
+                self.add_host(name, ip);
+            }
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config::new()
+    }
+}
+
+fn read_optional(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    #[test]
+    fn parse_resolv_conf() {
+        let mut config = Config::new();
+        config.parse_resolv_conf(
+            "# generated\n\
+             nameserver 10.0.0.53\n\
+             nameserver ::1 ; local\n\
+             nameserver bogus\n\
+             domain example.com\n\
+             search corp.example.com. example.com\n\
+             options ndots:2 timeout:1 attempts:3 rotate\n",
+        );
+
+        assert_eq!(
+            config.nameservers,
+            vec!["10.0.0.53:53".parse().unwrap(), "[::1]:53".parse().unwrap()]
+        );
+        assert_eq!(config.search, vec!["corp.example.com", "example.com"]);
+        assert_eq!(config.ndots, 2);
+        assert_eq!(config.timeout, Duration::from_secs(1));
+        assert_eq!(config.attempts, 3);
+    }
+
+    #[test]
+    fn parse_hosts() {
+        let mut config = Config::new();
+        config.parse_hosts(
+            "127.0.0.1 localhost\n\
+             ::1 localhost ip6-localhost # loopback\n\
+             # 10.0.0.1 commented\n\
+             10.0.0.2 DB.internal db\n",
+        );
+
+        assert_eq!(
+            config.hosts["localhost"],
+            vec!["127.0.0.1".parse().unwrap(), "::1".parse::<IpAddr>().unwrap()]
+        );
+        assert_eq!(config.hosts["db.internal"], vec!["10.0.0.2".parse::<IpAddr>().unwrap()]);
+        assert!(!config.hosts.contains_key("10.0.0.1"));
+    }
+}
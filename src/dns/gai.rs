@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures::channel::oneshot;
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+/// The number of threads of the resolver used by `dns::resolve`.
+const DEFAULT_THREADS: usize = 4;
+
+/// How long a thread waits for a new lookup before exiting.
+const KEEP_ALIVE_SECS: u64 = 10;
+
+lazy_static::lazy_static! {
+    static ref DEFAULT: GaiResolver = GaiResolver::new(DEFAULT_THREADS);
+}
+
+/// A resolver which calls the system's `getaddrinfo` on background threads.
+///
+/// `getaddrinfo` blocks, so lookups are run on a pool of at most
+/// `max_threads` threads, which are started when lookups are made and exit
+/// after being idle for a few seconds. Lookups which are made while all
+/// threads are busy are queued.
+///
+/// Cloning a `GaiResolver` returns a handle to the same pool.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::dns::GaiResolver;
+///
+/// # async fn lookup() -> std::io::Result<()> {
+/// let resolver = GaiResolver::new(8);
+/// let addrs = await!(resolver.lookup("db.internal", 5432))?;
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct GaiResolver {
+    pool: Arc<Pool>,
+}
+
+/// The future returned by `GaiResolver::lookup`, which resolves to the socket
+/// addresses of a host.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct GaiLookup {
+    rx: oneshot::Receiver<io::Result<Vec<SocketAddr>>>,
+}
+
+struct Pool {
+    state: Mutex<State>,
+
+    /// Signaled when a lookup is queued.
+    condvar: Condvar,
+
+    max_threads: usize,
+}
+
+struct State {
+    queue: VecDeque<Job>,
+
+    /// The number of running threads.
+    threads: usize,
+
+    /// The number of threads waiting for a lookup.
+    idle: usize,
+}
+
+struct Job {
+    host: String,
+    port: u16,
+    tx: oneshot::Sender<io::Result<Vec<SocketAddr>>>,
+}
+
+/// Looks `host` up with the resolver used by `dns::resolve`.
+pub(crate) fn lookup(host: &str, port: u16) -> GaiLookup {
+    DEFAULT.lookup(host, port)
+}
+
+impl GaiResolver {
+    /// Creates a resolver running lookups on at most `max_threads` threads.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max_threads` is 0.
+    pub fn new(max_threads: usize) -> GaiResolver {
+        assert!(max_threads > 0, "a resolver must have at least one thread");
+
+        GaiResolver {
+            pool: Arc::new(Pool {
+                state: Mutex::new(State {
+                    queue: VecDeque::new(),
+                    threads: 0,
+                    idle: 0,
+                }),
+                condvar: Condvar::new(),
+                max_threads,
+            }),
+        }
+    }
+
+    /// Resolves `host` to socket addresses with the given port.
+    pub fn lookup(&self, host: &str, port: u16) -> GaiLookup {
+        let (tx, rx) = oneshot::channel();
+        let job = Job {
+            host: host.to_string(),
+            port,
+            tx,
+        };
+
+        let mut state = self.pool.state.lock().unwrap();
+        state.queue.push_back(job);
+
+        if state.idle > 0 {
+            self.pool.condvar.notify_one();
+        } else if state.threads < self.pool.max_threads {
+            state.threads += 1;
+
+            let pool = self.pool.clone();
+            let spawned = thread::Builder::new()
+                .name("romio-resolver".to_string())
+                .spawn(move || pool.run());
+
+            if let Err(e) = spawned {
+                state.threads -= 1;
+
+                // Fail the lookup right away if no thread will ever run it.
+                if state.threads == 0 {
+                    let job = state.queue.pop_back().unwrap();
+                    drop(job.tx.send(Err(e)));
+                }
+            }
+        }
+
+        GaiLookup { rx }
+    }
+}
+
+impl fmt::Debug for GaiResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GaiResolver")
+            .field("max_threads", &self.pool.max_threads)
+            .finish()
+    }
+}
+
+impl Pool {
+    fn run(&self) {
+        let keep_alive = Duration::from_secs(KEEP_ALIVE_SECS);
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            let job = match state.queue.pop_front() {
+                Some(job) => job,
+                None => {
+                    state.idle += 1;
+                    let (guard, timeout) = self.condvar.wait_timeout(state, keep_alive).unwrap();
+                    state = guard;
+                    state.idle -= 1;
+
+                    if timeout.timed_out() && state.queue.is_empty() {
+                        state.threads -= 1;
+                        return;
+                    }
+                    continue;
+                }
+            };
+
+            drop(state);
+
+            // The lookup isn't needed anymore if the future has been dropped.
+            if !job.tx.is_canceled() {
+                let addrs = (&job.host[..], job.port)
+                    .to_socket_addrs()
+                    .map(|addrs| addrs.collect());
+                drop(job.tx.send(addrs));
+            }
+
+            state = self.state.lock().unwrap();
+        }
+    }
+}
+
+impl Future for GaiLookup {
+    type Output = io::Result<Vec<SocketAddr>>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        match Pin::new(&mut self.rx).poll(lw) {
+            Poll::Ready(Ok(res)) => Poll::Ready(res),
+            Poll::Ready(Err(oneshot::Canceled)) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                "resolver thread panicked",
+            ))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Asynchronous host name resolution.
+//!
+//! This module resolves host names to socket addresses without blocking the
+//! task which needs them. Two resolvers are provided:
+//!
+//! - [`GaiResolver`] calls the system's `getaddrinfo` on a bounded pool of
+//!   background threads, so it honors the system configuration, such as
+//!   `nsswitch.conf`, exactly like the blocking `std::net` functions do.
+//! - [`DnsResolver`] is a pure-Rust resolver, which looks names up in the
+//!   hosts file and sends DNS queries over a [`UdpSocket`] to the name servers
+//!   listed in `resolv.conf`, or in a [`Config`] built by hand.
+//!
+//! Functions which connect to an address, like [`TcpStream::connect`], take an
+//! implementation of [`ToSocketAddrs`], which can be a host name. Host names
+//! are resolved with a default [`GaiResolver`], as is done by [`resolve`].
+//!
+//! [`GaiResolver`]: struct.GaiResolver.html
+//! [`DnsResolver`]: struct.DnsResolver.html
+//! [`Config`]: struct.Config.html
+//! [`UdpSocket`]: ../udp/struct.UdpSocket.html
+//! [`TcpStream::connect`]: ../tcp/struct.TcpStream.html#method.connect
+//! [`ToSocketAddrs`]: trait.ToSocketAddrs.html
+//! [`resolve`]: fn.resolve.html
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! #![feature(async_await, await_macro, futures_api)]
+//! use romio::dns::{Config, DnsResolver};
+//! use romio::tcp::TcpStream;
+//!
+//! # async fn connect() -> std::io::Result<TcpStream> {
+//! // connect using the system resolver
+//! let stream = await!(TcpStream::connect("db.internal:5432"))?;
+//!
+//! // or resolve the name with the DNS resolver first
+//! let resolver = DnsResolver::new(Config::system()?);
+//! let addrs = await!(resolver.lookup("db.internal", 5432))?;
+//! let stream = await!(TcpStream::connect(&addrs[..]))?;
+//! # Ok(stream)}
+//! ```
+
+mod config;
+mod gai;
+mod proto;
+mod resolver;
+
+pub use self::config::Config;
+pub use self::gai::{GaiLookup, GaiResolver};
+pub use self::resolver::{DnsLookup, DnsResolver};
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::pin::Pin;
+
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+use self::sealed::Target;
+
+/// A value which can be resolved to one or more socket addresses.
+///
+/// This is the asynchronous counterpart of [`std::net::ToSocketAddrs`], and is
+/// implemented for the same types: socket and IP addresses, and strings
+/// containing either an address or a host name and a port, such as
+/// `"db.internal:5432"`. Host names are resolved without blocking.
+///
+/// This trait is sealed and cannot be implemented outside of romio.
+///
+/// [`std::net::ToSocketAddrs`]: https://doc.rust-lang.org/std/net/trait.ToSocketAddrs.html
+pub trait ToSocketAddrs: sealed::ToSocketAddrsPriv {}
+
+/// The future returned by [`resolve`], which resolves to the socket addresses
+/// of a host.
+///
+/// [`resolve`]: fn.resolve.html
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Resolve {
+    inner: ResolveState,
+}
+
+#[derive(Debug)]
+enum ResolveState {
+    Ready(Option<io::Result<Vec<SocketAddr>>>),
+    Lookup(GaiLookup),
+}
+
+/// Resolves `addr` to socket addresses, using the system's resolver.
+///
+/// Host names are looked up by `getaddrinfo` on a shared pool of background
+/// threads; see [`GaiResolver`] for details.
+///
+/// [`GaiResolver`]: struct.GaiResolver.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::dns;
+///
+/// # async fn lookup() -> std::io::Result<()> {
+/// for addr in await!(dns::resolve("rust-lang.org:443"))? {
+///     println!("{}", addr);
+/// }
+/// # Ok(())}
+/// ```
+pub fn resolve(addr: impl ToSocketAddrs) -> Resolve {
+    let inner = match addr.to_target() {
+        Ok(Target::Addrs(addrs)) => ResolveState::Ready(Some(Ok(addrs))),
+        Ok(Target::Host(host, port)) => ResolveState::Lookup(gai::lookup(&host, port)),
+        Err(e) => ResolveState::Ready(Some(Err(e))),
+    };
+
+    Resolve { inner }
+}
+
+impl Future for Resolve {
+    type Output = io::Result<Vec<SocketAddr>>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        match self.inner {
+            ResolveState::Ready(ref mut res) => {
+                Poll::Ready(res.take().expect("cannot poll Resolve twice"))
+            }
+            ResolveState::Lookup(ref mut lookup) => Pin::new(lookup).poll(lw),
+        }
+    }
+}
+
+/// Splits a `host:port` string, returning an error if the port is missing.
+fn split_host_port(s: &str) -> io::Result<(&str, u16)> {
+    let mut parts = s.rsplitn(2, ':');
+    let port = parts.next().and_then(|port| port.parse().ok());
+
+    match (parts.next(), port) {
+        (Some(host), Some(port)) => Ok((host, port)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid socket address",
+        )),
+    }
+}
+
+pub(crate) mod sealed {
+    use std::io;
+    use std::net::SocketAddr;
+
+    /// The addresses an implementation of `ToSocketAddrs` refers to.
+    #[derive(Debug)]
+    pub enum Target {
+        /// Addresses which don't need to be resolved.
+        Addrs(Vec<SocketAddr>),
+
+        /// A host name to resolve, with the port to use.
+        Host(String, u16),
+    }
+
+    pub trait ToSocketAddrsPriv {
+        fn to_target(&self) -> io::Result<Target>;
+    }
+}
+
+use self::sealed::ToSocketAddrsPriv;
+
+macro_rules! to_socket_addrs {
+    ($($ty:ty => |$addr:ident| $conv:expr;)*) => {
+        $(
+            impl ToSocketAddrs for $ty {}
+
+            impl ToSocketAddrsPriv for $ty {
+                fn to_target(&self) -> io::Result<Target> {
+                    let $addr = self;
+                    Ok(Target::Addrs(vec![$conv]))
+                }
+            }
+        )*
+    };
+}
+
+to_socket_addrs! {
+    SocketAddr => |addr| *addr;
+    SocketAddrV4 => |addr| SocketAddr::V4(*addr);
+    SocketAddrV6 => |addr| SocketAddr::V6(*addr);
+    (IpAddr, u16) => |addr| SocketAddr::new(addr.0, addr.1);
+    (Ipv4Addr, u16) => |addr| SocketAddr::new(IpAddr::V4(addr.0), addr.1);
+    (Ipv6Addr, u16) => |addr| SocketAddr::new(IpAddr::V6(addr.0), addr.1);
+}
+
+impl ToSocketAddrs for [SocketAddr] {}
+
+impl ToSocketAddrsPriv for [SocketAddr] {
+    fn to_target(&self) -> io::Result<Target> {
+        Ok(Target::Addrs(self.to_vec()))
+    }
+}
+
+impl ToSocketAddrs for str {}
+
+impl ToSocketAddrsPriv for str {
+    fn to_target(&self) -> io::Result<Target> {
+        if let Ok(addr) = self.parse() {
+            return Ok(Target::Addrs(vec![addr]));
+        }
+
+        let (host, port) = split_host_port(self)?;
+        (host, port).to_target()
+    }
+}
+
+impl<'a> ToSocketAddrs for (&'a str, u16) {}
+
+impl<'a> ToSocketAddrsPriv for (&'a str, u16) {
+    fn to_target(&self) -> io::Result<Target> {
+        let (host, port) = *self;
+
+        match host.parse() {
+            Ok(ip) => Ok(Target::Addrs(vec![SocketAddr::new(ip, port)])),
+            Err(_) => Ok(Target::Host(host.to_string(), port)),
+        }
+    }
+}
+
+impl ToSocketAddrs for String {}
+
+impl ToSocketAddrsPriv for String {
+    fn to_target(&self) -> io::Result<Target> {
+        self[..].to_target()
+    }
+}
+
+impl<'a, T: ToSocketAddrs + ?Sized> ToSocketAddrs for &'a T {}
+
+impl<'a, T: ToSocketAddrs + ?Sized> ToSocketAddrsPriv for &'a T {
+    fn to_target(&self) -> io::Result<Target> {
+        (**self).to_target()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::sealed::{Target, ToSocketAddrsPriv};
+
+    fn target(addr: impl ToSocketAddrsPriv) -> String {
+        format!("{:?}", addr.to_target().unwrap())
+    }
+
+    #[test]
+    fn to_target() {
+        assert_eq!(target("127.0.0.1:80"), "Addrs([V4(127.0.0.1:80)])");
+        assert_eq!(target("[::1]:80"), "Addrs([V6([::1]:80)])");
+        assert_eq!(target("db.internal:5432"), r#"Host("db.internal", 5432)"#);
+        assert_eq!(target(("10.0.0.1", 22)), "Addrs([V4(10.0.0.1:22)])");
+        assert!("db.internal".to_target().is_err());
+
+        match "localhost:80".to_string().to_target().unwrap() {
+            Target::Host(ref host, 80) => assert_eq!(host, "localhost"),
+            target => panic!("unexpected target: {:?}", target),
+        }
+    }
+}
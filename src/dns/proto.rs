@@ -0,0 +1,206 @@
+//! Encoding of DNS queries and decoding of their responses (RFC 1035).
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub(super) const TYPE_A: u16 = 1;
+pub(super) const TYPE_AAAA: u16 = 28;
+
+const CLASS_IN: u16 = 1;
+
+/// Set in the header of queries which ask for recursion.
+const FLAG_RD: u16 = 0x0100;
+
+/// Set in the header of responses.
+const FLAG_QR: u16 = 0x8000;
+
+const HEADER_LEN: usize = 12;
+
+/// Response codes which matter to the resolver.
+pub(super) const RCODE_NOERROR: u8 = 0;
+pub(super) const RCODE_NXDOMAIN: u8 = 3;
+
+/// The useful part of a response.
+#[derive(Debug)]
+pub(super) struct Response {
+    pub id: u16,
+    pub rcode: u8,
+
+    /// The addresses of the answer section, of the queried type.
+    pub addrs: Vec<IpAddr>,
+}
+
+/// Builds a query for the records of type `qtype` of `name`.
+pub(super) fn query(id: u16, name: &str, qtype: u16) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(HEADER_LEN + name.len() + 6);
+
+    put_u16(&mut buf, id);
+    put_u16(&mut buf, FLAG_RD);
+    put_u16(&mut buf, 1); // QDCOUNT
+    put_u16(&mut buf, 0); // ANCOUNT
+    put_u16(&mut buf, 0); // NSCOUNT
+    put_u16(&mut buf, 0); // ARCOUNT
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(invalid_name());
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+
+    // The encoded name must fit in 255 bytes.
+    if buf.len() - HEADER_LEN > 255 {
+        return Err(invalid_name());
+    }
+
+    put_u16(&mut buf, qtype);
+    put_u16(&mut buf, CLASS_IN);
+    Ok(buf)
+}
+
+/// Parses the response to a query of type `qtype`.
+pub(super) fn parse(buf: &[u8], qtype: u16) -> io::Result<Response> {
+    let mut r = Reader { buf, pos: 0 };
+
+    let id = r.u16()?;
+    let flags = r.u16()?;
+    let qdcount = r.u16()?;
+    let ancount = r.u16()?;
+    r.skip(4)?; // NSCOUNT, ARCOUNT
+
+    if flags & FLAG_QR == 0 {
+        return Err(invalid_response());
+    }
+
+    for _ in 0..qdcount {
+        r.skip_name()?;
+        r.skip(4)?; // QTYPE, QCLASS
+    }
+
+    let mut addrs = Vec::new();
+    for _ in 0..ancount {
+        r.skip_name()?;
+        let rtype = r.u16()?;
+        let class = r.u16()?;
+        r.skip(4)?; // TTL
+        let len = r.u16()? as usize;
+        let data = r.take(len)?;
+
+        if rtype != qtype || class != CLASS_IN {
+            continue;
+        }
+
+        match (rtype, data.len()) {
+            (TYPE_A, 4) => {
+                let ip = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+                addrs.push(IpAddr::V4(ip));
+            }
+            (TYPE_AAAA, 16) => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(data);
+                addrs.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => return Err(invalid_response()),
+        }
+    }
+
+    Ok(Response {
+        id,
+        rcode: (flags & 0xf) as u8,
+        addrs,
+    })
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() - self.pos < n {
+            return Err(invalid_response());
+        }
+        let ret = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(ret)
+    }
+
+    fn skip(&mut self, n: usize) -> io::Result<()> {
+        self.take(n).map(drop)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        self.take(2).map(|b| u16::from(b[0]) << 8 | u16::from(b[1]))
+    }
+
+    /// Skips a possibly compressed name.
+    fn skip_name(&mut self) -> io::Result<()> {
+        loop {
+            match self.u8()? {
+                0 => return Ok(()),
+                // A pointer ends the name.
+                len if len & 0xc0 == 0xc0 => return self.skip(1),
+                len if len & 0xc0 == 0 => self.skip(len as usize)?,
+                _ => return Err(invalid_response()),
+            }
+        }
+    }
+}
+
+fn put_u16(buf: &mut Vec<u8>, n: u16) {
+    buf.push((n >> 8) as u8);
+    buf.push(n as u8);
+}
+
+fn invalid_name() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "invalid host name")
+}
+
+fn invalid_response() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid DNS response")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_query() {
+        let buf = query(0x1234, "db.internal.", TYPE_AAAA).unwrap();
+        assert_eq!(
+            buf,
+            b"\x12\x34\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00\
+              \x02db\x08internal\x00\x00\x1c\x00\x01"
+                .to_vec()
+        );
+
+        assert!(query(1, "a..b", TYPE_A).is_err());
+        assert!(query(1, &"a".repeat(64), TYPE_A).is_err());
+    }
+
+    #[test]
+    fn parse_response() {
+        let mut buf = query(7, "db.internal", TYPE_A).unwrap();
+        buf[2] |= 0x80; // QR
+        buf[7] = 2; // ANCOUNT
+
+        // a CNAME, then an A record, both using a pointer to the question
+        buf.extend_from_slice(b"\xc0\x0c\x00\x05\x00\x01\x00\x00\x00\x3c\x00\x02\xc0\x0c");
+        buf.extend_from_slice(b"\xc0\x0c\x00\x01\x00\x01\x00\x00\x00\x3c\x00\x04\x0a\x00\x00\x01");
+
+        let response = parse(&buf, TYPE_A).unwrap();
+        assert_eq!(response.id, 7);
+        assert_eq!(response.rcode, RCODE_NOERROR);
+        assert_eq!(response.addrs, vec!["10.0.0.1".parse::<IpAddr>().unwrap()]);
+
+        // truncated
+        assert!(parse(&buf[..buf.len() - 1], TYPE_A).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use super::proto::{self, RCODE_NOERROR, RCODE_NXDOMAIN, TYPE_A, TYPE_AAAA};
+use super::Config;
+
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+use crate::reactor::Handle;
+use crate::timer::{self, Delay};
+use crate::udp::UdpSocket;
+
+/// The size of the buffer responses are received in, which is the largest
+/// response sent over UDP to queries without EDNS.
+const MAX_RESPONSE_LEN: usize = 512;
+
+/// A pure-Rust DNS resolver, sending queries over UDP.
+///
+/// A lookup first checks whether the host is listed in the hosts file of the
+/// resolver's [`Config`]. Otherwise, it queries the configured name servers for
+/// both the IPv6 and IPv4 addresses of the host, qualifying it with the search
+/// list as the system's resolver does. Each name server is queried in turn,
+/// until one of them answers within the configured timeout, and this is
+/// repeated for the configured number of attempts.
+///
+/// IPv6 addresses are returned first. Truncated responses are used as they are,
+/// without retrying the query over TCP.
+///
+/// [`Config`]: struct.Config.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::dns::DnsResolver;
+///
+/// # async fn lookup() -> std::io::Result<()> {
+/// let resolver = DnsResolver::system()?;
+/// for addr in await!(resolver.lookup("db.internal", 5432))? {
+///     println!("{}", addr);
+/// }
+/// # Ok(())}
+/// ```
+#[derive(Clone, Debug)]
+pub struct DnsResolver {
+    config: Arc<Config>,
+    handle: Handle,
+}
+
+/// The future returned by `DnsResolver::lookup`, which resolves to the socket
+/// addresses of a host.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct DnsLookup {
+    config: Arc<Config>,
+    handle: Handle,
+    port: u16,
+
+    /// The result of a lookup which doesn't need any query.
+    done: Option<io::Result<Vec<SocketAddr>>>,
+
+    /// The name currently looked up, and the ones to try after it.
+    name: Option<String>,
+    names: VecDeque<String>,
+
+    /// The number of queries made for the current name.
+    tries: usize,
+
+    query: Option<Query>,
+
+    /// The error of the last failed query.
+    error: Option<io::Error>,
+}
+
+/// The queries for the addresses of a name, sent to one name server.
+#[derive(Debug)]
+struct Query {
+    socket: UdpSocket,
+    server: SocketAddr,
+
+    /// Queries which have not been sent yet.
+    unsent: Vec<Vec<u8>>,
+
+    /// The ID and type of the queries which have not been answered yet.
+    pending: Vec<(u16, u16)>,
+
+    addrs: Vec<IpAddr>,
+    timeout: Delay,
+}
+
+/// How a query completed.
+enum Outcome {
+    Found(Vec<IpAddr>),
+
+    /// The name doesn't exist, or has no addresses.
+    NotFound,
+
+    /// The name server couldn't answer the query.
+    Failed(io::Error),
+}
+
+impl DnsResolver {
+    /// Creates a resolver with the given configuration.
+    ///
+    /// Its sockets and timers bind lazily to the default reactor of the task
+    /// making a lookup.
+    pub fn new(config: Config) -> DnsResolver {
+        DnsResolver::new_with_handle(config, &Handle::default())
+    }
+
+    /// Creates a resolver with the given configuration, whose sockets and
+    /// timers are driven by the reactor referenced by `handle`.
+    pub fn new_with_handle(config: Config, handle: &Handle) -> DnsResolver {
+        DnsResolver {
+            config: Arc::new(config),
+            handle: handle.clone(),
+        }
+    }
+
+    /// Creates a resolver with the system's configuration.
+    ///
+    /// See [`Config::system`] for details.
+    ///
+    /// [`Config::system`]: struct.Config.html#method.system
+    pub fn system() -> io::Result<DnsResolver> {
+        Ok(DnsResolver::new(Config::system()?))
+    }
+
+    /// Resolves `host` to socket addresses with the given port.
+    pub fn lookup(&self, host: &str, port: u16) -> DnsLookup {
+        let mut lookup = DnsLookup {
+            config: self.config.clone(),
+            handle: self.handle.clone(),
+            port,
+            done: None,
+            name: None,
+            names: VecDeque::new(),
+            tries: 0,
+            query: None,
+            error: None,
+        };
+
+        let name = host.trim_end_matches('.');
+        let hosts = self.config.hosts.get(&name.to_lowercase());
+
+        if let Ok(ip) = name.parse() {
+            lookup.done = Some(Ok(vec![SocketAddr::new(ip, port)]));
+        } else if let Some(ips) = hosts {
+            let addrs = ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect();
+            lookup.done = Some(Ok(addrs));
+        } else {
+            lookup.names = candidates(host, &self.config);
+            lookup.name = lookup.names.pop_front();
+        }
+
+        lookup
+    }
+}
+
+impl DnsLookup {
+    /// Starts querying the next name server for the current name, returning
+    /// `None` once all attempts have been made.
+    fn next_query(&mut self) -> io::Result<Option<Query>> {
+        let name = match self.name {
+            Some(ref name) => name,
+            None => return Ok(None),
+        };
+
+        let servers = &self.config.nameservers;
+        if self.tries >= servers.len() * self.config.attempts {
+            return Ok(None);
+        }
+
+        let server = servers[self.tries % servers.len()];
+        self.tries += 1;
+
+        let query = Query::new(name, server, &self.config, &self.handle)?;
+        Ok(Some(query))
+    }
+}
+
+impl Future for DnsLookup {
+    type Output = io::Result<Vec<SocketAddr>>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if let Some(res) = this.done.take() {
+            return Poll::Ready(res);
+        }
+
+        loop {
+            if this.query.is_none() {
+                this.query = match this.next_query()? {
+                    Some(query) => Some(query),
+                    None => {
+                        let err = this.error.take().unwrap_or_else(|| {
+                            io::Error::new(io::ErrorKind::NotFound, "no addresses found for host")
+                        });
+                        return Poll::Ready(Err(err));
+                    }
+                };
+            }
+
+            let outcome = match this.query.as_mut().unwrap().poll(lw) {
+                Poll::Ready(outcome) => outcome,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.query = None;
+
+            match outcome {
+                Outcome::Found(ips) => {
+                    let port = this.port;
+                    let addrs = ips.into_iter().map(|ip| SocketAddr::new(ip, port));
+                    return Poll::Ready(Ok(addrs.collect()));
+                }
+                Outcome::NotFound => {
+                    this.name = this.names.pop_front();
+                    this.tries = 0;
+                    this.error = None;
+                }
+                Outcome::Failed(e) => this.error = Some(e),
+            }
+        }
+    }
+}
+
+impl Query {
+    fn new(name: &str, server: SocketAddr, config: &Config, handle: &Handle) -> io::Result<Query> {
+        let local = match server {
+            SocketAddr::V4(..) => SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            SocketAddr::V6(..) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+        };
+        let socket = UdpSocket::bind_with_handle(&local, handle)?;
+
+        let id = random_id();
+        let pending = vec![(id, TYPE_AAAA), (id.wrapping_add(1), TYPE_A)];
+        let unsent = pending
+            .iter()
+            .map(|&(id, qtype)| proto::query(id, name, qtype))
+            .collect::<io::Result<_>>()?;
+
+        let deadline = timer::now_with_handle(handle) + config.timeout;
+
+        Ok(Query {
+            socket,
+            server,
+            unsent,
+            pending,
+            addrs: Vec::new(),
+            timeout: Delay::new_with_handle(deadline, handle),
+        })
+    }
+
+    fn poll(&mut self, lw: &LocalWaker) -> Poll<Outcome> {
+        while let Some(packet) = self.unsent.pop() {
+            match self.socket.poll_send_to(lw, &packet, &self.server) {
+                Poll::Ready(Ok(_)) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Outcome::Failed(e)),
+                Poll::Pending => {
+                    self.unsent.push(packet);
+                    break;
+                }
+            }
+        }
+
+        let mut buf = [0; MAX_RESPONSE_LEN];
+        loop {
+            match self.socket.poll_recv_from(lw, &mut buf) {
+                Poll::Ready(Ok((n, from))) => {
+                    if from != self.server {
+                        continue;
+                    }
+                    if let Some(outcome) = self.on_response(&buf[..n]) {
+                        return Poll::Ready(outcome);
+                    }
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Outcome::Failed(e)),
+                Poll::Pending => break,
+            }
+        }
+
+        match Pin::new(&mut self.timeout).poll(lw) {
+            Poll::Ready(()) => Poll::Ready(Outcome::Failed(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "name server did not answer in time",
+            ))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    /// Handles a response, returning the outcome of the queries once it is
+    /// known. Responses which don't match a pending query are ignored.
+    fn on_response(&mut self, buf: &[u8]) -> Option<Outcome> {
+        if buf.len() < 2 {
+            return None;
+        }
+        let id = u16::from(buf[0]) << 8 | u16::from(buf[1]);
+
+        let i = self.pending.iter().position(|&(pending, _)| pending == id)?;
+        let response = proto::parse(buf, self.pending[i].1).ok()?;
+        self.pending.remove(i);
+
+        match response.rcode {
+            RCODE_NOERROR => self.addrs.extend(response.addrs),
+            RCODE_NXDOMAIN => return Some(Outcome::NotFound),
+            rcode => {
+                let msg = format!("name server failed with response code {}", rcode);
+                return Some(Outcome::Failed(io::Error::new(io::ErrorKind::Other, msg)));
+            }
+        }
+
+        if !self.pending.is_empty() {
+            None
+        } else if self.addrs.is_empty() {
+            Some(Outcome::NotFound)
+        } else {
+            // Return IPv6 addresses first, keeping the order of the answers.
+            let mut addrs = self.addrs.split_off(0);
+            addrs.sort_by_key(IpAddr::is_ipv4);
+            Some(Outcome::Found(addrs))
+        }
+    }
+}
+
+/// Returns the names to query for `host`, qualified with the search list as
+/// described in `resolv.conf(5)`.
+fn candidates(host: &str, config: &Config) -> VecDeque<String> {
+    let mut names = VecDeque::new();
+
+    if host.ends_with('.') {
+        names.push_back(host.trim_end_matches('.').to_string());
+        return names;
+    }
+
+    names.extend(config.search.iter().map(|domain| format!("{}.{}", host, domain)));
+
+    if host.matches('.').count() >= config.ndots {
+        names.push_front(host.to_string());
+    } else {
+        names.push_back(host.to_string());
+    }
+
+    names
+}
+
+/// Returns a random query ID, which together with the random source port
+/// makes it harder to spoof responses.
+fn random_id() -> u16 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u8(0);
+    hasher.finish() as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::{candidates, Config};
+
+    #[test]
+    fn search_list() {
+        let mut config = Config::new();
+        config.add_search_domain("a.example").add_search_domain("b.example");
+
+        assert_eq!(candidates("db", &config), vec!["db.a.example", "db.b.example", "db"]);
+        assert_eq!(
+            candidates("db.internal", &config),
+            vec!["db.internal", "db.internal.a.example", "db.internal.b.example"]
+        );
+        assert_eq!(candidates("db.", &config), vec!["db"]);
+    }
+}
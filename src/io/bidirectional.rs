@@ -0,0 +1,265 @@
+use super::{poll_shutdown, BUF_SIZE};
+
+use std::any::Any;
+use std::io;
+use std::pin::Pin;
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll};
+
+#[cfg(target_os = "linux")]
+use super::{raw_stream, sys, RawStream};
+
+/// The future returned by [`copy_bidirectional`].
+///
+/// [`copy_bidirectional`]: fn.copy_bidirectional.html
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct CopyBidirectional<'a, A, B> {
+    a: &'a mut A,
+    b: &'a mut B,
+    a_to_b: Transfer,
+    b_to_a: Transfer,
+}
+
+/// The state of the copy in one direction.
+#[derive(Debug)]
+struct Transfer {
+    /// The pipe data is spliced through, created on first use.
+    #[cfg(target_os = "linux")]
+    pipe: Option<sys::Pipe>,
+
+    /// The number of bytes in the pipe.
+    #[cfg(target_os = "linux")]
+    pending: usize,
+
+    /// Whether the data is copied through `buf` rather than spliced.
+    buffered: bool,
+
+    buf: Vec<u8>,
+    pos: usize,
+    cap: usize,
+
+    read_done: bool,
+    done: bool,
+    amount: u64,
+}
+
+/// Copies data in both directions between `a` and `b`, until both have
+/// reached the end of their stream.
+///
+/// When one of them reaches the end of its stream, the other one is shut down
+/// for writing, while data keeps flowing in the other direction. The returned
+/// future resolves to the number of bytes copied from `a` to `b`, and from `b`
+/// to `a`.
+///
+/// On Linux, data is moved between romio streams with `splice(2)`, without
+/// being copied through userspace. Other I/O objects are copied through a
+/// buffer.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::io::copy_bidirectional;
+/// use romio::tcp::TcpStream;
+///
+/// # async fn proxy(mut client: TcpStream) -> std::io::Result<()> {
+/// let mut server = await!(TcpStream::connect("127.0.0.1:8080"))?;
+/// let (sent, received) = await!(copy_bidirectional(&mut client, &mut server))?;
+/// println!("sent {} bytes, received {} bytes", sent, received);
+/// # Ok(())}
+/// ```
+pub fn copy_bidirectional<'a, A, B>(a: &'a mut A, b: &'a mut B) -> CopyBidirectional<'a, A, B>
+where
+    A: AsyncRead + AsyncWrite + Any,
+    B: AsyncRead + AsyncWrite + Any,
+{
+    CopyBidirectional {
+        a,
+        b,
+        a_to_b: Transfer::new(),
+        b_to_a: Transfer::new(),
+    }
+}
+
+impl<'a, A, B> Future for CopyBidirectional<'a, A, B>
+where
+    A: AsyncRead + AsyncWrite + Any,
+    B: AsyncRead + AsyncWrite + Any,
+{
+    type Output = io::Result<(u64, u64)>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        let a_to_b = this.a_to_b.poll_transfer(lw, this.a, this.b)?;
+        let b_to_a = this.b_to_a.poll_transfer(lw, this.b, this.a)?;
+
+        match (a_to_b, b_to_a) {
+            (Poll::Ready(a_to_b), Poll::Ready(b_to_a)) => Poll::Ready(Ok((a_to_b, b_to_a))),
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl Transfer {
+    fn new() -> Transfer {
+        Transfer {
+            #[cfg(target_os = "linux")]
+            pipe: None,
+            #[cfg(target_os = "linux")]
+            pending: 0,
+            buffered: !cfg!(target_os = "linux"),
+            buf: Vec::new(),
+            pos: 0,
+            cap: 0,
+            read_done: false,
+            done: false,
+            amount: 0,
+        }
+    }
+
+    fn poll_transfer<R, W>(
+        &mut self,
+        lw: &LocalWaker,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Poll<io::Result<u64>>
+    where
+        R: AsyncRead + Any,
+        W: AsyncWrite + Any,
+    {
+        if self.done {
+            return Poll::Ready(Ok(self.amount));
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            if !self.buffered {
+                if let (Some(src), Some(dst)) = (raw_stream(reader), raw_stream(writer)) {
+                    match self.poll_splice(lw, src, dst) {
+                        Poll::Ready(Err(ref e))
+                            if sys::is_unsupported(e) && self.amount == 0 && self.pending == 0 => {}
+                        Poll::Ready(Ok(())) => return self.poll_finish(lw, writer),
+                        poll => return poll.map(|res| res.map(|()| self.amount)),
+                    }
+                }
+                self.buffered = true;
+            }
+        }
+
+        self.poll_copy(lw, reader, writer)
+    }
+
+    /// Splices data from `src` to `dst` until `src` reaches the end of its
+    /// stream.
+    #[cfg(target_os = "linux")]
+    fn poll_splice(
+        &mut self,
+        lw: &LocalWaker,
+        src: &dyn RawStream,
+        dst: &dyn RawStream,
+    ) -> Poll<io::Result<()>> {
+        /// The capacity of a pipe, by default.
+        const PIPE_SIZE: usize = 64 * 1024;
+
+        if self.pipe.is_none() {
+            self.pipe = Some(sys::Pipe::new()?);
+        }
+        let pipe = self.pipe.as_ref().unwrap();
+
+        loop {
+            // Only fill the pipe once it's empty, so that `EAGAIN` means that
+            // `src` has no data rather than that the pipe is full.
+            if self.pending == 0 {
+                if self.read_done {
+                    return Poll::Ready(Ok(()));
+                }
+
+                ready!(src.poll_read_ready(lw)?);
+                match sys::splice(src.as_raw_fd(), pipe.write, PIPE_SIZE) {
+                    Ok(0) => self.read_done = true,
+                    Ok(n) => self.pending = n,
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        src.clear_read_ready(lw)?;
+                        return Poll::Pending;
+                    }
+                    Err(e) => return Poll::Ready(Err(e)),
+                }
+            }
+
+            while self.pending > 0 {
+                ready!(dst.poll_write_ready(lw)?);
+                match sys::splice(pipe.read, dst.as_raw_fd(), self.pending) {
+                    Ok(n) => {
+                        self.pending -= n;
+                        self.amount += n as u64;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        dst.clear_write_ready(lw)?;
+                        return Poll::Pending;
+                    }
+                    Err(e) => return Poll::Ready(Err(e)),
+                }
+            }
+        }
+    }
+
+    /// Copies data from `reader` to `writer` through a buffer, until `reader`
+    /// reaches the end of its stream.
+    fn poll_copy<R, W>(
+        &mut self,
+        lw: &LocalWaker,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Poll<io::Result<u64>>
+    where
+        R: AsyncRead + Any,
+        W: AsyncWrite + Any,
+    {
+        if self.buf.is_empty() {
+            self.buf = vec![0; BUF_SIZE];
+        }
+
+        loop {
+            if self.pos == self.cap && !self.read_done {
+                let n = ready!(reader.poll_read(lw, &mut self.buf))?;
+                if n == 0 {
+                    self.read_done = true;
+                } else {
+                    self.pos = 0;
+                    self.cap = n;
+                }
+            }
+
+            while self.pos < self.cap {
+                let n = ready!(writer.poll_write(lw, &self.buf[self.pos..self.cap]))?;
+                if n == 0 {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "write zero byte into writer",
+                    )));
+                }
+                self.pos += n;
+                self.amount += n as u64;
+            }
+
+            if self.read_done {
+                ready!(writer.poll_flush(lw))?;
+                return self.poll_finish(lw, writer);
+            }
+        }
+    }
+
+    /// Shuts `writer` down once all the data has been written to it.
+    fn poll_finish<W>(&mut self, lw: &LocalWaker, writer: &mut W) -> Poll<io::Result<u64>>
+    where
+        W: AsyncWrite + Any,
+    {
+        ready!(poll_shutdown(writer, lw))?;
+        self.done = true;
+        Poll::Ready(Ok(self.amount))
+    }
+}
@@ -0,0 +1,158 @@
+use super::BUF_SIZE;
+
+use std::any::Any;
+use std::fs::File;
+use std::io::{self, Read};
+use std::pin::Pin;
+
+use futures::io::AsyncWrite;
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll};
+
+#[cfg(target_os = "linux")]
+use super::{raw_stream, sys, RawStream};
+#[cfg(target_os = "linux")]
+use std::os::unix::io::AsRawFd;
+
+/// The future returned by [`copy_file`].
+///
+/// [`copy_file`]: fn.copy_file.html
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct CopyFile<'a, W> {
+    file: &'a mut File,
+    writer: &'a mut W,
+    buffered: bool,
+    buf: Vec<u8>,
+    pos: usize,
+    cap: usize,
+    amount: u64,
+}
+
+/// Writes the contents of `file` to `writer`, from the current position of
+/// the file until its end.
+///
+/// The returned future resolves to the number of bytes written. The writer
+/// is flushed, but not shut down.
+///
+/// On Linux, data is sent to romio streams with `sendfile(2)`, without being
+/// copied through userspace. Otherwise, the file is read into a buffer, which
+/// blocks the task while the data is read from the disk.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::io::copy_file;
+/// use romio::tcp::TcpStream;
+/// use std::fs::File;
+///
+/// # async fn serve(mut stream: TcpStream) -> std::io::Result<()> {
+/// let mut file = File::open("index.html")?;
+/// await!(copy_file(&mut file, &mut stream))?;
+/// # Ok(())}
+/// ```
+pub fn copy_file<'a, W>(file: &'a mut File, writer: &'a mut W) -> CopyFile<'a, W>
+where
+    W: AsyncWrite + Any,
+{
+    CopyFile {
+        file,
+        writer,
+        buffered: !cfg!(target_os = "linux"),
+        buf: Vec::new(),
+        pos: 0,
+        cap: 0,
+        amount: 0,
+    }
+}
+
+impl<'a, W> CopyFile<'a, W>
+where
+    W: AsyncWrite + Any,
+{
+    /// Copies the file to the writer through a buffer, until its end.
+    fn poll_copy(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        if self.buf.is_empty() {
+            self.buf = vec![0; BUF_SIZE];
+        }
+
+        loop {
+            if self.pos == self.cap {
+                let n = self.file.read(&mut self.buf)?;
+                if n == 0 {
+                    return Poll::Ready(Ok(()));
+                }
+                self.pos = 0;
+                self.cap = n;
+            }
+
+            while self.pos < self.cap {
+                let n = ready!(self.writer.poll_write(lw, &self.buf[self.pos..self.cap]))?;
+                if n == 0 {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "write zero byte into writer",
+                    )));
+                }
+                self.pos += n;
+                self.amount += n as u64;
+            }
+        }
+    }
+}
+
+/// Sends `file` to `dst` until its end.
+#[cfg(target_os = "linux")]
+fn poll_sendfile(
+    lw: &LocalWaker,
+    file: &File,
+    dst: &dyn RawStream,
+    amount: &mut u64,
+) -> Poll<io::Result<()>> {
+    /// The largest number of bytes sent by a single call.
+    const CHUNK_SIZE: usize = 1024 * 1024;
+
+    loop {
+        ready!(dst.poll_write_ready(lw)?);
+        match sys::sendfile(dst.as_raw_fd(), file.as_raw_fd(), CHUNK_SIZE) {
+            Ok(0) => return Poll::Ready(Ok(())),
+            Ok(n) => *amount += n as u64,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                dst.clear_write_ready(lw)?;
+                return Poll::Pending;
+            }
+            Err(e) => return Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl<'a, W> Future for CopyFile<'a, W>
+where
+    W: AsyncWrite + Any,
+{
+    type Output = io::Result<u64>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<io::Result<u64>> {
+        let this = &mut *self;
+
+        #[cfg(target_os = "linux")]
+        {
+            if !this.buffered {
+                if let Some(dst) = raw_stream(this.writer) {
+                    match poll_sendfile(lw, this.file, dst, &mut this.amount) {
+                        Poll::Ready(Err(ref e)) if sys::is_unsupported(e) && this.amount == 0 => {}
+                        Poll::Ready(Ok(())) => return Poll::Ready(Ok(this.amount)),
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                this.buffered = true;
+            }
+        }
+
+        ready!(this.poll_copy(lw))?;
+        ready!(this.writer.poll_flush(lw))?;
+        Poll::Ready(Ok(this.amount))
+    }
+}
@@ -0,0 +1,130 @@
+//! Utilities for moving data between I/O objects.
+//!
+//! - [`copy_bidirectional`] forwards data in both directions between two
+//!   streams, as done by a proxy.
+//! - [`copy_file`] sends the contents of a file to a stream.
+//!
+//! On Linux, data is moved between romio streams with `splice(2)` and
+//! `sendfile(2)`, without copying it through userspace. This includes the
+//! connections yielded by `LimitedIncoming` streams, and the write halves of
+//! split streams. Other I/O objects, and other platforms, use a buffer.
+//!
+//! [`copy_bidirectional`]: fn.copy_bidirectional.html
+//! [`copy_file`]: fn.copy_file.html
+
+mod bidirectional;
+mod file;
+#[cfg(target_os = "linux")]
+mod sys;
+
+pub use self::bidirectional::{copy_bidirectional, CopyBidirectional};
+pub use self::file::{copy_file, CopyFile};
+
+use std::any::Any;
+use std::io;
+
+use futures::io::AsyncWrite;
+use futures::task::LocalWaker;
+use futures::Poll;
+
+#[cfg(unix)]
+use std::net::Shutdown;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+
+#[cfg(unix)]
+use crate::reactor::Ready;
+
+/// The size of the buffers used when data can't be moved by the kernel.
+const BUF_SIZE: usize = 8 * 1024;
+
+/// A romio stream, which data can be spliced to and from.
+#[cfg(unix)]
+pub(crate) trait RawStream: AsRawFd {
+    fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>>;
+    fn clear_read_ready(&self, lw: &LocalWaker) -> io::Result<()>;
+    fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>>;
+    fn clear_write_ready(&self, lw: &LocalWaker) -> io::Result<()>;
+    fn shutdown(&self, how: Shutdown) -> io::Result<()>;
+}
+
+/// Returns `io` as a romio stream, if it is one, or wraps one.
+#[cfg(unix)]
+fn raw_stream<T: Any>(io: &T) -> Option<&dyn RawStream> {
+    use crate::server::Limited;
+    use crate::{tcp, uds};
+
+    let io = io as &dyn Any;
+
+    if let Some(stream) = io.downcast_ref::<tcp::TcpStream>() {
+        return Some(stream);
+    }
+    if let Some(stream) = io.downcast_ref::<uds::UnixStream>() {
+        return Some(stream);
+    }
+
+    // connections accepted by a `LimitedIncoming` stream
+    if let Some(stream) = io.downcast_ref::<Limited<tcp::TcpStream>>() {
+        return Some(stream.get_ref());
+    }
+    if let Some(stream) = io.downcast_ref::<Limited<uds::UnixStream>>() {
+        return Some(stream.get_ref());
+    }
+
+    // the write halves of split streams, which `copy_file` can write to
+    if let Some(half) = io.downcast_ref::<tcp::OwnedWriteHalf>() {
+        return Some(half.stream());
+    }
+    if let Some(half) = io.downcast_ref::<uds::OwnedWriteHalf>() {
+        return Some(half.stream());
+    }
+    None
+}
+
+/// Shuts the write side of `io` down.
+///
+/// `AsyncWrite::poll_close` doesn't shut sockets down, so romio streams are
+/// shut down explicitly.
+fn poll_shutdown<W: AsyncWrite + Any>(io: &mut W, lw: &LocalWaker) -> Poll<io::Result<()>> {
+    #[cfg(unix)]
+    {
+        if let Some(stream) = raw_stream(io) {
+            return Poll::Ready(match stream.shutdown(Shutdown::Write) {
+                Err(ref e) if e.kind() == io::ErrorKind::NotConnected => Ok(()),
+                res => res,
+            });
+        }
+    }
+
+    io.poll_close(lw)
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::raw_stream;
+    use crate::server::ConnectionLimit;
+    use crate::tcp::TcpListener;
+    use crate::uds::UnixStream;
+
+    use futures::executor::block_on;
+    use futures::io::AllowStdIo;
+    use futures::StreamExt;
+    use std::net;
+
+    #[test]
+    fn wrapped_raw_streams() {
+        let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+        let _client = net::TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+
+        let limit = ConnectionLimit::new(1);
+        let mut incoming = listener.incoming().limit(&limit);
+        let limited = block_on(incoming.next()).unwrap().unwrap();
+        assert!(raw_stream(&limited).is_some());
+
+        let (stream, _peer) = UnixStream::pair().unwrap();
+        let (_read, write) = stream.into_split();
+        assert!(raw_stream(&write).is_some());
+
+        assert!(raw_stream(&AllowStdIo::new(Vec::<u8>::new())).is_none());
+    }
+}
@@ -0,0 +1,71 @@
+//! The Linux system calls used to transfer data without copying it through
+//! userspace.
+
+use std::io;
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+/// A non-blocking pipe, which data is spliced through.
+#[derive(Debug)]
+pub(super) struct Pipe {
+    pub read: RawFd,
+    pub write: RawFd,
+}
+
+impl Pipe {
+    pub fn new() -> io::Result<Pipe> {
+        let mut fds = [0; 2];
+        let flags = libc::O_NONBLOCK | libc::O_CLOEXEC;
+
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), flags) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Pipe {
+            read: fds[0],
+            write: fds[1],
+        })
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.read);
+            libc::close(self.write);
+        }
+    }
+}
+
+/// Moves up to `len` bytes from `from` to `to`, one of which must be a pipe.
+pub(super) fn splice(from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+    let flags = libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK;
+    let n = unsafe { libc::splice(from, ptr::null_mut(), to, ptr::null_mut(), len, flags) };
+
+    if n == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(n as usize)
+    }
+}
+
+/// Sends up to `len` bytes from the current offset of the file `from` to the
+/// socket `to`, advancing the offset.
+pub(super) fn sendfile(to: RawFd, from: RawFd, len: usize) -> io::Result<usize> {
+    let n = unsafe { libc::sendfile(to, from, ptr::null_mut(), len) };
+
+    if n == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(n as usize)
+    }
+}
+
+/// Returns whether `err` means that the system call can't be used with these
+/// file descriptors, in which case the data must be copied through a buffer.
+pub(super) fn is_unsupported(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(libc::EINVAL) | Some(libc::ENOSYS) | Some(libc::EOPNOTSUPP) => true,
+        _ => false,
+    }
+}
@@ -46,9 +46,8 @@
 //! use romio::tcp::{TcpListener, TcpStream};
 //!
 //! async fn receive_sonnet() -> Result<(), Box<dyn Error + 'static>> {
-//!     let socket_addr = "127.0.0.1:8080".parse()?;
 //!     let mut buffer = vec![];
-//!     let mut stream = await!(TcpStream::connect(&socket_addr))?;
+//!     let mut stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
 //!
 //!     await!(stream.read(&mut buffer))?;
 //!     println!("{:?}", buffer);
@@ -60,14 +59,20 @@
 #![deny(missing_docs, missing_debug_implementations)]
 #![cfg_attr(test, deny(warnings))]
 
+#[cfg(unix)]
+pub mod activation;
+pub mod dns;
+pub mod io;
+pub mod reactor;
+pub mod runtime;
+pub mod server;
 pub mod tcp;
+pub mod timer;
 pub mod udp;
 
 #[cfg(unix)]
 pub mod uds;
 
-mod reactor;
-
 #[doc(inline)]
 pub use crate::tcp::{TcpListener, TcpStream};
 #[doc(inline)]
@@ -0,0 +1,302 @@
+use super::{Handle, Interest, PollEvented, Ready};
+
+use futures::task::LocalWaker;
+use futures::{ready, Poll};
+use mio::event::Evented;
+use mio::unix::EventedFd;
+use mio::{PollOpt, Token};
+
+use std::fmt;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// Associates an arbitrary file descriptor with the reactor that drives it.
+///
+/// `AsyncFd` registers any type which owns a file descriptor with a reactor,
+/// and exposes its readiness, so that I/O on file descriptors which romio
+/// has no dedicated type for, e.g. an `eventfd`, `timerfd`, `inotify`
+/// instance, netlink socket or tun device, can be driven by the reactor.
+///
+/// The file descriptor must be in non-blocking mode. `AsyncFd` does not
+/// perform any I/O itself: instead, [`poll_read_ready`] and
+/// [`poll_write_ready`] return a [`ReadyGuard`] once the file descriptor is
+/// ready, and the caller performs the operation on the inner value. If the
+/// operation fails with `WouldBlock`, the readiness must be cleared through
+/// the guard, which makes the task wait for the next readiness event. The
+/// file descriptor stays ready until then.
+///
+/// Like [`PollEvented`], `AsyncFd` supports at most one task reading and one
+/// task writing concurrently.
+///
+/// [`poll_read_ready`]: #method.poll_read_ready
+/// [`poll_write_ready`]: #method.poll_write_ready
+/// [`ReadyGuard`]: struct.ReadyGuard.html
+/// [`PollEvented`]: struct.PollEvented.html
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::future::poll_fn;
+/// use futures::ready;
+/// use romio::reactor::AsyncFd;
+/// use std::io::Read;
+/// use std::os::unix::net::UnixStream;
+///
+/// # async fn read(stream: UnixStream) -> std::io::Result<()> {
+/// stream.set_nonblocking(true)?;
+/// let fd = AsyncFd::new(stream);
+///
+/// let mut buf = [0; 1024];
+/// let n = await!(poll_fn(|lw| {
+///     let guard = ready!(fd.poll_read_ready(lw))?;
+///     guard.try_io(|mut stream| stream.read(&mut buf))
+/// }))?;
+/// # drop(n);
+/// # Ok(())}
+/// ```
+pub struct AsyncFd<T: AsRawFd> {
+    io: PollEvented<Fd<T>>,
+}
+
+/// The readiness of an [`AsyncFd`], returned by [`poll_read_ready`] and
+/// [`poll_write_ready`].
+///
+/// Dropping the guard leaves the readiness in place, so the next call to
+/// `poll_read_ready` or `poll_write_ready` returns immediately. Once an
+/// operation fails with `WouldBlock`, the readiness must be cleared with
+/// [`clear_ready`], or by performing the operation through [`try_io`].
+///
+/// [`AsyncFd`]: struct.AsyncFd.html
+/// [`poll_read_ready`]: struct.AsyncFd.html#method.poll_read_ready
+/// [`poll_write_ready`]: struct.AsyncFd.html#method.poll_write_ready
+/// [`clear_ready`]: #method.clear_ready
+/// [`try_io`]: #method.try_io
+pub struct ReadyGuard<'a, T: AsRawFd> {
+    fd: &'a AsyncFd<T>,
+    lw: &'a LocalWaker,
+    ready: Ready,
+    write: bool,
+}
+
+/// Owns the value wrapped by `AsyncFd`, registering its file descriptor.
+struct Fd<T> {
+    inner: T,
+
+    /// The readiness to register for, regardless of what the reactor asks
+    /// for.
+    interest: mio::Ready,
+}
+
+impl<T: AsRawFd> AsyncFd<T> {
+    /// Creates a new `AsyncFd` associated with the default reactor, which is
+    /// interested in both readable and writable readiness.
+    ///
+    /// The file descriptor is registered with the reactor when the `AsyncFd`
+    /// is first polled; registration errors are returned from the first call
+    /// to `poll_read_ready` or `poll_write_ready`.
+    pub fn new(inner: T) -> AsyncFd<T> {
+        AsyncFd::with_interest(inner, Interest::READABLE | Interest::WRITABLE)
+    }
+
+    /// Creates a new `AsyncFd` associated with the default reactor, which is
+    /// only interested in the given readiness.
+    pub fn with_interest(inner: T, interest: Interest) -> AsyncFd<T> {
+        AsyncFd {
+            io: PollEvented::new(Fd::new(inner, interest)),
+        }
+    }
+
+    /// Creates a new `AsyncFd` associated with the reactor referenced by
+    /// `handle`, which is interested in both readable and writable readiness.
+    ///
+    /// Unlike [`new`], the file descriptor is registered with the reactor
+    /// immediately, so any registration error is returned here.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_with_handle(inner: T, handle: &Handle) -> io::Result<AsyncFd<T>> {
+        AsyncFd::with_interest_and_handle(inner, Interest::READABLE | Interest::WRITABLE, handle)
+    }
+
+    /// Creates a new `AsyncFd` associated with the reactor referenced by
+    /// `handle`, which is only interested in the given readiness.
+    ///
+    /// This is the same as [`with_interest`], except that the file descriptor
+    /// is registered with the given reactor immediately.
+    ///
+    /// [`with_interest`]: #method.with_interest
+    pub fn with_interest_and_handle(
+        inner: T,
+        interest: Interest,
+        handle: &Handle,
+    ) -> io::Result<AsyncFd<T>> {
+        let io = PollEvented::new_with_handle(Fd::new(inner, interest), handle)?;
+        Ok(AsyncFd { io })
+    }
+
+    /// Returns a shared reference to the inner value.
+    pub fn get_ref(&self) -> &T {
+        &self.io.get_ref().inner
+    }
+
+    /// Returns a mutable reference to the inner value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io.get_mut().inner
+    }
+
+    /// Deregisters the file descriptor from the reactor, returning the inner
+    /// value.
+    pub fn into_inner(self) -> io::Result<T> {
+        self.io.into_inner().map(|fd| fd.inner)
+    }
+
+    /// Checks whether the file descriptor is ready to be read from.
+    ///
+    /// If it is not, the current task is notified once it becomes readable.
+    /// Readiness also includes hang up and error events, as reading then
+    /// returns immediately.
+    pub fn poll_read_ready<'a>(
+        &'a self,
+        lw: &'a LocalWaker,
+    ) -> Poll<io::Result<ReadyGuard<'a, T>>> {
+        let ready = ready!(self.io.poll_read_ready(lw)?);
+        Poll::Ready(Ok(ReadyGuard {
+            fd: self,
+            lw,
+            ready,
+            write: false,
+        }))
+    }
+
+    /// Checks whether the file descriptor is ready to be written to.
+    ///
+    /// If it is not, the current task is notified once it becomes writable.
+    pub fn poll_write_ready<'a>(
+        &'a self,
+        lw: &'a LocalWaker,
+    ) -> Poll<io::Result<ReadyGuard<'a, T>>> {
+        let ready = ready!(self.io.poll_write_ready(lw)?);
+        Poll::Ready(Ok(ReadyGuard {
+            fd: self,
+            lw,
+            ready,
+            write: true,
+        }))
+    }
+
+    /// Clears the read readiness of the file descriptor, and registers the
+    /// current task to be notified once it becomes readable again.
+    pub fn clear_read_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+        self.io.clear_read_ready(lw)
+    }
+
+    /// Clears the write readiness of the file descriptor, and registers the
+    /// current task to be notified once it becomes writable again.
+    pub fn clear_write_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+        self.io.clear_write_ready(lw)
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for AsyncFd<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.get_ref().as_raw_fd()
+    }
+}
+
+impl<T: AsRawFd + fmt::Debug> fmt::Debug for AsyncFd<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFd")
+            .field("inner", self.get_ref())
+            .finish()
+    }
+}
+
+// ===== impl ReadyGuard =====
+
+impl<'a, T: AsRawFd> ReadyGuard<'a, T> {
+    /// Returns the readiness which was observed.
+    pub fn ready(&self) -> Ready {
+        self.ready
+    }
+
+    /// Returns a shared reference to the inner value of the `AsyncFd`.
+    pub fn get_ref(&self) -> &'a T {
+        self.fd.get_ref()
+    }
+
+    /// Clears the readiness, registering the current task to be notified
+    /// once the file descriptor becomes ready again.
+    ///
+    /// This should be called once an operation has failed with
+    /// `WouldBlock`.
+    pub fn clear_ready(self) -> io::Result<()> {
+        if self.write {
+            self.fd.clear_write_ready(self.lw)
+        } else {
+            self.fd.clear_read_ready(self.lw)
+        }
+    }
+
+    /// Performs an I/O operation on the inner value, clearing the readiness
+    /// if it fails with `WouldBlock`.
+    ///
+    /// Returns `Pending` if the operation would block, in which case the
+    /// current task is notified once the file descriptor becomes ready again.
+    pub fn try_io<R, F>(self, f: F) -> Poll<io::Result<R>>
+    where
+        F: FnOnce(&'a T) -> io::Result<R>,
+    {
+        match f(self.get_ref()) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.clear_ready()?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+}
+
+impl<'a, T: AsRawFd> fmt::Debug for ReadyGuard<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadyGuard")
+            .field("ready", &self.ready)
+            .finish()
+    }
+}
+
+// ===== impl Fd =====
+
+impl<T> Fd<T> {
+    fn new(inner: T, interest: Interest) -> Fd<T> {
+        Fd {
+            inner,
+            interest: interest.to_mio(),
+        }
+    }
+}
+
+impl<T: AsRawFd> Evented for Fd<T> {
+    fn register(
+        &self,
+        poll: &mio::Poll,
+        token: Token,
+        _: mio::Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).register(poll, token, self.interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &mio::Poll,
+        token: Token,
+        _: mio::Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).reregister(poll, token, self.interest, opts)
+    }
+
+    fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+        EventedFd(&self.inner.as_raw_fd()).deregister(poll)
+    }
+}
@@ -82,6 +82,33 @@ impl Background {
         })
     }
 
+    /// Returns a reference to the reactor handle.
+    pub fn handle(&self) -> &Handle {
+        &self.inner.as_ref().unwrap().handle
+    }
+
+    /// Shutdown the reactor on idle.
+    ///
+    /// Returns a future that completes once the shutdown operation has
+    /// completed.
+    pub fn shutdown_on_idle(mut self) -> Shutdown {
+        let inner = self.inner.take().unwrap();
+        inner.shutdown_on_idle();
+
+        Shutdown { inner }
+    }
+
+    /// Shutdown the reactor immediately
+    ///
+    /// Returns a future that completes once the shutdown operation has
+    /// completed.
+    pub fn shutdown_now(mut self) -> Shutdown {
+        let inner = self.inner.take().unwrap();
+        inner.shutdown_now();
+
+        Shutdown { inner }
+    }
+
     /// Run the reactor on its thread until the process terminates.
     pub fn forget(mut self) {
         drop(self.inner.take());
@@ -126,6 +153,19 @@ impl Inner {
         self.shared.shutdown.load(SeqCst) == SHUTDOWN
     }
 
+    /// Notify the reactor thread to shutdown once the reactor becomes idle.
+    fn shutdown_on_idle(&self) {
+        if self
+            .shared
+            .shutdown
+            .compare_and_swap(0, SHUTDOWN_IDLE, SeqCst)
+            == 0
+        {
+            // Wake up the reactor so that it checks whether it is idle.
+            self.handle.wakeup();
+        }
+    }
+
     /// Notify the reactor thread to shutdown immediately.
     fn shutdown_now(&self) {
         let mut curr = self.shared.shutdown.load(SeqCst);
@@ -1,26 +1,82 @@
-pub(crate) mod background;
+//! The core reactor driving all I/O resources.
+//!
+//! By default, every I/O object in romio binds lazily to a global fallback
+//! reactor, which is driven by a background thread that is started the first
+//! time it is needed. This module exposes the reactor itself, so that
+//! applications can create their own reactors, decide which thread drives
+//! them, and bind I/O objects to them explicitly.
+//!
+//! - To create a reactor, use [`Reactor::new`], and drive it by calling
+//!   [`Reactor::turn`] in a loop or by moving it to a background thread with
+//!   [`Reactor::background`].
+//! - To bind an I/O object to a specific reactor, pass a [`Handle`] to one of
+//!   the `*_with_handle` constructors, e.g. [`TcpListener::bind_with_handle`].
+//! - To make a reactor the default for everything that binds lazily on the
+//!   current thread, use [`with_default`] or [`set_default`].
+//! - To drive a file descriptor that romio has no dedicated type for, wrap
+//!   it in an [`AsyncFd`].
+//!
+//! [`Reactor::new`]: struct.Reactor.html#method.new
+//! [`Reactor::turn`]: struct.Reactor.html#method.turn
+//! [`Reactor::background`]: struct.Reactor.html#method.background
+//! [`Handle`]: struct.Handle.html
+//! [`TcpListener::bind_with_handle`]: ../tcp/struct.TcpListener.html#method.bind_with_handle
+//! [`with_default`]: fn.with_default.html
+//! [`set_default`]: fn.set_default.html
+//! [`AsyncFd`]: struct.AsyncFd.html
+//!
+//! # Examples
+//!
+//! ```rust
+//! use romio::reactor::Reactor;
+//! use romio::tcp::TcpListener;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+//! let reactor = Reactor::new()?;
+//! let handle = reactor.handle();
+//!
+//! let addr = "127.0.0.1:0".parse()?;
+//! let listener = TcpListener::bind_with_handle(&addr, &handle)?;
+//!
+//! // Drive the reactor, and with it the listener, on a background thread.
+//! let background = reactor.background()?;
+//! # drop(listener);
+//! # drop(background);
+//! # Ok(())}
+//! ```
+
+#[cfg(unix)]
+mod async_fd;
+mod background;
 mod poll_evented;
+mod ready;
 mod registration;
 mod sharded_rwlock;
 
 // ===== Public re-exports =====
 
-use self::background::Background;
+#[cfg(unix)]
+pub use self::async_fd::{AsyncFd, ReadyGuard};
+pub use self::background::{Background, Shutdown};
 pub use self::poll_evented::PollEvented;
-use self::registration::Registration;
+pub use self::ready::{Interest, Ready};
+pub(crate) use self::registration::Registration;
 
 // ===== Private imports =====
 
 use self::sharded_rwlock::RwLock;
+use crate::timer::{Clock, Driver};
 
 use std::cell::RefCell;
 use std::io;
+use std::marker::PhantomData;
 use std::mem;
+use std::rc::Rc;
 use std::sync::atomic::Ordering::{Relaxed, SeqCst};
 use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT};
 use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
-use std::{fmt, usize};
+use std::{cmp, fmt, usize};
 
 use futures::task::{AtomicWaker, LocalWaker};
 use log::{debug, log_enabled, trace, Level};
@@ -33,7 +89,7 @@ use slab::Slab;
 /// all other I/O events and notifications happening. Each event loop can have
 /// multiple handles pointing to it, each of which can then be used to create
 /// various I/O objects to interact with the event loop in interesting ways.
-struct Reactor {
+pub struct Reactor {
     /// Reuse the `mio::Events` value across calls to poll.
     events: mio::Events,
 
@@ -52,7 +108,7 @@ struct Reactor {
 /// By default, most components bind lazily to reactors.
 /// To get this behavior when manually passing a `Handle`, use `default()`.
 #[derive(Clone)]
-struct Handle {
+pub struct Handle {
     inner: Option<HandlePriv>,
 }
 
@@ -62,12 +118,22 @@ struct HandlePriv {
     inner: Weak<Inner>,
 }
 
+/// Guard returned by [`set_default`], which resets the default reactor of the
+/// current thread when dropped.
+///
+/// [`set_default`]: fn.set_default.html
+#[derive(Debug)]
+pub struct DefaultGuard {
+    // The guard restores thread-local state, so it must stay on its thread.
+    _p: PhantomData<Rc<()>>,
+}
+
 /// Return value from the `turn` method on `Reactor`.
 ///
 /// Currently this value doesn't actually provide any functionality, but it may
 /// in the future give insight into what happened during `turn`.
 #[derive(Debug)]
-struct Turn {
+pub struct Turn {
     _priv: (),
 }
 
@@ -89,6 +155,9 @@ struct Inner {
 
     /// Used to wake up the reactor from a call to `turn`
     wakeup: mio::SetReadiness,
+
+    /// Timers driven by this reactor
+    timer: Driver,
 }
 
 struct ScheduledIo {
@@ -127,7 +196,16 @@ fn _assert_kinds() {
 impl Reactor {
     /// Creates a new event loop, returning any error that happened during the
     /// creation.
-    fn new() -> io::Result<Reactor> {
+    pub fn new() -> io::Result<Reactor> {
+        Reactor::new_with_clock(Clock::system())
+    }
+
+    /// Creates a new event loop whose timers measure time using `clock`.
+    ///
+    /// See [`Clock`] for details.
+    ///
+    /// [`Clock`]: ../timer/struct.Clock.html
+    pub fn new_with_clock(clock: Clock) -> io::Result<Reactor> {
         let io = mio::Poll::new()?;
         let wakeup_pair = mio::Registration::new2();
 
@@ -138,7 +216,7 @@ impl Reactor {
             mio::PollOpt::level(),
         )?;
 
-        Ok(Reactor {
+        let reactor = Reactor {
             events: mio::Events::with_capacity(1024),
             _wakeup_registration: wakeup_pair.0,
             inner: Arc::new(Inner {
@@ -146,8 +224,12 @@ impl Reactor {
                 next_aba_guard: AtomicUsize::new(0),
                 io_dispatch: RwLock::new(Slab::with_capacity(1)),
                 wakeup: wakeup_pair.1,
+                timer: Driver::new(clock.clone()),
             }),
-        })
+        };
+
+        clock.register(reactor.handle());
+        Ok(reactor)
     }
 
     /// Returns a handle to this event loop which can be sent across threads
@@ -156,7 +238,7 @@ impl Reactor {
     /// Handles are cloneable and clones always refer to the same event loop.
     /// This handle is typically passed into functions that create I/O objects
     /// to bind them to this event loop.
-    fn handle(&self) -> Handle {
+    pub fn handle(&self) -> Handle {
         Handle {
             inner: Some(HandlePriv {
                 inner: Arc::downgrade(&self.inner),
@@ -175,6 +257,12 @@ impl Reactor {
     /// the duration specified, but this shouldn't be used as a super-precise
     /// timer but rather a "ballpark approximation"
     ///
+    /// The reactor also drives the timers in [`romio::timer`] that are
+    /// registered with it: it blocks no longer than until the next timer
+    /// fires, and fires all timers that have elapsed before returning.
+    ///
+    /// [`romio::timer`]: ../timer/index.html
+    ///
     /// # Return value
     ///
     /// This function returns an instance of `Turn`
@@ -189,18 +277,25 @@ impl Reactor {
     /// for readiness of I/O objects with the OS. This is quite unlikely to
     /// arise and typically mean that things have gone horribly wrong at that
     /// point. Currently this is primarily only known to happen for internal
-    /// bugs to `tokio` itself.
-    fn turn(&mut self, max_wait: Option<Duration>) -> io::Result<Turn> {
+    /// bugs to `romio` itself.
+    pub fn turn(&mut self, max_wait: Option<Duration>) -> io::Result<Turn> {
+        let max_wait = match (max_wait, self.inner.timer.next_timeout()) {
+            (Some(max_wait), Some(timeout)) => Some(cmp::min(max_wait, timeout)),
+            (max_wait, None) => max_wait,
+            (None, timeout) => timeout,
+        };
+
         self.poll(max_wait)?;
+        self.inner.timer.process();
         Ok(Turn { _priv: () })
     }
 
     /// Returns true if the reactor is currently idle.
     ///
     /// Idle is defined as all tasks that have been spawned have completed,
-    /// either successfully or with an error.
-    fn is_idle(&self) -> bool {
-        self.inner.io_dispatch.read().is_empty()
+    /// either successfully or with an error, and no timers are pending.
+    pub fn is_idle(&self) -> bool {
+        self.inner.io_dispatch.read().is_empty() && self.inner.timer.is_empty()
     }
 
     /// Run this reactor on a background thread.
@@ -209,7 +304,7 @@ impl Reactor {
     /// reactor to this new thread. It then runs the reactor, driving all
     /// associated I/O resources, until the `Background` handle is dropped or
     /// explicitly shutdown.
-    fn background(self) -> io::Result<Background> {
+    pub fn background(self) -> io::Result<Background> {
         Background::new(self)
     }
 
@@ -308,6 +403,27 @@ impl fmt::Debug for Reactor {
 // ===== impl Handle =====
 
 impl Handle {
+    /// Returns a handle to the current reactor.
+    ///
+    /// Unlike `Handle::default()`, the returned handle is bound eagerly: if no
+    /// reactor has been set for the current execution context, this is the
+    /// global fallback reactor, which is started if it is not running yet.
+    ///
+    /// An error is returned if the fallback reactor can't be created.
+    pub fn current() -> io::Result<Handle> {
+        let inner = HandlePriv::try_current()?;
+
+        Ok(Handle { inner: Some(inner) })
+    }
+
+    /// Returns a handle to the current reactor without binding: `None` is
+    /// returned instead of starting the global fallback reactor.
+    pub(crate) fn try_current() -> Option<Handle> {
+        let inner = HandlePriv::current_if_running()?;
+
+        Some(Handle { inner: Some(inner) })
+    }
+
     fn as_priv(&self) -> Option<&HandlePriv> {
         self.inner.as_ref()
     }
@@ -316,7 +432,30 @@ impl Handle {
         self.inner
     }
 
-    fn wakeup(&self) {
+    /// Returns a handle which is bound to a reactor, resolving the default
+    /// reactor for the current execution context if this handle isn't bound.
+    pub(crate) fn bind(&self) -> io::Result<Handle> {
+        let inner = match self.as_priv() {
+            Some(handle) => handle.clone(),
+            None => HandlePriv::try_current()?,
+        };
+
+        Ok(Handle { inner: Some(inner) })
+    }
+
+    /// Calls `f` with the timer driver of the referenced reactor, or returns
+    /// `None` if the reactor has gone away or this handle isn't bound.
+    pub(crate) fn with_timer<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&Driver) -> R,
+    {
+        let inner = self.as_priv()?.inner()?;
+        Some(f(&inner.timer))
+    }
+
+    /// Forces the reactor to wake up from a call to `turn`, see
+    /// `HandlePriv::wakeup`.
+    pub(crate) fn wakeup(&self) {
         if let Some(handle) = self.as_priv() {
             handle.wakeup();
         }
@@ -336,6 +475,88 @@ impl fmt::Debug for Handle {
     }
 }
 
+// ===== impl DefaultGuard =====
+
+/// Sets `handle` as the default reactor for the current thread, returning a
+/// guard that unsets it when dropped.
+///
+/// While the guard is alive, I/O objects which bind lazily, i.e. those that
+/// were not created with an explicit [`Handle`], register with this reactor
+/// when they are first polled on the current thread, rather than with the
+/// global fallback reactor. Executors use this to make the tasks they run use
+/// their own reactor.
+///
+/// Because the guard resets the default when dropped, the default is also
+/// reset if the thread panics while the guard is alive.
+///
+/// # Panics
+///
+/// This function panics if a default reactor is already set for the current
+/// thread, or if `handle` does not reference a reactor, as is the case for
+/// `Handle::default()`.
+///
+/// [`Handle`]: struct.Handle.html
+pub fn set_default(handle: &Handle) -> DefaultGuard {
+    let handle = match handle.as_priv() {
+        Some(handle) => handle.clone(),
+        None => panic!("`handle` does not reference a reactor"),
+    };
+
+    CURRENT_REACTOR.with(|current| {
+        let mut current = current.borrow_mut();
+
+        assert!(
+            current.is_none(),
+            "default reactor already set for execution context"
+        );
+
+        *current = Some(handle);
+    });
+
+    DefaultGuard { _p: PhantomData }
+}
+
+/// Runs `f` with `handle` set as the default reactor for the current thread.
+///
+/// This is a scoped version of [`set_default`]; the default is reset once `f`
+/// returns or panics.
+///
+/// # Panics
+///
+/// This function panics if a default reactor is already set for the current
+/// thread, or if `handle` does not reference a reactor.
+///
+/// # Examples
+///
+/// ```rust
+/// use romio::reactor::{self, Reactor};
+///
+/// # fn main() -> std::io::Result<()> {
+/// let reactor = Reactor::new()?;
+///
+/// reactor::with_default(&reactor.handle(), || {
+///     // I/O objects polled in here register with `reactor`.
+/// });
+/// # Ok(())}
+/// ```
+///
+/// [`set_default`]: fn.set_default.html
+pub fn with_default<F, R>(handle: &Handle, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _guard = set_default(handle);
+    f()
+}
+
+impl Drop for DefaultGuard {
+    fn drop(&mut self) {
+        CURRENT_REACTOR.with(|current| {
+            *current.borrow_mut() = None;
+        });
+    }
+}
+
 fn set_fallback(handle: HandlePriv) -> Result<(), ()> {
     unsafe {
         let val = handle.into_usize();
@@ -362,6 +583,17 @@ impl HandlePriv {
         })
     }
 
+    /// Returns a handle to the current reactor, or `None` if no reactor has
+    /// been set for the current execution context and the fallback reactor
+    /// hasn't been started.
+    fn current_if_running() -> Option<HandlePriv> {
+        CURRENT_REACTOR.with(|current| match *current.borrow() {
+            Some(ref handle) => Some(handle.clone()),
+            None if HANDLE_FALLBACK.load(SeqCst) != 0 => HandlePriv::fallback().ok(),
+            None => None,
+        })
+    }
+
     /// Returns a handle to the fallback reactor.
     fn fallback() -> io::Result<HandlePriv> {
         let mut fallback = HANDLE_FALLBACK.load(SeqCst);
@@ -535,6 +767,8 @@ impl Drop for Inner {
             io.writer.wake();
             io.reader.wake();
         }
+
+        self.timer.shutdown();
     }
 }
 
@@ -552,27 +786,68 @@ impl Direction {
 
 #[cfg(unix)]
 mod platform {
+    use super::{Interest, Ready};
     use mio::unix::UnixReady;
-    use mio::Ready;
 
-    pub fn hup() -> Ready {
+    pub fn hup() -> mio::Ready {
         UnixReady::hup().into()
     }
 
-    pub fn is_hup(ready: &Ready) -> bool {
+    pub fn is_hup(ready: &mio::Ready) -> bool {
         UnixReady::from(*ready).is_hup()
     }
+
+    /// Maps the platform-specific readiness reported by mio.
+    pub fn ready_from_mio(ready: mio::Ready) -> Ready {
+        let ready = UnixReady::from(ready);
+        let mut ret = Ready::EMPTY;
+
+        // mio reports both `EPOLLHUP` and `EPOLLRDHUP` as HUP. Either way the
+        // peer won't send any more data, but only `EPOLLHUP` means the write
+        // side is closed too, so HUP can't be reported as write-closed.
+        if ready.is_hup() {
+            ret |= Ready::READ_CLOSED;
+        }
+
+        if ready.is_error() {
+            ret |= Ready::ERROR;
+        }
+
+        // `UnixReady::priority` shares its bit with `Ready::writable` on Linux,
+        // so it is neither registered for nor reported.
+
+        ret
+    }
+
+    /// Maps the platform-specific part of an interest to mio.
+    pub fn interest_to_mio(interest: Interest) -> mio::Ready {
+        let mut ret = mio::Ready::empty();
+
+        if interest.is_readable() {
+            ret |= UnixReady::hup() | UnixReady::error();
+        }
+
+        ret
+    }
 }
 
 #[cfg(windows)]
 mod platform {
-    use mio::Ready;
+    use super::{Interest, Ready};
 
-    pub fn hup() -> Ready {
-        Ready::empty()
+    pub fn hup() -> mio::Ready {
+        mio::Ready::empty()
     }
 
-    pub fn is_hup(_: &Ready) -> bool {
+    pub fn is_hup(_: &mio::Ready) -> bool {
         false
     }
+
+    pub fn ready_from_mio(_: mio::Ready) -> Ready {
+        Ready::EMPTY
+    }
+
+    pub fn interest_to_mio(_: Interest) -> mio::Ready {
+        mio::Ready::empty()
+    }
 }
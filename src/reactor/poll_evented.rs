@@ -1,4 +1,4 @@
-use super::Registration;
+use super::{Handle, Ready, Registration};
 
 use futures::io::{AsyncRead, AsyncWrite};
 use futures::task::LocalWaker;
@@ -71,9 +71,20 @@ use std::sync::atomic::Ordering::Relaxed;
 ///
 /// ## Platform-specific events
 ///
-/// `PollEvented` also allows receiving platform-specific `mio::Ready` events.
-/// These events are included as part of the read readiness event stream. The
-/// write readiness event stream is only for `Ready::writable()` events.
+/// `PollEvented` also allows receiving platform-specific events, such as
+/// [`Ready::READ_CLOSED`] or [`Ready::ERROR`]. These events are included as
+/// part of the read readiness event stream. The write readiness event stream
+/// is only for [`Ready::WRITABLE`] events, but is also woken up when the
+/// resource is closed.
+///
+/// Read-closed readiness is a final state: once it has been received, it is
+/// returned by every call to [`poll_read_ready`], and [`poll_read_closed`]
+/// completes immediately.
+///
+/// [`Ready::READ_CLOSED`]: struct.Ready.html#associatedconstant.READ_CLOSED
+/// [`Ready::ERROR`]: struct.Ready.html#associatedconstant.ERROR
+/// [`Ready::WRITABLE`]: struct.Ready.html#associatedconstant.WRITABLE
+/// [`poll_read_closed`]: #method.poll_read_closed
 ///
 /// [`std::io::Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
 /// [`std::io::Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
@@ -119,6 +130,20 @@ where
         }
     }
 
+    /// Creates a new `PollEvented` associated with the specified reactor.
+    ///
+    /// Unlike [`new`], the I/O resource is registered with the reactor
+    /// immediately, so any registration error is returned here.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_with_handle(io: E, handle: &Handle) -> io::Result<PollEvented<E>> {
+        let ret = PollEvented::new(io);
+        ret.inner
+            .registration
+            .register_with(ret.io.as_ref().unwrap(), handle)?;
+        Ok(ret)
+    }
+
     /// Returns a shared reference to the underlying I/O object this readiness
     /// stream is wrapping.
     pub fn get_ref(&self) -> &E {
@@ -147,10 +172,8 @@ where
 
     /// Check the I/O resource's read readiness state.
     ///
-    /// The mask argument allows specifying what readiness to notify on. This
-    /// can be any value, including platform specific readiness, **except**
-    /// `writable`. HUP is always implicitly included on platforms that support
-    /// it.
+    /// This checks for readable readiness, and also for read-closed readiness
+    /// on platforms that support it.
     ///
     /// If the resource is not ready for a read then `Async::NotReady` is
     /// returned and the current task is notified once a new event is received.
@@ -159,15 +182,16 @@ where
     /// cleared by calling [`clear_read_ready`].
     ///
     /// [`clear_read_ready`]: #method.clear_read_ready
-    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.register()?;
 
         // Load cached & encoded readiness.
         let mut cached = self.inner.read_readiness.load(Relaxed);
         let mask = mio::Ready::readable() | super::platform::hup();
 
-        // See if the current readiness matches any bits.
-        let mut ret = mio::Ready::from_usize(cached) & mio::Ready::readable();
+        // See if the current readiness matches any bits. HUP is never cleared,
+        // so it stays visible once received.
+        let mut ret = mio::Ready::from_usize(cached) & mask;
 
         if ret.is_empty() {
             // Readiness does not match, consume the registration's readiness
@@ -183,7 +207,7 @@ where
                 ret |= ready & mask;
 
                 if !ret.is_empty() {
-                    return Poll::Ready(Ok(ret));
+                    return Poll::Ready(Ok(Ready::from_mio(ret)));
                 }
             }
         } else {
@@ -194,18 +218,16 @@ where
                 self.inner.read_readiness.store(cached, Relaxed);
             }
 
-            Poll::Ready(Ok(mio::Ready::from_usize(cached)))
+            Poll::Ready(Ok(Ready::from_mio(mio::Ready::from_usize(cached))))
         }
     }
 
     /// Clears the I/O resource's read readiness state and registers the current
     /// task to be notified once a read readiness event is received.
     ///
-    /// After calling this function, `poll_read_ready` will return `NotReady`
-    /// until a new read readiness event has been received.
-    ///
-    /// The `mask` argument specifies the readiness bits to clear. This may not
-    /// include `writable` or `hup`.
+    /// After calling this function, `poll_read_ready` will return `Pending`
+    /// until a new read readiness event has been received, unless read-closed
+    /// readiness has been received already, as it cannot be cleared.
     pub fn clear_read_ready(&self, lw: &LocalWaker) -> io::Result<()> {
         self.inner
             .read_readiness
@@ -219,6 +241,37 @@ where
         Ok(())
     }
 
+    /// Checks whether the read side of the I/O resource has been closed, e.g.
+    /// because the peer of a socket has shut down its write side.
+    ///
+    /// Unlike [`poll_read_ready`], this keeps waiting while the resource is
+    /// readable, so the peer shutting down can be detected without consuming
+    /// the data that is still buffered. Data can still be read after this
+    /// returns `Ready`, until end-of-file is reached.
+    ///
+    /// This uses the same notification as [`poll_read_ready`], so it must be
+    /// called from the task reading from the resource. On platforms that don't
+    /// report read-closed readiness, like Windows, it never completes.
+    ///
+    /// [`poll_read_ready`]: #method.poll_read_ready
+    pub fn poll_read_closed(&self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.register()?;
+
+        loop {
+            let cached = mio::Ready::from_usize(self.inner.read_readiness.load(Relaxed));
+            if super::platform::is_hup(&cached) {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Consume the registration's readiness stream until HUP is received,
+            // keeping the other readiness around for `poll_read_ready`.
+            let ready = ready!(self.inner.registration.poll_read_ready(lw)?);
+            self.inner
+                .read_readiness
+                .fetch_or(ready.as_usize(), Relaxed);
+        }
+    }
+
     /// Check the I/O resource's write readiness state.
     ///
     /// This always checks for writable readiness and also checks for HUP
@@ -238,7 +291,7 @@ where
     ///
     /// * `ready` contains bits besides `writable` and `hup`.
     /// * called from outside of a task context.
-    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<Result<mio::Ready, io::Error>> {
+    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.register()?;
 
         // Load cached & encoded readiness.
@@ -262,7 +315,7 @@ where
                 ret |= ready & mask;
 
                 if !ret.is_empty() {
-                    return Poll::Ready(Ok(ret));
+                    return Poll::Ready(Ok(Ready::from_mio(ret)));
                 }
             }
         } else {
@@ -273,7 +326,7 @@ where
                 self.inner.write_readiness.store(cached, Relaxed);
             }
 
-            Poll::Ready(Ok(mio::Ready::from_usize(cached)))
+            Poll::Ready(Ok(Ready::from_mio(mio::Ready::from_usize(cached))))
         }
     }
 
@@ -0,0 +1,304 @@
+use super::platform;
+
+use std::fmt;
+use std::ops;
+
+/// Describes the readiness state of an I/O resource.
+///
+/// `Ready` is returned by the `poll_read_ready` and `poll_write_ready`
+/// methods of romio's I/O types, and is a set of the following flags:
+///
+/// - [`READABLE`]: the resource can be read from.
+/// - [`WRITABLE`]: the resource can be written to.
+/// - [`READ_CLOSED`]: the read side of the resource has been closed, e.g.
+///   the peer of a socket has shut down its write side. Reads return
+///   end-of-file once the buffered data has been consumed.
+/// - [`WRITE_CLOSED`]: the write side of the resource has been closed.
+/// - [`ERROR`]: an error is pending on the resource.
+/// - [`PRIORITY`]: priority data, e.g. TCP out-of-band data, can be read.
+///
+/// Which flags are reported depends on the platform; on Windows, for
+/// example, the closed and error flags are never set. With the current mio
+/// backend, neither [`PRIORITY`] nor [`WRITE_CLOSED`] is ever set; writing to
+/// a socket whose write side has been closed fails with `BrokenPipe` instead.
+/// Priority readiness can't be registered for, as mio 0.6 gives `EPOLLPRI`
+/// the same bit as writable readiness.
+///
+/// [`READABLE`]: #associatedconstant.READABLE
+/// [`WRITABLE`]: #associatedconstant.WRITABLE
+/// [`READ_CLOSED`]: #associatedconstant.READ_CLOSED
+/// [`WRITE_CLOSED`]: #associatedconstant.WRITE_CLOSED
+/// [`ERROR`]: #associatedconstant.ERROR
+/// [`PRIORITY`]: #associatedconstant.PRIORITY
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Ready(u8);
+
+/// Describes the readiness an I/O resource is interested in.
+///
+/// `Interest` is passed when registering a file descriptor with
+/// [`AsyncFd::with_interest`], and is a set of the following flags:
+///
+/// - [`READABLE`]: readable, read-closed and error readiness.
+/// - [`WRITABLE`]: writable and write-closed readiness.
+///
+/// [`AsyncFd::with_interest`]: struct.AsyncFd.html#method.with_interest
+/// [`READABLE`]: #associatedconstant.READABLE
+/// [`WRITABLE`]: #associatedconstant.WRITABLE
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Interest(u8);
+
+const READABLE: u8 = 0b00_0001;
+const WRITABLE: u8 = 0b00_0010;
+const READ_CLOSED: u8 = 0b00_0100;
+const WRITE_CLOSED: u8 = 0b00_1000;
+const ERROR: u8 = 0b01_0000;
+const PRIORITY: u8 = 0b10_0000;
+
+// ===== impl Ready =====
+
+impl Ready {
+    /// No readiness.
+    pub const EMPTY: Ready = Ready(0);
+
+    /// Readable readiness.
+    pub const READABLE: Ready = Ready(READABLE);
+
+    /// Writable readiness.
+    pub const WRITABLE: Ready = Ready(WRITABLE);
+
+    /// Read-closed readiness.
+    pub const READ_CLOSED: Ready = Ready(READ_CLOSED);
+
+    /// Write-closed readiness.
+    pub const WRITE_CLOSED: Ready = Ready(WRITE_CLOSED);
+
+    /// Error readiness.
+    pub const ERROR: Ready = Ready(ERROR);
+
+    /// Priority readiness.
+    pub const PRIORITY: Ready = Ready(PRIORITY);
+
+    /// Returns true if no flags are set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if the value includes readable readiness.
+    pub fn is_readable(self) -> bool {
+        self.contains(Ready::READABLE)
+    }
+
+    /// Returns true if the value includes writable readiness.
+    pub fn is_writable(self) -> bool {
+        self.contains(Ready::WRITABLE)
+    }
+
+    /// Returns true if the value includes read-closed readiness.
+    pub fn is_read_closed(self) -> bool {
+        self.contains(Ready::READ_CLOSED)
+    }
+
+    /// Returns true if the value includes write-closed readiness.
+    pub fn is_write_closed(self) -> bool {
+        self.contains(Ready::WRITE_CLOSED)
+    }
+
+    /// Returns true if the value includes error readiness.
+    pub fn is_error(self) -> bool {
+        self.contains(Ready::ERROR)
+    }
+
+    /// Returns true if the value includes priority readiness.
+    pub fn is_priority(self) -> bool {
+        self.contains(Ready::PRIORITY)
+    }
+
+    /// Returns true if all flags set in `other` are also set in `self`.
+    pub fn contains(self, other: Ready) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Converts readiness reported by mio.
+    pub(crate) fn from_mio(ready: mio::Ready) -> Ready {
+        let mut ret = Ready::EMPTY;
+
+        if ready.is_readable() {
+            ret |= Ready::READABLE;
+        }
+
+        if ready.is_writable() {
+            ret |= Ready::WRITABLE;
+        }
+
+        ret | platform::ready_from_mio(ready)
+    }
+}
+
+impl ops::BitOr for Ready {
+    type Output = Ready;
+
+    fn bitor(self, other: Ready) -> Ready {
+        Ready(self.0 | other.0)
+    }
+}
+
+impl ops::BitOrAssign for Ready {
+    fn bitor_assign(&mut self, other: Ready) {
+        self.0 |= other.0;
+    }
+}
+
+impl ops::BitAnd for Ready {
+    type Output = Ready;
+
+    fn bitand(self, other: Ready) -> Ready {
+        Ready(self.0 & other.0)
+    }
+}
+
+impl ops::Sub for Ready {
+    type Output = Ready;
+
+    fn sub(self, other: Ready) -> Ready {
+        Ready(self.0 & !other.0)
+    }
+}
+
+impl fmt::Debug for Ready {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (Ready::READABLE, "READABLE"),
+            (Ready::WRITABLE, "WRITABLE"),
+            (Ready::READ_CLOSED, "READ_CLOSED"),
+            (Ready::WRITE_CLOSED, "WRITE_CLOSED"),
+            (Ready::ERROR, "ERROR"),
+            (Ready::PRIORITY, "PRIORITY"),
+        ];
+
+        write!(f, "Ready {{")?;
+        let mut first = true;
+        for &(flag, name) in &flags {
+            if self.contains(flag) {
+                write!(f, "{}{}", if first { " " } else { " | " }, name)?;
+                first = false;
+            }
+        }
+        write!(f, " }}")
+    }
+}
+
+// ===== impl Interest =====
+
+impl Interest {
+    /// Interest in readable readiness.
+    pub const READABLE: Interest = Interest(READABLE);
+
+    /// Interest in writable readiness.
+    pub const WRITABLE: Interest = Interest(WRITABLE);
+
+    /// Returns true if the value includes readable interest.
+    pub fn is_readable(self) -> bool {
+        self.0 & READABLE != 0
+    }
+
+    /// Returns true if the value includes writable interest.
+    pub fn is_writable(self) -> bool {
+        self.0 & WRITABLE != 0
+    }
+
+    /// Converts the interest into the readiness to register with mio.
+    pub(crate) fn to_mio(self) -> mio::Ready {
+        let mut ret = mio::Ready::empty();
+
+        if self.is_readable() {
+            ret |= mio::Ready::readable();
+        }
+
+        if self.is_writable() {
+            ret |= mio::Ready::writable();
+        }
+
+        ret | platform::interest_to_mio(self)
+    }
+}
+
+impl ops::BitOr for Interest {
+    type Output = Interest;
+
+    fn bitor(self, other: Interest) -> Interest {
+        Interest(self.0 | other.0)
+    }
+}
+
+impl ops::BitOrAssign for Interest {
+    fn bitor_assign(&mut self, other: Interest) {
+        self.0 |= other.0;
+    }
+}
+
+impl fmt::Debug for Interest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [(READABLE, "READABLE"), (WRITABLE, "WRITABLE")];
+
+        write!(f, "Interest {{")?;
+        let mut first = true;
+        for &(flag, name) in &flags {
+            if self.0 & flag != 0 {
+                write!(f, "{}{}", if first { " " } else { " | " }, name)?;
+                first = false;
+            }
+        }
+        write!(f, " }}")
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::{Interest, Ready};
+    use mio::unix::UnixReady;
+
+    #[test]
+    fn from_mio() {
+        let ready = Ready::from_mio(mio::Ready::readable() | UnixReady::hup());
+        assert!(ready.is_readable());
+        assert!(ready.is_read_closed());
+        assert!(!ready.is_write_closed());
+        assert!(!ready.is_writable());
+
+        let ready = Ready::from_mio(mio::Ready::writable() | UnixReady::error());
+        assert_eq!(ready, Ready::WRITABLE | Ready::ERROR);
+    }
+
+    #[test]
+    fn interest_to_mio() {
+        let ready = UnixReady::from(Interest::READABLE.to_mio());
+        assert!(ready.is_readable());
+        assert!(ready.is_hup());
+        assert!(!ready.is_writable());
+
+        let ready = UnixReady::from(Interest::WRITABLE.to_mio());
+        assert!(ready.is_writable());
+        assert!(!ready.is_readable());
+
+        // `UnixReady::priority` is the writable bit on Linux: only writable
+        // interest may register it, or writers would be woken spuriously
+        let interests = [
+            Interest::READABLE,
+            Interest::WRITABLE,
+            Interest::READABLE | Interest::WRITABLE,
+        ];
+        for &interest in &interests {
+            let ready = UnixReady::from(interest.to_mio());
+            assert_eq!(ready.is_writable(), interest.is_writable());
+        }
+    }
+
+    #[test]
+    fn debug() {
+        assert_eq!(format!("{:?}", Ready::EMPTY), "Ready { }");
+        assert_eq!(
+            format!("{:?}", Ready::READABLE | Ready::READ_CLOSED),
+            "Ready { READABLE | READ_CLOSED }"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Direction, HandlePriv};
+use super::{Direction, Handle, HandlePriv};
 
 use futures::task::LocalWaker;
 use futures::Poll;
@@ -116,6 +116,25 @@ impl Registration {
         self.register2(io, || HandlePriv::try_current())
     }
 
+    /// Register the I/O resource with the specified reactor.
+    ///
+    /// This function is safe to call concurrently and repeatedly. However, only
+    /// the first call will establish the registration. Subsequent calls will be
+    /// no-ops.
+    ///
+    /// If the registration happened successfully, `Ok(true)` is returned.
+    ///
+    /// If an I/O resource has previously been successfully registered,
+    /// `Ok(false)` is returned.
+    ///
+    /// If an error is encountered during registration, `Err` is returned.
+    pub fn register_with(&self, io: &impl Evented, handle: &Handle) -> io::Result<bool> {
+        self.register2(io, || match handle.as_priv() {
+            Some(handle) => Ok(handle.clone()),
+            None => HandlePriv::try_current(),
+        })
+    }
+
     /// Deregister the I/O resource from the reactor it is associated with.
     ///
     /// This function must be called before the I/O resource associated with the
@@ -0,0 +1,396 @@
+//! A runtime that runs tasks and the reactor on the current thread.
+//!
+//! [`Runtime`] multiplexes any number of tasks onto the thread which calls
+//! [`Runtime::block_on`] or [`Runtime::run`], and turns its own reactor
+//! whenever none of those tasks can make progress. I/O objects that bind
+//! lazily register with this reactor when they are first polled by one of
+//! the runtime's tasks.
+//!
+//! Because tasks never leave the current thread, they do not need to be
+//! `Send`. Tasks can be spawned from within other tasks using a [`Spawner`],
+//! and `Send` tasks can be spawned from other threads using a
+//! [`RemoteSpawner`].
+//!
+//! [`Runtime`]: struct.Runtime.html
+//! [`Runtime::block_on`]: struct.Runtime.html#method.block_on
+//! [`Runtime::run`]: struct.Runtime.html#method.run
+//! [`Spawner`]: struct.Spawner.html
+//! [`RemoteSpawner`]: struct.RemoteSpawner.html
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! #![feature(async_await, await_macro, futures_api)]
+//! use futures::prelude::*;
+//! use futures::task::LocalSpawnExt;
+//! use romio::runtime::current_thread::Runtime;
+//! use romio::tcp::{TcpListener, TcpStream};
+//!
+//! async fn say_hello(mut stream: TcpStream) {
+//!     await!(stream.write_all(b"Shall I hear more, or shall I speak at this?")).unwrap();
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+//! let mut runtime = Runtime::new()?;
+//! let mut spawner = runtime.spawner();
+//!
+//! let listener = TcpListener::bind(&"127.0.0.1:8080".parse()?)?;
+//! let mut incoming = listener.incoming();
+//!
+//! runtime.block_on(async move {
+//!     while let Some(Ok(stream)) = await!(incoming.next()) {
+//!         spawner.spawn_local(say_hello(stream)).unwrap();
+//!     }
+//! });
+//! # Ok(())}
+//! ```
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::executor;
+use futures::future::{FutureObj, LocalFutureObj};
+use futures::stream::{FuturesUnordered, StreamExt};
+use futures::task::{self, LocalSpawn, LocalWaker, Spawn, SpawnError, Wake};
+use futures::{Future, Poll};
+
+use crate::reactor::{self, Handle, Reactor};
+use crate::timer::Clock;
+
+/// A single-threaded runtime which drives tasks and a reactor.
+///
+/// See the [module documentation] for details.
+///
+/// [module documentation]: index.html
+pub struct Runtime {
+    /// The reactor, turned whenever no task can make progress.
+    reactor: Reactor,
+
+    /// Tasks which have been spawned onto the runtime.
+    pool: FuturesUnordered<LocalFutureObj<'static, ()>>,
+
+    /// Tasks which have been spawned but not yet added to `pool`.
+    incoming: Rc<Incoming>,
+
+    /// Tasks which have been spawned from other threads.
+    remote: Arc<Remote>,
+
+    /// Wakes up the runtime when a task is notified.
+    notify: Arc<Notify>,
+}
+
+/// A handle to a [`Runtime`] that can spawn tasks onto it.
+///
+/// Since the runtime runs all tasks on its own thread, a `Spawner` can
+/// spawn futures which are not `Send`, using [`LocalSpawn`].
+///
+/// [`Runtime`]: struct.Runtime.html
+/// [`LocalSpawn`]: https://docs.rs/futures-preview/0.3.0-alpha.10/futures/task/trait.LocalSpawn.html
+#[derive(Clone, Debug)]
+pub struct Spawner {
+    incoming: Weak<Incoming>,
+}
+
+/// A handle to a [`Runtime`] that can spawn tasks onto it from any thread.
+///
+/// Unlike [`Spawner`], a `RemoteSpawner` is `Send` and `Sync`, and can only
+/// spawn futures which are `Send`.
+///
+/// [`Runtime`]: struct.Runtime.html
+/// [`Spawner`]: struct.Spawner.html
+#[derive(Clone, Debug)]
+pub struct RemoteSpawner {
+    remote: Arc<Remote>,
+}
+
+type Incoming = RefCell<Vec<LocalFutureObj<'static, ()>>>;
+
+/// Queue of tasks spawned from other threads.
+struct Remote {
+    /// `None` once the runtime has been dropped.
+    queue: Mutex<Option<Vec<FutureObj<'static, ()>>>>,
+
+    notify: Arc<Notify>,
+}
+
+/// Wakes the runtime by waking up its reactor.
+struct Notify {
+    handle: Handle,
+
+    /// Set when a task has been notified since the runtime last polled.
+    woken: AtomicBool,
+}
+
+// ===== impl Runtime =====
+
+impl Runtime {
+    /// Creates a new runtime with its own reactor.
+    pub fn new() -> io::Result<Runtime> {
+        Runtime::new_with_clock(Clock::system())
+    }
+
+    /// Creates a new runtime with its own reactor, whose timers measure time
+    /// using `clock`.
+    ///
+    /// See [`Clock`] for details.
+    ///
+    /// [`Clock`]: ../../timer/struct.Clock.html
+    pub fn new_with_clock(clock: Clock) -> io::Result<Runtime> {
+        let reactor = Reactor::new_with_clock(clock)?;
+        let notify = Arc::new(Notify {
+            handle: reactor.handle(),
+            woken: AtomicBool::new(false),
+        });
+
+        let remote = Arc::new(Remote {
+            queue: Mutex::new(Some(Vec::new())),
+            notify: notify.clone(),
+        });
+
+        Ok(Runtime {
+            reactor,
+            pool: FuturesUnordered::new(),
+            incoming: Default::default(),
+            remote,
+            notify,
+        })
+    }
+
+    /// Returns a handle to the runtime's reactor.
+    ///
+    /// This can be used to bind I/O objects to the runtime's reactor before
+    /// they are polled by one of its tasks.
+    pub fn reactor_handle(&self) -> Handle {
+        self.reactor.handle()
+    }
+
+    /// Returns a spawner which spawns tasks onto this runtime.
+    pub fn spawner(&self) -> Spawner {
+        Spawner {
+            incoming: Rc::downgrade(&self.incoming),
+        }
+    }
+
+    /// Returns a spawner which spawns tasks onto this runtime from any
+    /// thread.
+    pub fn remote_spawner(&self) -> RemoteSpawner {
+        RemoteSpawner {
+            remote: self.remote.clone(),
+        }
+    }
+
+    /// Spawns a future onto the runtime.
+    ///
+    /// The future starts running the next time the runtime is run, by
+    /// [`block_on`] or [`run`].
+    ///
+    /// [`block_on`]: #method.block_on
+    /// [`run`]: #method.run
+    pub fn spawn<F>(&mut self, future: F) -> &mut Self
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        self.incoming
+            .borrow_mut()
+            .push(LocalFutureObj::new(Box::pinned(future)));
+        self
+    }
+
+    /// Runs the given future to completion on the current thread, running
+    /// spawned tasks and the reactor alongside it.
+    ///
+    /// The function returns once `future` completes; spawned tasks that have
+    /// not completed yet are left in the runtime, and continue running the
+    /// next time it is run.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called from within another executor, or
+    /// if a default reactor is already set for the current thread.
+    pub fn block_on<F: Future>(&mut self, mut future: F) -> F::Output {
+        // The future is shadowed, so it can never be moved after being pinned.
+        let mut future = unsafe { Pin::new_unchecked(&mut future) };
+
+        self.enter(|runtime, lw| {
+            if let Poll::Ready(output) = future.as_mut().poll(lw) {
+                return Poll::Ready(output);
+            }
+
+            let _ = runtime.poll_pool(lw);
+            Poll::Pending
+        })
+    }
+
+    /// Runs all spawned tasks to completion.
+    ///
+    /// This includes tasks which are spawned while the runtime is running.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called from within another executor, or
+    /// if a default reactor is already set for the current thread.
+    pub fn run(&mut self) {
+        self.enter(|runtime, lw| runtime.poll_pool(lw))
+    }
+
+    /// Sets up the execution context and runs `f` until it completes, turning
+    /// the reactor whenever `f` cannot make progress.
+    fn enter<T, F>(&mut self, mut f: F) -> T
+    where
+        F: FnMut(&mut Runtime, &LocalWaker) -> Poll<T>,
+    {
+        let _enter = executor::enter().expect(
+            "cannot execute `Runtime` executor from within \
+             another executor",
+        );
+
+        let handle = self.reactor.handle();
+        let lw = task::local_waker_from_nonlocal(self.notify.clone());
+
+        reactor::with_default(&handle, || loop {
+            self.notify.woken.store(false, SeqCst);
+
+            if let Poll::Ready(t) = f(self, &lw) {
+                return t;
+            }
+
+            // If a task was notified while polling, only check for I/O events
+            // that are already pending, without blocking.
+            let max_wait = if self.notify.woken.load(SeqCst) {
+                Some(Duration::from_millis(0))
+            } else {
+                None
+            };
+
+            self.reactor.turn(max_wait).expect("failed to turn reactor");
+        })
+    }
+
+    /// Makes as much progress as possible on all spawned tasks, returning
+    /// `Ready` once there are no tasks left.
+    fn poll_pool(&mut self, lw: &LocalWaker) -> Poll<()> {
+        loop {
+            // Add all newly spawned tasks to the pool
+            {
+                let mut incoming = self.incoming.borrow_mut();
+                for task in incoming.drain(..) {
+                    self.pool.push(task);
+                }
+            }
+
+            {
+                let mut queue = self.remote.queue.lock().unwrap();
+                for task in queue.as_mut().unwrap().drain(..) {
+                    self.pool.push(task.into());
+                }
+            }
+
+            let ret = self.pool.poll_next_unpin(lw);
+
+            // Tasks were spawned while polling the pool; add them and poll
+            // again.
+            if !self.incoming.borrow().is_empty() {
+                continue;
+            }
+
+            match ret {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Ready(Some(())) => {}
+            }
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // Reject tasks spawned from other threads from now on.
+        *self.remote.queue.lock().unwrap() = None;
+    }
+}
+
+impl fmt::Debug for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Runtime")
+            .field("reactor", &self.reactor)
+            .field("tasks", &self.pool.len())
+            .finish()
+    }
+}
+
+// ===== impl Spawner =====
+
+impl Spawn for Spawner {
+    fn spawn_obj(&mut self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.spawn_local_obj(future.into())
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        self.status_local()
+    }
+}
+
+impl LocalSpawn for Spawner {
+    fn spawn_local_obj(&mut self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+        match self.incoming.upgrade() {
+            Some(incoming) => {
+                incoming.borrow_mut().push(future);
+                Ok(())
+            }
+            None => Err(SpawnError::shutdown()),
+        }
+    }
+
+    fn status_local(&self) -> Result<(), SpawnError> {
+        if self.incoming.upgrade().is_some() {
+            Ok(())
+        } else {
+            Err(SpawnError::shutdown())
+        }
+    }
+}
+
+// ===== impl RemoteSpawner =====
+
+impl Spawn for RemoteSpawner {
+    fn spawn_obj(&mut self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        match *self.remote.queue.lock().unwrap() {
+            Some(ref mut queue) => queue.push(future),
+            None => return Err(SpawnError::shutdown()),
+        }
+
+        Wake::wake(&self.remote.notify);
+        Ok(())
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        if self.remote.queue.lock().unwrap().is_some() {
+            Ok(())
+        } else {
+            Err(SpawnError::shutdown())
+        }
+    }
+}
+
+impl fmt::Debug for Remote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Remote").finish()
+    }
+}
+
+// ===== impl Notify =====
+
+impl Wake for Notify {
+    fn wake(arc_self: &Arc<Self>) {
+        if !arc_self.woken.swap(true, SeqCst) {
+            arc_self.handle.wakeup();
+        }
+    }
+}
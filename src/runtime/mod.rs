@@ -0,0 +1,21 @@
+//! Runtimes which drive both tasks and the reactor.
+//!
+//! By default, romio's I/O objects are driven by a global reactor running on
+//! a background thread, and tasks are run by any executor, e.g. the ones
+//! found in `futures::executor`. Every readiness event then costs a wakeup
+//! across threads, from the reactor thread to the executor thread.
+//!
+//! The runtimes in this module avoid this by running the reactor on the same
+//! thread as the tasks: when there are no tasks ready to make progress, the
+//! runtime parks the thread by turning its reactor, rather than by blocking on
+//! a condition variable.
+//!
+//! - [`current_thread`] runs all tasks and the reactor on a single thread.
+//! - [`multi_thread`] runs a pool of worker threads, each with its own
+//!   reactor, and distributes tasks over them.
+//!
+//! [`current_thread`]: current_thread/index.html
+//! [`multi_thread`]: multi_thread/index.html
+
+pub mod current_thread;
+pub mod multi_thread;
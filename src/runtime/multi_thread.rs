@@ -0,0 +1,326 @@
+//! A runtime that runs tasks on a pool of worker threads, each with its own
+//! reactor.
+//!
+//! Every worker thread runs a [`current_thread::Runtime`], which owns a
+//! reactor of its own. Spawned tasks are distributed over the workers in a
+//! round-robin fashion, and then stay on the worker they were assigned to.
+//! I/O objects which bind lazily register with the reactor of the worker
+//! whose task first polls them, so all readiness events for a connection are
+//! received on the thread that handles it, and there is no single reactor
+//! thread that all I/O funnels through. Idle workers park by turning their
+//! reactor.
+//!
+//! [`current_thread::Runtime`]: ../current_thread/struct.Runtime.html
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! #![feature(async_await, await_macro, futures_api)]
+//! use futures::prelude::*;
+//! use futures::task::SpawnExt;
+//! use romio::runtime::multi_thread::Runtime;
+//! use romio::tcp::{TcpListener, TcpStream};
+//!
+//! async fn say_hello(mut stream: TcpStream) {
+//!     await!(stream.write_all(b"Shall I hear more, or shall I speak at this?")).unwrap();
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+//! let runtime = Runtime::new()?;
+//! let mut spawner = runtime.spawner();
+//!
+//! let listener = TcpListener::bind(&"127.0.0.1:8080".parse()?)?;
+//! let mut incoming = listener.incoming();
+//!
+//! runtime.block_on(async move {
+//!     while let Some(Ok(stream)) = await!(incoming.next()) {
+//!         spawner.spawn(say_hello(stream)).unwrap();
+//!     }
+//! });
+//! # Ok(())}
+//! ```
+
+use std::io;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use futures::channel::oneshot;
+use futures::executor;
+use futures::future::FutureObj;
+use futures::task::{Spawn, SpawnError, SpawnExt};
+use futures::{Future, FutureExt};
+use log::debug;
+use num_cpus;
+
+use super::current_thread::{self, RemoteSpawner};
+use crate::timer::Clock;
+
+/// A multi-threaded runtime with one reactor per worker thread.
+///
+/// See the [module documentation] for details.
+///
+/// [module documentation]: index.html
+#[derive(Debug)]
+pub struct Runtime {
+    workers: Vec<Worker>,
+    spawner: Spawner,
+}
+
+/// Builds a [`Runtime`] with custom configuration values.
+///
+/// [`Runtime`]: struct.Runtime.html
+#[derive(Debug)]
+pub struct Builder {
+    /// The number of worker threads.
+    workers: usize,
+
+    /// Prefix used for the names of the worker threads.
+    name_prefix: String,
+
+    /// The clock shared by the reactors of all workers.
+    clock: Clock,
+}
+
+/// A handle to a [`Runtime`] that can spawn tasks onto its workers.
+///
+/// Spawned tasks are assigned to the workers in a round-robin fashion.
+///
+/// [`Runtime`]: struct.Runtime.html
+#[derive(Clone, Debug)]
+pub struct Spawner {
+    inner: Arc<SpawnerInner>,
+}
+
+#[derive(Debug)]
+struct SpawnerInner {
+    workers: Vec<RemoteSpawner>,
+
+    /// Index of the worker which receives the next task.
+    next: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Worker {
+    thread: thread::JoinHandle<()>,
+    shutdown: oneshot::Sender<Shutdown>,
+}
+
+/// How a worker should shut down.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Shutdown {
+    /// Run the worker's tasks to completion first.
+    OnIdle,
+
+    /// Drop the worker's tasks.
+    Now,
+}
+
+// ===== impl Builder =====
+
+impl Builder {
+    /// Returns a new builder with the default configuration: one worker
+    /// thread per CPU core.
+    pub fn new() -> Builder {
+        Builder {
+            workers: num_cpus::get(),
+            name_prefix: "romio-worker-".to_string(),
+            clock: Clock::system(),
+        }
+    }
+
+    /// Sets the number of worker threads.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `workers` is 0.
+    pub fn workers(&mut self, workers: usize) -> &mut Self {
+        assert!(workers > 0, "a runtime needs at least one worker");
+        self.workers = workers;
+        self
+    }
+
+    /// Sets the prefix of the names of the worker threads.
+    ///
+    /// Worker threads are named by appending their index to the prefix.
+    pub fn name_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.name_prefix = prefix.into();
+        self
+    }
+
+    /// Sets the clock used by the timers of all workers.
+    ///
+    /// See [`Clock`] for details.
+    ///
+    /// [`Clock`]: ../../timer/struct.Clock.html
+    pub fn clock(&mut self, clock: Clock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Creates the runtime, starting its worker threads.
+    pub fn build(&self) -> io::Result<Runtime> {
+        let mut workers = Vec::with_capacity(self.workers);
+        let mut spawners = Vec::with_capacity(self.workers);
+
+        for i in 0..self.workers {
+            let (tx, rx) = mpsc::channel();
+            let (shutdown_tx, shutdown_rx) = oneshot::channel();
+            let clock = self.clock.clone();
+
+            let thread = thread::Builder::new()
+                .name(format!("{}{}", self.name_prefix, i))
+                .spawn(move || run(clock, tx, shutdown_rx))?;
+
+            // The worker reports back once its runtime has been created.
+            let spawner = match rx.recv() {
+                Ok(res) => res?,
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "worker thread failed to start",
+                    ))
+                }
+            };
+
+            spawners.push(spawner);
+            workers.push(Worker {
+                thread,
+                shutdown: shutdown_tx,
+            });
+        }
+
+        let spawner = Spawner {
+            inner: Arc::new(SpawnerInner {
+                workers: spawners,
+                next: AtomicUsize::new(0),
+            }),
+        };
+
+        Ok(Runtime { workers, spawner })
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Builder {
+        Builder::new()
+    }
+}
+
+// ===== impl Runtime =====
+
+impl Runtime {
+    /// Creates a new runtime with one worker thread per CPU core.
+    pub fn new() -> io::Result<Runtime> {
+        Builder::new().build()
+    }
+
+    /// Returns a spawner which spawns tasks onto the runtime's workers.
+    pub fn spawner(&self) -> Spawner {
+        self.spawner.clone()
+    }
+
+    /// Spawns a future onto one of the runtime's workers.
+    pub fn spawn<F>(&self, future: F) -> &Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawner
+            .clone()
+            .spawn(future)
+            .expect("runtime workers have shut down");
+        self
+    }
+
+    /// Runs the given future to completion on one of the runtime's workers,
+    /// blocking the current thread until it completes.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if it is called from within an executor, or if
+    /// the worker running the future shuts down before it completes.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future + Send + 'static,
+        F::Output: Send,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.spawn(future.map(move |output| {
+            let _ = tx.send(output);
+        }));
+
+        executor::block_on(rx).expect("runtime worker shut down")
+    }
+
+    /// Shuts the runtime down once all spawned tasks have completed, blocking
+    /// until all worker threads have exited.
+    pub fn shutdown_on_idle(mut self) {
+        self.shutdown(Shutdown::OnIdle)
+    }
+
+    /// Shuts the runtime down immediately, dropping all spawned tasks, and
+    /// blocks until all worker threads have exited.
+    pub fn shutdown_now(mut self) {
+        self.shutdown(Shutdown::Now)
+    }
+
+    fn shutdown(&mut self, how: Shutdown) {
+        for worker in self.workers.drain(..) {
+            let _ = worker.shutdown.send(how);
+            let _ = worker.thread.join();
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        self.shutdown(Shutdown::Now)
+    }
+}
+
+// ===== impl Spawner =====
+
+impl Spawn for Spawner {
+    fn spawn_obj(&mut self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        let workers = &self.inner.workers;
+        let next = self.inner.next.fetch_add(1, Relaxed);
+
+        workers[next % workers.len()].clone().spawn_obj(future)
+    }
+
+    fn status(&self) -> Result<(), SpawnError> {
+        self.inner.workers[0].status()
+    }
+}
+
+// ===== worker thread =====
+
+fn run(
+    clock: Clock,
+    tx: mpsc::Sender<io::Result<RemoteSpawner>>,
+    shutdown: oneshot::Receiver<Shutdown>,
+) {
+    let mut runtime = match current_thread::Runtime::new_with_clock(clock) {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            let _ = tx.send(Err(e));
+            return;
+        }
+    };
+
+    let _ = tx.send(Ok(runtime.remote_spawner()));
+
+    debug!("starting runtime worker");
+
+    // If the `Runtime` goes away without sending a signal, shut down
+    // immediately.
+    let how = runtime.block_on(shutdown).unwrap_or(Shutdown::Now);
+
+    if how == Shutdown::OnIdle {
+        runtime.run();
+    }
+
+    debug!("runtime worker has shut down");
+}
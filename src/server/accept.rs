@@ -0,0 +1,229 @@
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll};
+use log::{debug, error};
+
+use crate::timer::{self, Delay};
+
+/// How long to stop accepting connections after running out of resources,
+/// by default.
+const BACKOFF_MS: u64 = 1000;
+
+/// How an `Incoming` stream handles the errors of `accept`.
+///
+/// Without a policy, every error is yielded by the stream. With one, errors
+/// which only concern the connection being accepted, such as `ECONNABORTED`,
+/// are logged and skipped. Errors caused by a lack of resources, such as
+/// `EMFILE` or `ENOBUFS`, are logged, and accepting stops for the [backoff]
+/// period: as the listener stays readable, retrying right away would only
+/// spin. Other errors are still yielded.
+///
+/// [backoff]: #method.set_backoff
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::prelude::*;
+/// use romio::server::AcceptPolicy;
+/// use romio::TcpListener;
+/// use std::time::Duration;
+///
+/// async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
+///     let mut policy = AcceptPolicy::new();
+///     policy
+///         .set_backoff(Duration::from_millis(500))
+///         .on_fd_exhaustion(|err| eprintln!("out of file descriptors: {}", err));
+///
+///     let listener = TcpListener::bind(&"127.0.0.1:0".parse()?)?;
+///     let mut incoming = listener.incoming().accept_policy(policy);
+///
+///     while let Some(stream) = await!(incoming.next()) {
+///         let stream = stream?;
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AcceptPolicy {
+    backoff: Duration,
+    on_fd_exhaustion: Option<Arc<dyn Fn(&io::Error) + Send + Sync>>,
+}
+
+/// Applies an `AcceptPolicy` to the accept calls of an `Incoming` stream.
+#[derive(Debug, Default)]
+pub(crate) struct Accept {
+    policy: Option<AcceptPolicy>,
+
+    /// Fires when accepting can resume after running out of resources.
+    backoff: Option<Delay>,
+}
+
+/// How an accept error is handled.
+#[derive(Debug, PartialEq)]
+enum ErrorClass {
+    /// The connection failed before being accepted.
+    Connection,
+
+    /// The process or the system ran out of a resource.
+    Resources { fd_exhaustion: bool },
+
+    Fatal,
+}
+
+impl AcceptPolicy {
+    /// Creates a policy which backs off for one second after running out of
+    /// resources.
+    pub fn new() -> AcceptPolicy {
+        AcceptPolicy {
+            backoff: Duration::from_millis(BACKOFF_MS),
+            on_fd_exhaustion: None,
+        }
+    }
+
+    /// Sets how long to stop accepting connections after running out of
+    /// resources.
+    pub fn set_backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets a function to call when accepting a connection fails because the
+    /// process or the system has run out of file descriptors (`EMFILE` or
+    /// `ENFILE`).
+    ///
+    /// The function is called before backing off, from the task polling the
+    /// `Incoming` stream.
+    pub fn on_fd_exhaustion<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&io::Error) + Send + Sync + 'static,
+    {
+        self.on_fd_exhaustion = Some(Arc::new(f));
+        self
+    }
+}
+
+impl Default for AcceptPolicy {
+    fn default() -> AcceptPolicy {
+        AcceptPolicy::new()
+    }
+}
+
+impl fmt::Debug for AcceptPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AcceptPolicy")
+            .field("backoff", &self.backoff)
+            .field("on_fd_exhaustion", &self.on_fd_exhaustion.is_some())
+            .finish()
+    }
+}
+
+impl Accept {
+    pub fn set_policy(&mut self, policy: AcceptPolicy) {
+        self.policy = Some(policy);
+    }
+
+    /// Polls `accept` until it succeeds or fails with an error the policy
+    /// doesn't handle.
+    pub fn poll_accept<T, F>(&mut self, lw: &LocalWaker, mut accept: F) -> Poll<io::Result<T>>
+    where
+        F: FnMut(&LocalWaker) -> Poll<io::Result<T>>,
+    {
+        let policy = match self.policy {
+            Some(ref policy) => policy,
+            None => return accept(lw),
+        };
+
+        loop {
+            if let Some(ref mut backoff) = self.backoff {
+                ready!(Pin::new(backoff).poll(lw));
+                self.backoff = None;
+            }
+
+            let err = match ready!(accept(lw)) {
+                Ok(accepted) => return Poll::Ready(Ok(accepted)),
+                Err(err) => err,
+            };
+
+            match classify(&err) {
+                ErrorClass::Connection => debug!("skipping failed connection: {}", err),
+                ErrorClass::Resources { fd_exhaustion } => {
+                    error!(
+                        "failed to accept a connection, backing off for {:?}: {}",
+                        policy.backoff, err
+                    );
+
+                    if fd_exhaustion {
+                        if let Some(ref f) = policy.on_fd_exhaustion {
+                            f(&err);
+                        }
+                    }
+
+                    self.backoff = Some(timer::sleep(policy.backoff));
+                }
+                ErrorClass::Fatal => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+}
+
+fn classify(err: &io::Error) -> ErrorClass {
+    match err.kind() {
+        io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::Interrupted => return ErrorClass::Connection,
+        _ => {}
+    }
+
+    #[cfg(unix)]
+    {
+        match err.raw_os_error() {
+            Some(libc::EMFILE) | Some(libc::ENFILE) => {
+                return ErrorClass::Resources {
+                    fd_exhaustion: true,
+                }
+            }
+            Some(libc::ENOBUFS) | Some(libc::ENOMEM) => {
+                return ErrorClass::Resources {
+                    fd_exhaustion: false,
+                }
+            }
+            Some(libc::EPROTO) => return ErrorClass::Connection,
+            _ => {}
+        }
+    }
+
+    ErrorClass::Fatal
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::{classify, ErrorClass};
+    use std::io;
+
+    #[test]
+    fn classify_errors() {
+        let os = io::Error::from_raw_os_error;
+
+        assert_eq!(classify(&os(libc::ECONNABORTED)), ErrorClass::Connection);
+        assert_eq!(
+            classify(&os(libc::EMFILE)),
+            ErrorClass::Resources {
+                fd_exhaustion: true
+            }
+        );
+        assert_eq!(
+            classify(&os(libc::ENOBUFS)),
+            ErrorClass::Resources {
+                fd_exhaustion: false
+            }
+        );
+        assert_eq!(classify(&os(libc::EBADF)), ErrorClass::Fatal);
+    }
+}
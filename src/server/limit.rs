@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::io;
+use std::marker::Unpin;
+use std::net::IpAddr;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::stream::Stream;
+use futures::task::{LocalWaker, Waker};
+use futures::{ready, Poll};
+use iovec::IoVec;
+use log::debug;
+
+use super::sealed::Peer;
+use super::{ShutdownController, UntilShutdown};
+
+/// The largest number of connections over the per-IP limit which are closed
+/// in one call to `poll_next`, before yielding to other tasks.
+const MAX_REJECTS_PER_POLL: usize = 32;
+
+/// A limit on the number of live connections accepted by `Incoming` streams.
+///
+/// Once the limit is reached, a limited stream stops accepting connections
+/// until one of them is dropped, leaving new connections in the listen
+/// backlog of the kernel. Connections are yielded wrapped in [`Limited`],
+/// which holds a [`Permit`] until it is dropped.
+///
+/// A limit can also be set on the number of connections from each peer IP
+/// address: connections over that limit are closed as soon as they are
+/// accepted. A limited stream closes a bounded number of them each time it is
+/// polled, so that a flood of connections from one address doesn't starve
+/// the other tasks of the executor.
+///
+/// Cloning a `ConnectionLimit` returns a handle to the same counts, so that
+/// several streams can share a limit. The limit is checked before accepting
+/// each connection, so streams sharing a limit may each exceed it by one
+/// connection when they accept connections at the same time.
+///
+/// [`Limited`]: struct.Limited.html
+/// [`Permit`]: struct.Permit.html
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::prelude::*;
+/// use romio::server::ConnectionLimit;
+/// use romio::TcpListener;
+///
+/// async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
+///     let mut limit = ConnectionLimit::new(1000);
+///     limit.set_per_ip_limit(10);
+///
+///     let listener = TcpListener::bind(&"127.0.0.1:0".parse()?)?;
+///     let mut incoming = listener.incoming().limit(&limit);
+///
+///     while let Some(stream) = await!(incoming.next()) {
+///         // the permit is released when `stream` is dropped
+///         let stream = stream?;
+///         // ...
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConnectionLimit {
+    max: usize,
+    per_ip: Option<usize>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// The stream returned by the `limit` method of `Incoming` streams.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct LimitedIncoming<I> {
+    incoming: I,
+    limit: ConnectionLimit,
+}
+
+/// A connection accepted by a `LimitedIncoming` stream, which counts against
+/// the limit until it is dropped.
+///
+/// A `Limited` connection dereferences to the underlying stream, and can be
+/// read from and written to like it.
+#[derive(Debug)]
+pub struct Limited<S> {
+    stream: S,
+    permit: Permit,
+}
+
+/// Holds a place under a `ConnectionLimit`, which is released when the
+/// permit is dropped.
+#[derive(Debug)]
+pub struct Permit {
+    shared: Arc<Mutex<Shared>>,
+    ip: Option<IpAddr>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    active: usize,
+
+    /// The number of live connections by peer address, when they are limited.
+    per_ip: HashMap<IpAddr, usize>,
+
+    /// The tasks waiting for a connection to be dropped.
+    waiters: Vec<Waker>,
+}
+
+impl ConnectionLimit {
+    /// Creates a limit of `max` live connections.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max` is 0.
+    pub fn new(max: usize) -> ConnectionLimit {
+        assert!(max > 0, "the connection limit must be at least 1");
+
+        ConnectionLimit {
+            max,
+            per_ip: None,
+            shared: Arc::new(Mutex::new(Shared::default())),
+        }
+    }
+
+    /// Sets the largest number of live connections from a single peer IP
+    /// address.
+    ///
+    /// This only applies to streams which are limited afterwards, and to TCP
+    /// connections.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `max` is 0.
+    pub fn set_per_ip_limit(&mut self, max: usize) -> &mut Self {
+        assert!(max > 0, "the connection limit must be at least 1");
+        self.per_ip = Some(max);
+        self
+    }
+
+    /// Returns the number of live connections.
+    pub fn active(&self) -> usize {
+        self.shared.lock().unwrap().active
+    }
+}
+
+impl<I> LimitedIncoming<I> {
+    pub(crate) fn new(incoming: I, limit: &ConnectionLimit) -> LimitedIncoming<I> {
+        LimitedIncoming {
+            incoming,
+            limit: limit.clone(),
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &I {
+        &self.incoming
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.incoming
+    }
+
+    /// Consumes the `LimitedIncoming`, returning the underlying stream.
+    pub fn into_inner(self) -> I {
+        self.incoming
+    }
+
+    /// Ends this stream when the shutdown of `controller` begins.
+    ///
+    /// See [`ShutdownController`] for details.
+    ///
+    /// [`ShutdownController`]: struct.ShutdownController.html
+    pub fn until_shutdown(self, controller: &ShutdownController) -> UntilShutdown<Self> {
+        UntilShutdown::new(self, controller)
+    }
+}
+
+impl<I, S> Stream for LimitedIncoming<I>
+where
+    I: Stream<Item = io::Result<S>> + Unpin,
+    S: Peer,
+{
+    type Item = io::Result<Limited<S>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let limit = &this.limit;
+        let mut rejected = 0;
+
+        loop {
+            {
+                let mut shared = limit.shared.lock().unwrap();
+                if shared.active >= limit.max {
+                    if !shared.waiters.iter().any(|w| lw.will_wake_nonlocal(w)) {
+                        shared.waiters.push(lw.clone().into_waker());
+                    }
+                    return Poll::Pending;
+                }
+            }
+
+            let stream = match ready!(Pin::new(&mut this.incoming).poll_next(lw)) {
+                Some(Ok(stream)) => stream,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            };
+
+            let ip = match limit.per_ip {
+                Some(_) => stream.peer_ip(),
+                None => None,
+            };
+
+            let mut shared = limit.shared.lock().unwrap();
+
+            if let (Some(ip), Some(max)) = (ip, limit.per_ip) {
+                let count = shared.per_ip.entry(ip).or_insert(0);
+                if *count >= max {
+                    debug!("closing connection from {}: too many connections", ip);
+                    rejected += 1;
+                    if rejected == MAX_REJECTS_PER_POLL {
+                        lw.wake();
+                        return Poll::Pending;
+                    }
+                    continue;
+                }
+                *count += 1;
+            }
+            shared.active += 1;
+
+            let permit = Permit {
+                shared: limit.shared.clone(),
+                ip,
+            };
+            return Poll::Ready(Some(Ok(Limited { stream, permit })));
+        }
+    }
+}
+
+impl<S> Limited<S> {
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Splits the connection into the underlying stream and its permit.
+    ///
+    /// The connection counts against the limit until the permit is dropped.
+    pub fn into_parts(self) -> (S, Permit) {
+        (self.stream, self.permit)
+    }
+}
+
+impl<S> Deref for Limited<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S> DerefMut for Limited<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for Limited<S> {
+    fn poll_read(&mut self, lw: &LocalWaker, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.stream.poll_read(lw, buf)
+    }
+
+    fn poll_vectored_read(
+        &mut self,
+        lw: &LocalWaker,
+        vec: &mut [&mut IoVec],
+    ) -> Poll<io::Result<usize>> {
+        self.stream.poll_vectored_read(lw, vec)
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for Limited<S> {
+    fn poll_write(&mut self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.stream.poll_write(lw, buf)
+    }
+
+    fn poll_vectored_write(&mut self, lw: &LocalWaker, vec: &[&IoVec]) -> Poll<io::Result<usize>> {
+        self.stream.poll_vectored_write(lw, vec)
+    }
+
+    fn poll_flush(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.stream.poll_flush(lw)
+    }
+
+    fn poll_close(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.stream.poll_close(lw)
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        shared.active -= 1;
+
+        if let Some(ip) = self.ip {
+            let remove = match shared.per_ip.get_mut(&ip) {
+                Some(count) => {
+                    *count -= 1;
+                    *count == 0
+                }
+                None => false,
+            };
+            if remove {
+                shared.per_ip.remove(&ip);
+            }
+        }
+
+        for waker in shared.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Utilities for servers accepting connections.
+//!
+//! - An [`AcceptPolicy`] makes the `Incoming` streams of listeners resilient
+//!   to transient `accept` errors, such as running out of file descriptors.
+//! - A [`ConnectionLimit`] caps the number of live connections accepted by
+//!   `Incoming` streams, overall and by peer address.
+//! - A [`ShutdownController`] stops `Incoming` streams and drains in-flight
+//!   connections, before shutting down the reactor.
+//!
+//! [`AcceptPolicy`]: struct.AcceptPolicy.html
+//! [`ConnectionLimit`]: struct.ConnectionLimit.html
+//! [`ShutdownController`]: struct.ShutdownController.html
+
+mod accept;
+mod limit;
+mod shutdown;
+
+pub use self::accept::AcceptPolicy;
+pub use self::limit::{ConnectionLimit, Limited, LimitedIncoming, Permit};
+pub use self::shutdown::{Drain, ReactorShutdown, ShutdownController, Signal, UntilShutdown};
+
+pub(crate) use self::accept::Accept;
+
+pub(crate) mod sealed {
+    use std::net::IpAddr;
+
+    /// A connection yielded by an `Incoming` stream.
+    pub trait Peer {
+        /// Returns the IP address of the peer, for TCP connections.
+        fn peer_ip(&self) -> Option<IpAddr>;
+    }
+
+    impl Peer for crate::tcp::TcpStream {
+        fn peer_ip(&self) -> Option<IpAddr> {
+            self.peer_addr().ok().map(|addr| addr.ip())
+        }
+    }
+
+    #[cfg(unix)]
+    impl Peer for crate::uds::UnixStream {
+        fn peer_ip(&self) -> Option<IpAddr> {
+            None
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    impl Peer for crate::uds::UnixSeqpacket {
+        fn peer_ip(&self) -> Option<IpAddr> {
+            None
+        }
+    }
+}
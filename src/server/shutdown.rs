@@ -0,0 +1,406 @@
+use std::collections::HashMap;
+use std::io;
+use std::marker::Unpin;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::stream::Stream;
+use futures::task::{LocalWaker, Waker};
+use futures::{Future, Poll};
+use log::debug;
+
+use crate::reactor::{self, Background};
+use crate::timer::{Elapsed, Timeout};
+
+/// Coordinates the graceful shutdown of a server.
+///
+/// A server stops accepting connections when its `Incoming` streams, wrapped
+/// with [`until_shutdown`], end. Each connection task holds a [`Signal`],
+/// which resolves when the shutdown begins so that the task can finish its
+/// current request and close the connection. [`shutdown`] begins the
+/// shutdown, and returns a future which waits for every signal to be dropped,
+/// up to a deadline. The background reactor the server runs on can then be
+/// shut down with [`Drain::shutdown_reactor`].
+///
+/// Cloning a `ShutdownController` returns a handle to the same shutdown, so
+/// that it can be triggered from another task, e.g. one handling `SIGTERM`.
+///
+/// [`until_shutdown`]: ../tcp/struct.Incoming.html#method.until_shutdown
+/// [`Signal`]: struct.Signal.html
+/// [`shutdown`]: #method.shutdown
+/// [`Drain::shutdown_reactor`]: struct.Drain.html#method.shutdown_reactor
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::prelude::*;
+/// use romio::server::{ShutdownController, Signal};
+/// use romio::{TcpListener, TcpStream};
+/// use std::time::Duration;
+///
+/// async fn listen(controller: ShutdownController) -> Result<(), Box<dyn std::error::Error + 'static>> {
+///     let listener = TcpListener::bind(&"127.0.0.1:0".parse()?)?;
+///     let mut incoming = listener.incoming().until_shutdown(&controller);
+///
+///     // the stream ends when the shutdown begins
+///     while let Some(stream) = await!(incoming.next()) {
+///         let signal = controller.signal();
+///         // spawn a task running `handle(stream?, signal)`
+///     }
+///     Ok(())
+/// }
+///
+/// async fn handle(stream: TcpStream, signal: Signal) {
+///     // serve requests until `signal` resolves, then close the connection
+/// }
+///
+/// async fn stop(controller: ShutdownController) {
+///     if await!(controller.shutdown(Duration::from_secs(30))).is_err() {
+///         eprintln!("{} connections are still open", controller.active());
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShutdownController {
+    shared: Arc<Mutex<Shared>>,
+}
+
+/// Held by an in-flight connection task, resolves when the shutdown begins.
+///
+/// The connection counts as in flight until its signal is dropped.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Signal {
+    waiter: Waiter,
+}
+
+/// The stream returned by the `until_shutdown` method of `Incoming` streams,
+/// which ends when the shutdown begins.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct UntilShutdown<I> {
+    incoming: I,
+    waiter: Waiter,
+}
+
+/// A future which resolves once every in-flight connection has dropped its
+/// [`Signal`], or fails with [`Elapsed`] when the deadline is reached first.
+///
+/// [`Signal`]: struct.Signal.html
+/// [`Elapsed`]: ../timer/struct.Elapsed.html
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct Drain {
+    inner: Timeout<Drained>,
+}
+
+/// A future which drains the connections of a server, then shuts down the
+/// background reactor it runs on.
+///
+/// It resolves to the result of the drain, once the reactor has shut down.
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct ReactorShutdown {
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    Draining(Drain, Background),
+    ShuttingDown(reactor::Shutdown, Result<(), Elapsed>),
+    Done,
+}
+
+/// Wakes a task when the connections have been drained.
+#[derive(Debug)]
+struct Drained {
+    shared: Arc<Mutex<Shared>>,
+    id: usize,
+}
+
+/// Wakes a task when the shutdown begins.
+#[derive(Debug)]
+struct Waiter {
+    shared: Arc<Mutex<Shared>>,
+    id: usize,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    shutdown: bool,
+
+    /// The number of signals which haven't been dropped.
+    active: usize,
+
+    /// The tasks waiting for the shutdown to begin, by waiter.
+    waiters: HashMap<usize, Waker>,
+    next_id: usize,
+
+    /// The tasks waiting for the connections to be drained, by drain.
+    drains: HashMap<usize, Waker>,
+}
+
+impl ShutdownController {
+    /// Creates a controller for a server which is running.
+    pub fn new() -> ShutdownController {
+        ShutdownController {
+            shared: Arc::new(Mutex::new(Shared::default())),
+        }
+    }
+
+    /// Returns a signal for a new in-flight connection.
+    ///
+    /// Signals returned after the shutdown has begun resolve immediately, but
+    /// are still waited for by [`Drain`].
+    ///
+    /// [`Drain`]: struct.Drain.html
+    pub fn signal(&self) -> Signal {
+        self.shared.lock().unwrap().active += 1;
+        Signal {
+            waiter: Waiter::new(&self.shared),
+        }
+    }
+
+    /// Begins the shutdown, and returns a future which waits for in-flight
+    /// connections to drop their signals for up to `timeout`.
+    ///
+    /// This ends the `Incoming` streams and resolves the signals associated
+    /// with this controller. Calling it again returns another future waiting
+    /// for the same connections.
+    pub fn shutdown(&self, timeout: Duration) -> Drain {
+        let waiters = {
+            let mut shared = self.shared.lock().unwrap();
+            if !shared.shutdown {
+                debug!("shutting down, {} connections in flight", shared.active);
+            }
+            shared.shutdown = true;
+            mem::replace(&mut shared.waiters, HashMap::new())
+        };
+
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+
+        Drain {
+            inner: Timeout::new(Drained::new(&self.shared), timeout),
+        }
+    }
+
+    /// Returns whether the shutdown has begun.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shared.lock().unwrap().shutdown
+    }
+
+    /// Returns the number of in-flight connections.
+    pub fn active(&self) -> usize {
+        self.shared.lock().unwrap().active
+    }
+}
+
+impl Default for ShutdownController {
+    fn default() -> ShutdownController {
+        ShutdownController::new()
+    }
+}
+
+impl Signal {
+    /// Returns whether the shutdown has begun.
+    pub fn is_shutting_down(&self) -> bool {
+        self.waiter.shared.lock().unwrap().shutdown
+    }
+}
+
+impl Future for Signal {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<()> {
+        self.waiter.poll_shutdown(lw)
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        let drains = {
+            let mut shared = self.waiter.shared.lock().unwrap();
+            shared.active -= 1;
+            if !shared.shutdown || shared.active > 0 {
+                return;
+            }
+            mem::replace(&mut shared.drains, HashMap::new())
+        };
+
+        for (_, waker) in drains {
+            waker.wake();
+        }
+    }
+}
+
+impl<I> UntilShutdown<I> {
+    pub(crate) fn new(incoming: I, controller: &ShutdownController) -> UntilShutdown<I> {
+        UntilShutdown {
+            incoming,
+            waiter: Waiter::new(&controller.shared),
+        }
+    }
+
+    /// Returns a reference to the underlying stream.
+    pub fn get_ref(&self) -> &I {
+        &self.incoming
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.incoming
+    }
+
+    /// Consumes the `UntilShutdown`, returning the underlying stream.
+    pub fn into_inner(self) -> I {
+        self.incoming
+    }
+}
+
+impl<I, S> Stream for UntilShutdown<I>
+where
+    I: Stream<Item = io::Result<S>> + Unpin,
+{
+    type Item = io::Result<S>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.waiter.poll_shutdown(lw).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        Pin::new(&mut this.incoming).poll_next(lw)
+    }
+}
+
+impl Drain {
+    /// Shuts down `background` once the connections have been drained, or
+    /// the deadline has been reached.
+    ///
+    /// The reactor is shut down immediately rather than on idle, as the
+    /// listeners and any connection still open keep it busy.
+    pub fn shutdown_reactor(self, background: Background) -> ReactorShutdown {
+        ReactorShutdown {
+            state: State::Draining(self, background),
+        }
+    }
+}
+
+impl Future for Drain {
+    type Output = Result<(), Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner).poll(lw)
+    }
+}
+
+impl Future for ReactorShutdown {
+    type Output = Result<(), Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        loop {
+            self.state = match mem::replace(&mut self.state, State::Done) {
+                State::Draining(mut drain, background) => {
+                    match Pin::new(&mut drain).poll(lw) {
+                        Poll::Ready(res) => State::ShuttingDown(background.shutdown_now(), res),
+                        Poll::Pending => {
+                            self.state = State::Draining(drain, background);
+                            return Poll::Pending;
+                        }
+                    }
+                }
+                State::ShuttingDown(mut shutdown, res) => {
+                    match Pin::new(&mut shutdown).poll(lw) {
+                        Poll::Ready(_) => return Poll::Ready(res),
+                        Poll::Pending => {
+                            self.state = State::ShuttingDown(shutdown, res);
+                            return Poll::Pending;
+                        }
+                    }
+                }
+                State::Done => panic!("polled a ReactorShutdown after completion"),
+            }
+        }
+    }
+}
+
+impl Drained {
+    fn new(shared: &Arc<Mutex<Shared>>) -> Drained {
+        Drained {
+            shared: shared.clone(),
+            id: shared.lock().unwrap().next_id(),
+        }
+    }
+}
+
+impl Future for Drained {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<()> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.active == 0 {
+            return Poll::Ready(());
+        }
+
+        let waker = shared
+            .drains
+            .entry(self.id)
+            .or_insert_with(|| lw.clone().into_waker());
+        if !lw.will_wake_nonlocal(waker) {
+            *waker = lw.clone().into_waker();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Drained {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().drains.remove(&self.id);
+    }
+}
+
+impl Waiter {
+    fn new(shared: &Arc<Mutex<Shared>>) -> Waiter {
+        Waiter {
+            shared: shared.clone(),
+            id: shared.lock().unwrap().next_id(),
+        }
+    }
+
+    fn poll_shutdown(&mut self, lw: &LocalWaker) -> Poll<()> {
+        let mut shared = self.shared.lock().unwrap();
+        if shared.shutdown {
+            return Poll::Ready(());
+        }
+
+        let waker = shared
+            .waiters
+            .entry(self.id)
+            .or_insert_with(|| lw.clone().into_waker());
+        if !lw.will_wake_nonlocal(waker) {
+            *waker = lw.clone().into_waker();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().waiters.remove(&self.id);
+    }
+}
+
+impl Shared {
+    /// Returns the id of a new waiter or drain.
+    fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
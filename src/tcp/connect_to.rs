@@ -0,0 +1,290 @@
+use super::{ConnectFuture, TcpStream};
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+use crate::dns::{self, Resolve, ToSocketAddrs};
+use crate::reactor::Handle;
+use crate::timer::{self, Delay};
+
+/// The default delay between two connection attempts, as recommended by
+/// RFC 8305.
+const ATTEMPT_DELAY_MS: u64 = 250;
+
+/// The future returned by `TcpStream::connect_to`, which connects to the first
+/// of several addresses that accepts the connection.
+///
+/// The addresses are tried following the "Happy Eyeballs" algorithm of
+/// [RFC 8305]: IPv6 and IPv4 addresses are interleaved, starting with the
+/// family of the first address, and a new connection attempt is started
+/// whenever the previous one fails or hasn't completed within the
+/// [attempt delay], without cancelling the attempts which are still in
+/// progress. The first attempt to succeed wins, and the others are dropped.
+///
+/// If all attempts fail, the returned error is of the kind of the last
+/// failure, and describes the failures for all addresses.
+///
+/// [RFC 8305]: https://tools.ietf.org/html/rfc8305
+/// [attempt delay]: #method.attempt_delay
+#[must_use = "futures do nothing unless polled"]
+#[derive(Debug)]
+pub struct ConnectTo {
+    /// Addresses which have not been tried yet, in the order they are tried.
+    addrs: VecDeque<SocketAddr>,
+
+    /// Connection attempts in progress.
+    attempts: Vec<(SocketAddr, ConnectFuture)>,
+
+    /// The failures of the attempts which have completed.
+    errors: Vec<(SocketAddr, io::Error)>,
+
+    /// Resolves the addresses, if they have not been resolved yet.
+    resolving: Option<Resolve>,
+
+    handle: Handle,
+
+    attempt_delay: Duration,
+
+    /// Fires when the next connection attempt must be started.
+    next_attempt: Option<Delay>,
+
+    timeout: Option<Duration>,
+
+    /// Fires when the overall timeout has elapsed, set on the first poll.
+    deadline: Option<Delay>,
+
+    started: bool,
+}
+
+/// The failures of all connection attempts made by `ConnectTo`.
+#[derive(Debug)]
+struct ConnectError {
+    errors: Vec<(SocketAddr, io::Error)>,
+}
+
+impl ConnectTo {
+    pub(crate) fn new(addrs: impl ToSocketAddrs, handle: &Handle) -> ConnectTo {
+        ConnectTo {
+            addrs: VecDeque::new(),
+            attempts: Vec::new(),
+            errors: Vec::new(),
+            resolving: Some(dns::resolve(addrs)),
+            handle: handle.clone(),
+            attempt_delay: Duration::from_millis(ATTEMPT_DELAY_MS),
+            next_attempt: None,
+            timeout: None,
+            deadline: None,
+            started: false,
+        }
+    }
+
+    /// Sets how long to wait for a connection attempt to complete before
+    /// starting the next one, in parallel.
+    ///
+    /// This defaults to 250 milliseconds, as recommended by RFC 8305.
+    pub fn attempt_delay(mut self, delay: Duration) -> ConnectTo {
+        self.attempt_delay = delay;
+        self
+    }
+
+    /// Sets a timeout for connecting to any of the addresses.
+    ///
+    /// The timeout starts when the future is first polled, and includes the
+    /// time spent resolving the addresses. Once it has elapsed, all attempts
+    /// are dropped and the future fails with an error of
+    /// kind `TimedOut`.
+    pub fn timeout(mut self, timeout: Duration) -> ConnectTo {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Starts a connection attempt to `addr`.
+    fn start(&mut self, addr: SocketAddr) {
+        let attempt = TcpStream::connect_with_handle(&addr, &self.handle);
+        self.attempts.push((addr, attempt));
+
+        let deadline = timer::now_with_handle(&self.handle) + self.attempt_delay;
+        self.next_attempt = Some(Delay::new_with_handle(deadline, &self.handle));
+    }
+
+    /// Polls the attempts in progress, returning the stream of the first one
+    /// which succeeds, and recording the failures of the others.
+    fn poll_attempts(&mut self, lw: &LocalWaker) -> Option<TcpStream> {
+        let mut i = 0;
+
+        while i < self.attempts.len() {
+            match Pin::new(&mut self.attempts[i].1).poll(lw) {
+                Poll::Ready(Ok(stream)) => return Some(stream),
+                Poll::Ready(Err(e)) => {
+                    let (addr, _) = self.attempts.remove(i);
+                    self.errors.push((addr, e));
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        None
+    }
+
+    /// Fails with a `TimedOut` error once the deadline has elapsed.
+    fn poll_deadline(&mut self, lw: &LocalWaker) -> Poll<io::Result<TcpStream>> {
+        if let Some(ref mut deadline) = self.deadline {
+            if Pin::new(deadline).poll(lw).is_ready() {
+                self.resolving = None;
+                self.attempts.clear();
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "connection attempts timed out",
+                )));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Returns the error reported once all attempts have failed.
+    fn take_error(&mut self) -> io::Error {
+        let kind = match self.errors.last() {
+            Some((_, e)) => e.kind(),
+            None => {
+                return io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "could not resolve to any addresses",
+                )
+            }
+        };
+
+        let errors = self.errors.drain(..).collect();
+        io::Error::new(kind, ConnectError { errors })
+    }
+}
+
+impl Future for ConnectTo {
+    type Output = io::Result<TcpStream>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<io::Result<TcpStream>> {
+        let this = &mut *self;
+
+        if !this.started {
+            this.started = true;
+
+            if let Some(timeout) = this.timeout {
+                let deadline = timer::now_with_handle(&this.handle) + timeout;
+                this.deadline = Some(Delay::new_with_handle(deadline, &this.handle));
+            }
+        }
+
+        if let Some(ref mut resolving) = this.resolving {
+            match Pin::new(resolving).poll(lw) {
+                Poll::Ready(Ok(addrs)) => this.addrs = interleave(addrs.into_iter()),
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return this.poll_deadline(lw),
+            }
+            this.resolving = None;
+        }
+
+        loop {
+            let failures = this.errors.len();
+            if let Some(stream) = this.poll_attempts(lw) {
+                return Poll::Ready(Ok(stream));
+            }
+            let failed = this.errors.len() > failures;
+
+            // Start the next attempt right away if an attempt failed, or if the
+            // attempt delay has elapsed.
+            let start_next = failed
+                || this.attempts.is_empty()
+                || match this.next_attempt {
+                    Some(ref mut delay) => Pin::new(delay).poll(lw).is_ready(),
+                    None => false,
+                };
+
+            if !start_next {
+                break;
+            }
+
+            match this.addrs.pop_front() {
+                Some(addr) => this.start(addr),
+                None if this.attempts.is_empty() => return Poll::Ready(Err(this.take_error())),
+                None => {
+                    this.next_attempt = None;
+                    break;
+                }
+            }
+        }
+
+        this.poll_deadline(lw)
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not connect to any address")?;
+        for (i, (addr, e)) in self.errors.iter().enumerate() {
+            let sep = if i == 0 { ": " } else { "; " };
+            write!(f, "{}{}: {}", sep, addr, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ConnectError {
+    fn description(&self) -> &str {
+        "could not connect to any address"
+    }
+}
+
+/// Orders addresses as described in section 4 of RFC 8305, alternating between
+/// address families, starting with the family of the first address.
+fn interleave(addrs: impl Iterator<Item = SocketAddr>) -> VecDeque<SocketAddr> {
+    let (mut first, mut second) = (VecDeque::new(), VecDeque::new());
+    let mut first_v6 = None;
+
+    for addr in addrs {
+        let v6 = addr.is_ipv6();
+        if *first_v6.get_or_insert(v6) == v6 {
+            first.push_back(addr);
+        } else {
+            second.push_back(addr);
+        }
+    }
+
+    let mut ret = VecDeque::with_capacity(first.len() + second.len());
+    loop {
+        match (first.pop_front(), second.pop_front()) {
+            (None, None) => return ret,
+            (a, b) => ret.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::interleave;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn interleaves_families() {
+        let addrs: Vec<SocketAddr> = vec![
+            "[::1]:1".parse().unwrap(),
+            "[::2]:1".parse().unwrap(),
+            "[::3]:1".parse().unwrap(),
+            "127.0.0.1:1".parse().unwrap(),
+            "127.0.0.2:1".parse().unwrap(),
+        ];
+
+        let ordered: Vec<_> = interleave(addrs.iter().cloned()).into_iter().collect();
+        assert_eq!(
+            ordered,
+            vec![addrs[0], addrs[3], addrs[1], addrs[4], addrs[2]]
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use super::{Incoming, TcpListener, TcpSocket, TcpStream};
+
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use futures::stream::Stream;
+use futures::task::LocalWaker;
+use futures::Poll;
+
+use crate::reactor::Handle;
+use crate::server::{
+    AcceptPolicy, ConnectionLimit, LimitedIncoming, ShutdownController, UntilShutdown,
+};
+
+/// The listen backlog of each listener in a group, matching `TcpListener::bind`.
+const BACKLOG: u32 = 1024;
+
+/// A group of TCP listeners sharing the same address through `SO_REUSEPORT`.
+///
+/// The OS spreads the incoming connections across the listeners of the group,
+/// so several accept loops, possibly each driven by a different reactor, can
+/// accept connections on the same port.
+///
+/// The listeners can either be accepted from as a single stream, using
+/// [`incoming`], or be handed out to different workers, using
+/// [`into_listeners`].
+///
+/// [`incoming`]: #method.incoming
+/// [`into_listeners`]: #method.into_listeners
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::prelude::*;
+/// use futures::executor::ThreadPool;
+/// use futures::task::SpawnExt;
+/// use romio::tcp::TcpListenerGroup;
+///
+/// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let group = TcpListenerGroup::bind(&"127.0.0.1:80".parse()?, 4)?;
+/// let mut pool = ThreadPool::new()?;
+///
+/// // run an accept loop per listener
+/// for listener in group.into_listeners() {
+///     pool.spawn(async move {
+///         let mut incoming = listener.incoming();
+///         while let Some(Ok(stream)) = await!(incoming.next()) {
+///             println!("accepted connection from {:?}", stream.peer_addr());
+///         }
+///     }).unwrap();
+/// }
+/// # Ok(())}
+/// ```
+#[derive(Debug)]
+pub struct TcpListenerGroup {
+    listeners: Vec<TcpListener>,
+}
+
+/// Stream returned by the `TcpListenerGroup::incoming` function, which yields
+/// the connections accepted by all listeners of a group.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct GroupIncoming {
+    incoming: Vec<Incoming>,
+
+    /// The listener polled first, rotated to accept from all of them fairly.
+    next: usize,
+}
+
+impl TcpListenerGroup {
+    /// Creates `count` listeners bound to the specified address.
+    ///
+    /// If the port of `addr` is 0, the OS assigns a port to the first listener,
+    /// and the others are bound to the same port. The listeners bind lazily to
+    /// the default reactor of the task which first polls them.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `count` is 0.
+    pub fn bind(addr: &SocketAddr, count: usize) -> io::Result<TcpListenerGroup> {
+        assert!(count > 0, "a listener group must have at least one listener");
+        TcpListenerGroup::bind_each(addr, count, |socket, _| socket.listen(BACKLOG))
+    }
+
+    /// Creates a listener bound to the specified address for each reactor
+    /// referenced by `handles`, registered with that reactor.
+    ///
+    /// The streams accepted by each listener are registered with the same
+    /// reactor.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `handles` is empty.
+    pub fn bind_with_handles(
+        addr: &SocketAddr,
+        handles: &[Handle],
+    ) -> io::Result<TcpListenerGroup> {
+        assert!(
+            !handles.is_empty(),
+            "a listener group must have at least one listener"
+        );
+        TcpListenerGroup::bind_each(addr, handles.len(), |socket, i| {
+            socket.listen_with_handle(BACKLOG, &handles[i])
+        })
+    }
+
+    fn bind_each<F>(
+        addr: &SocketAddr,
+        count: usize,
+        mut listen: F,
+    ) -> io::Result<TcpListenerGroup>
+    where
+        F: FnMut(TcpSocket, usize) -> io::Result<TcpListener>,
+    {
+        let mut addr = *addr;
+        let mut listeners = Vec::with_capacity(count);
+
+        for i in 0..count {
+            let socket = TcpSocket::new_for_addr(&addr)?;
+            socket.set_reuseaddr(true)?;
+            socket.set_reuseport(true)?;
+            socket.bind(&addr)?;
+
+            let listener = listen(socket, i)?;
+
+            // Bind the other listeners to the port assigned to the first one.
+            addr = listener.local_addr()?;
+            listeners.push(listener);
+        }
+
+        Ok(TcpListenerGroup { listeners })
+    }
+
+    /// Returns the local address that the listeners are bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listeners[0].local_addr()
+    }
+
+    /// Returns the listeners of the group, to accept connections from each of
+    /// them separately.
+    pub fn into_listeners(self) -> Vec<TcpListener> {
+        self.listeners
+    }
+
+    /// Consumes the group, returning a single stream of the connections
+    /// accepted by all of its listeners.
+    pub fn incoming(self) -> GroupIncoming {
+        GroupIncoming {
+            incoming: self.listeners.into_iter().map(TcpListener::incoming).collect(),
+            next: 0,
+        }
+    }
+}
+
+impl GroupIncoming {
+    /// Sets how errors returned by `accept` are handled, for each listener of
+    /// the group.
+    ///
+    /// See [`Incoming::accept_policy`] for details.
+    ///
+    /// [`Incoming::accept_policy`]: struct.Incoming.html#method.accept_policy
+    pub fn accept_policy(self, policy: AcceptPolicy) -> GroupIncoming {
+        GroupIncoming {
+            incoming: self
+                .incoming
+                .into_iter()
+                .map(|incoming| incoming.accept_policy(policy.clone()))
+                .collect(),
+            next: self.next,
+        }
+    }
+
+    /// Limits the number of live connections accepted by this stream.
+    ///
+    /// See [`ConnectionLimit`] for details.
+    ///
+    /// [`ConnectionLimit`]: ../server/struct.ConnectionLimit.html
+    pub fn limit(self, limit: &ConnectionLimit) -> LimitedIncoming<GroupIncoming> {
+        LimitedIncoming::new(self, limit)
+    }
+
+    /// Ends this stream when the shutdown of `controller` begins.
+    ///
+    /// See [`ShutdownController`] for details.
+    ///
+    /// [`ShutdownController`]: ../server/struct.ShutdownController.html
+    pub fn until_shutdown(self, controller: &ShutdownController) -> UntilShutdown<GroupIncoming> {
+        UntilShutdown::new(self, controller)
+    }
+}
+
+impl Stream for GroupIncoming {
+    type Item = io::Result<TcpStream>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let len = this.incoming.len();
+
+        for i in 0..len {
+            let idx = (this.next + i) % len;
+
+            if let Poll::Ready(item) = Pin::new(&mut this.incoming[idx]).poll_next(lw) {
+                this.next = (idx + 1) % len;
+                return Poll::Ready(item);
+            }
+        }
+
+        Poll::Pending
+    }
+}
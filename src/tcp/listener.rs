@@ -10,7 +10,10 @@ use futures::task::LocalWaker;
 use futures::{ready, Poll};
 use mio;
 
-use crate::reactor::PollEvented;
+use crate::reactor::{Handle, PollEvented};
+use crate::server::{
+    Accept, AcceptPolicy, ConnectionLimit, LimitedIncoming, ShutdownController, UntilShutdown,
+};
 
 /// A TCP socket server, listening for connections.
 ///
@@ -50,6 +53,10 @@ use crate::reactor::PollEvented;
 /// ```
 pub struct TcpListener {
     io: PollEvented<mio::net::TcpListener>,
+
+    /// The reactor the accepted streams are registered with, if the listener
+    /// was created with one.
+    handle: Option<Handle>,
 }
 
 impl TcpListener {
@@ -80,9 +87,46 @@ impl TcpListener {
         Ok(TcpListener::new(l))
     }
 
-    fn new(listener: mio::net::TcpListener) -> TcpListener {
+    /// Creates a new `TcpListener` bound to the specified address and
+    /// associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the listener, and the streams
+    /// it accepts, are registered with the given reactor instead of lazily
+    /// binding to the default one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use romio::reactor::Reactor;
+    /// use romio::tcp::TcpListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let reactor = Reactor::new()?;
+    /// let socket_addr = "127.0.0.1:0".parse()?;
+    /// let listener = TcpListener::bind_with_handle(&socket_addr, &reactor.handle())?;
+    /// # Ok(())}
+    /// ```
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(addr: &SocketAddr, handle: &Handle) -> io::Result<TcpListener> {
+        let l = mio::net::TcpListener::bind(addr)?;
+        TcpListener::new_with_handle(l, handle)
+    }
+
+    pub(crate) fn new(listener: mio::net::TcpListener) -> TcpListener {
         let io = PollEvented::new(listener);
-        TcpListener { io }
+        TcpListener { io, handle: None }
+    }
+
+    pub(crate) fn new_with_handle(
+        listener: mio::net::TcpListener,
+        handle: &Handle,
+    ) -> io::Result<TcpListener> {
+        let io = PollEvented::new_with_handle(listener, handle)?;
+        Ok(TcpListener {
+            io,
+            handle: Some(handle.clone()),
+        })
     }
 
     /// Returns the local address that this listener is bound to.
@@ -198,7 +242,10 @@ impl TcpListener {
         let (io, addr) = ready!(self.poll_accept_std(lw)?);
 
         let io = mio::net::TcpStream::from_stream(io)?;
-        let io = TcpStream::new(io);
+        let io = match self.handle {
+            Some(ref handle) => TcpStream::new_with_handle(io, handle)?,
+            None => TcpStream::new(io),
+        };
 
         Poll::Ready(Ok((io, addr)))
     }
@@ -244,11 +291,44 @@ mod sys {
 #[derive(Debug)]
 pub struct Incoming {
     inner: TcpListener,
+    accept: Accept,
 }
 
 impl Incoming {
     pub(crate) fn new(listener: TcpListener) -> Incoming {
-        Incoming { inner: listener }
+        Incoming {
+            inner: listener,
+            accept: Accept::default(),
+        }
+    }
+
+    /// Sets how errors returned by `accept` are handled.
+    ///
+    /// By default, every error is yielded by the stream. See [`AcceptPolicy`]
+    /// for details.
+    ///
+    /// [`AcceptPolicy`]: ../server/struct.AcceptPolicy.html
+    pub fn accept_policy(mut self, policy: AcceptPolicy) -> Incoming {
+        self.accept.set_policy(policy);
+        self
+    }
+
+    /// Limits the number of live connections accepted by this stream.
+    ///
+    /// See [`ConnectionLimit`] for details.
+    ///
+    /// [`ConnectionLimit`]: ../server/struct.ConnectionLimit.html
+    pub fn limit(self, limit: &ConnectionLimit) -> LimitedIncoming<Incoming> {
+        LimitedIncoming::new(self, limit)
+    }
+
+    /// Ends this stream when the shutdown of `controller` begins.
+    ///
+    /// See [`ShutdownController`] for details.
+    ///
+    /// [`ShutdownController`]: ../server/struct.ShutdownController.html
+    pub fn until_shutdown(self, controller: &ShutdownController) -> UntilShutdown<Incoming> {
+        UntilShutdown::new(self, controller)
     }
 }
 
@@ -256,7 +336,10 @@ impl Stream for Incoming {
     type Item = io::Result<TcpStream>;
 
     fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
-        let (socket, _) = ready!(self.inner.poll_accept(lw)?);
+        let this = &mut *self;
+        let inner = &mut this.inner;
+
+        let (socket, _) = ready!(this.accept.poll_accept(lw, |lw| inner.poll_accept(lw))?);
         Poll::Ready(Some(Ok(socket)))
     }
 }
@@ -4,17 +4,27 @@
 //! `std::net`, but suitable for async programming via futures and
 //! `async`/`await`.
 //!
-//! - To connect to an address via TCP, use [`TcpStream::connect`].
+//! - To connect to an address via TCP, use [`TcpStream::connect`]. To connect
+//!   to the first of several addresses that accepts the connection, use
+//!   [`TcpStream::connect_to`].
 //! - To listen for TCP connection, use [`TcpListener::bind`] and then
 //!   [`TcpListener::incoming`].
+//! - To set socket options before binding or connecting, such as
+//!   `SO_REUSEPORT` or the listen backlog, use a [`TcpSocket`].
+//! - To run several accept loops on the same port, use a [`TcpListenerGroup`].
 //! - Once you have a [`TcpStream`], you can use methods from `AsyncRead`,
 //!   `AsyncWrite`, and their extension traits (`AsyncReadExt`, `AsyncWriteExt`)
-//!   to send and receive data.
+//!   to send and receive data. To read and write from different tasks, split
+//!   it with [`TcpStream::into_split`].
 //!
 //! [`TcpStream`]: struct.TcpStream.html
 //! [`TcpStream::connect`]: struct.TcpStream.html#method.connect
+//! [`TcpStream::connect_to`]: struct.TcpStream.html#method.connect_to
+//! [`TcpStream::into_split`]: struct.TcpStream.html#method.into_split
 //! [`TcpListener::bind`]: struct.TcpListener.html#method.bind
 //! [`TcpListener::incoming`]: struct.TcpListener.html#method.incoming
+//! [`TcpSocket`]: struct.TcpSocket.html
+//! [`TcpListenerGroup`]: struct.TcpListenerGroup.html
 //!
 //! # Example
 //!
@@ -40,8 +50,18 @@
 //! }
 //! ```
 
+mod connect_to;
+#[cfg(unix)]
+mod group;
 mod listener;
+mod socket;
+mod split;
 mod stream;
 
+pub use self::connect_to::ConnectTo;
+#[cfg(unix)]
+pub use self::group::{GroupIncoming, TcpListenerGroup};
 pub use self::listener::{Incoming, TcpListener};
+pub use self::socket::TcpSocket;
+pub use self::split::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
 pub use self::stream::{ConnectFuture, TcpStream};
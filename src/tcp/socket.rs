@@ -0,0 +1,216 @@
+use super::{ConnectFuture, TcpListener, TcpStream};
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use mio;
+use net2::TcpBuilder;
+
+use crate::reactor::Handle;
+
+/// A TCP socket that has not yet been converted to a [`TcpListener`] or a
+/// [`TcpStream`].
+///
+/// `TcpSocket` gives access to the options which must be set before the socket
+/// is bound or connected, such as `SO_REUSEADDR`, `SO_REUSEPORT` and
+/// `IPV6_V6ONLY`, and allows choosing the listen backlog or binding a client
+/// socket to a local address before connecting it.
+///
+/// The socket is turned into a [`TcpListener`] by [`listen`], or into a
+/// [`TcpStream`] by [`connect`].
+///
+/// [`TcpListener`]: struct.TcpListener.html
+/// [`TcpStream`]: struct.TcpStream.html
+/// [`listen`]: #method.listen
+/// [`connect`]: #method.connect
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use romio::tcp::TcpSocket;
+///
+/// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let socket = TcpSocket::new_v4()?;
+/// socket.set_reuseaddr(true)?;
+/// socket.bind(&"127.0.0.1:8080".parse()?)?;
+///
+/// let listener = socket.listen(1024)?;
+/// # Ok(())}
+/// ```
+pub struct TcpSocket {
+    inner: TcpBuilder,
+}
+
+impl TcpSocket {
+    /// Creates a new IPv4 TCP socket.
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        let inner = TcpBuilder::new_v4()?;
+        Ok(TcpSocket { inner })
+    }
+
+    /// Creates a new IPv6 TCP socket.
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        let inner = TcpBuilder::new_v6()?;
+        Ok(TcpSocket { inner })
+    }
+
+    /// Creates a new TCP socket of the same family as `addr`.
+    pub fn new_for_addr(addr: &SocketAddr) -> io::Result<TcpSocket> {
+        match *addr {
+            SocketAddr::V4(..) => TcpSocket::new_v4(),
+            SocketAddr::V6(..) => TcpSocket::new_v6(),
+        }
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// This allows binding a listener to an address whose previous connections
+    /// are still in the `TIME_WAIT` state.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.inner.reuse_address(reuseaddr).map(drop)
+    }
+
+    /// Gets the value of the `SO_REUSEADDR` option on this socket.
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.inner.get_reuse_address()
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// This allows several sockets, possibly owned by different processes, to
+    /// listen on the same address, as long as they all set this option.
+    #[cfg(unix)]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        use net2::unix::UnixTcpBuilderExt;
+
+        self.inner.reuse_port(reuseport).map(drop)
+    }
+
+    /// Gets the value of the `SO_REUSEPORT` option on this socket.
+    #[cfg(unix)]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        use net2::unix::UnixTcpBuilderExt;
+
+        self.inner.get_reuse_port()
+    }
+
+    /// Sets the value of the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// If this is set to `true`, an IPv6 socket only communicates over IPv6,
+    /// and doesn't accept IPv4 connections on an unspecified address. This is
+    /// only valid for IPv6 sockets.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.inner.only_v6(only_v6).map(drop)
+    }
+
+    /// Binds the socket to the given address.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this socket. The port allocated can be queried via the
+    /// [`local_addr`] method.
+    ///
+    /// [`local_addr`]: #method.local_addr
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<()> {
+        self.inner.bind(addr).map(drop)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Converts the socket into a `TcpListener`, accepting connections on the
+    /// address it is bound to.
+    ///
+    /// `backlog` is the maximum number of pending connections which have not
+    /// been accepted yet; the OS may cap it to a lower value.
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let listener = self.listen_mio(backlog)?;
+        Ok(TcpListener::new(listener))
+    }
+
+    /// Converts the socket into a `TcpListener` associated with the reactor
+    /// referenced by `handle`.
+    ///
+    /// This is the same as [`listen`], except that the listener, and the
+    /// streams it accepts, are registered with the given reactor instead of
+    /// lazily binding to the default one.
+    ///
+    /// [`listen`]: #method.listen
+    pub fn listen_with_handle(self, backlog: u32, handle: &Handle) -> io::Result<TcpListener> {
+        let listener = self.listen_mio(backlog)?;
+        TcpListener::new_with_handle(listener, handle)
+    }
+
+    /// Connects the socket to the given address, converting it into a
+    /// `TcpStream`.
+    ///
+    /// If the socket has been bound, the connection is made from the address
+    /// it is bound to.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::tcp::{TcpSocket, TcpStream};
+    ///
+    /// # async fn connect() -> Result<TcpStream, Box<dyn std::error::Error + 'static>> {
+    /// let socket = TcpSocket::new_v4()?;
+    /// socket.bind(&"10.0.0.2:0".parse()?)?;
+    ///
+    /// let stream = await!(socket.connect(&"10.0.0.1:8080".parse()?))?;
+    /// # Ok(stream)}
+    /// ```
+    pub fn connect(self, addr: &SocketAddr) -> ConnectFuture {
+        ConnectFuture::new(self.connect_mio(addr).map(TcpStream::new))
+    }
+
+    /// Connects the socket to the given address, converting it into a
+    /// `TcpStream` associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`connect`], except that the stream is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`connect`]: #method.connect
+    pub fn connect_with_handle(self, addr: &SocketAddr, handle: &Handle) -> ConnectFuture {
+        let stream = self
+            .connect_mio(addr)
+            .and_then(|tcp| TcpStream::new_with_handle(tcp, handle));
+        ConnectFuture::new(stream)
+    }
+
+    fn listen_mio(&self, backlog: u32) -> io::Result<mio::net::TcpListener> {
+        let backlog = if backlog > i32::max_value() as u32 {
+            i32::max_value()
+        } else {
+            backlog as i32
+        };
+
+        let listener = self.inner.listen(backlog)?;
+        mio::net::TcpListener::from_std(listener)
+    }
+
+    fn connect_mio(&self, addr: &SocketAddr) -> io::Result<mio::net::TcpStream> {
+        let stream = self.inner.to_tcp_stream()?;
+        mio::net::TcpStream::connect_stream(stream, addr)
+    }
+}
+
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use super::TcpSocket;
+    use std::os::unix::prelude::*;
+
+    impl AsRawFd for TcpSocket {
+        fn as_raw_fd(&self) -> RawFd {
+            self.inner.as_raw_fd()
+        }
+    }
+}
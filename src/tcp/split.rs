@@ -0,0 +1,200 @@
+use super::TcpStream;
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::{Shutdown, SocketAddr};
+use std::sync::Arc;
+
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::task::LocalWaker;
+use futures::Poll;
+use iovec::IoVec;
+
+use crate::reactor::Ready;
+
+/// The reading half of a `TcpStream`, created by [`TcpStream::into_split`].
+///
+/// [`TcpStream::into_split`]: struct.TcpStream.html#method.into_split
+#[derive(Debug)]
+pub struct OwnedReadHalf {
+    inner: Arc<TcpStream>,
+}
+
+/// The writing half of a `TcpStream`, created by [`TcpStream::into_split`].
+///
+/// Dropping the write half shuts the write side of the connection down, so
+/// that the peer reads the end of the stream, while the read half can still
+/// receive data.
+///
+/// [`TcpStream::into_split`]: struct.TcpStream.html#method.into_split
+#[derive(Debug)]
+pub struct OwnedWriteHalf {
+    inner: Arc<TcpStream>,
+    shutdown_on_drop: bool,
+}
+
+/// The error returned by `reunite` when the two halves don't come from the
+/// same stream, which gives the halves back.
+#[derive(Debug)]
+pub struct ReuniteError(pub OwnedReadHalf, pub OwnedWriteHalf);
+
+pub(crate) fn split(stream: TcpStream) -> (OwnedReadHalf, OwnedWriteHalf) {
+    let inner = Arc::new(stream);
+
+    let read = OwnedReadHalf {
+        inner: inner.clone(),
+    };
+    let write = OwnedWriteHalf {
+        inner,
+        shutdown_on_drop: true,
+    };
+
+    (read, write)
+}
+
+fn reunite(read: OwnedReadHalf, mut write: OwnedWriteHalf) -> Result<TcpStream, ReuniteError> {
+    if !Arc::ptr_eq(&read.inner, &write.inner) {
+        return Err(ReuniteError(read, write));
+    }
+
+    write.shutdown_on_drop = false;
+    drop(write);
+
+    let stream = Arc::try_unwrap(read.inner)
+        .expect("TcpStream: the halves of a stream are its only owners");
+    Ok(stream)
+}
+
+impl OwnedReadHalf {
+    /// Puts the two halves of a `TcpStream` back together.
+    ///
+    /// This fails if the halves don't come from the same call to
+    /// `into_split`.
+    pub fn reunite(self, other: OwnedWriteHalf) -> Result<TcpStream, ReuniteError> {
+        reunite(self, other)
+    }
+
+    /// Returns the local address that this stream is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the remote address that this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Poll the stream's readiness for reading.
+    ///
+    /// See [`TcpStream::poll_read_ready`] for details.
+    ///
+    /// [`TcpStream::poll_read_ready`]: struct.TcpStream.html#method.poll_read_ready
+    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.inner.poll_read_ready(lw)
+    }
+
+    /// Poll whether the peer has shut the write side of its connection down.
+    ///
+    /// See [`TcpStream::poll_read_closed`] for details.
+    ///
+    /// [`TcpStream::poll_read_closed`]: struct.TcpStream.html#method.poll_read_closed
+    pub fn poll_read_closed(&self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.inner.poll_read_closed(lw)
+    }
+}
+
+impl OwnedWriteHalf {
+    /// Puts the two halves of a `TcpStream` back together.
+    ///
+    /// This fails if the halves don't come from the same call to
+    /// `into_split`.
+    pub fn reunite(self, other: OwnedReadHalf) -> Result<TcpStream, ReuniteError> {
+        reunite(other, self)
+    }
+
+    /// Drops the write half without shutting the write side of the
+    /// connection down.
+    ///
+    /// The connection is then closed once the read half is dropped.
+    pub fn forget(mut self) {
+        self.shutdown_on_drop = false;
+    }
+
+    /// Returns the stream this half writes to.
+    pub(crate) fn stream(&self) -> &TcpStream {
+        &self.inner
+    }
+
+    /// Returns the local address that this stream is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the remote address that this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Poll the stream's readiness for writing.
+    ///
+    /// See [`TcpStream::poll_write_ready`] for details.
+    ///
+    /// [`TcpStream::poll_write_ready`]: struct.TcpStream.html#method.poll_write_ready
+    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.inner.poll_write_ready(lw)
+    }
+}
+
+impl Drop for OwnedWriteHalf {
+    fn drop(&mut self) {
+        if self.shutdown_on_drop {
+            let _ = self.inner.shutdown(Shutdown::Write);
+        }
+    }
+}
+
+impl AsyncRead for OwnedReadHalf {
+    fn poll_read(&mut self, lw: &LocalWaker, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        (&*self.inner).poll_read(lw, buf)
+    }
+
+    fn poll_vectored_read(
+        &mut self,
+        lw: &LocalWaker,
+        vec: &mut [&mut IoVec],
+    ) -> Poll<io::Result<usize>> {
+        (&*self.inner).poll_vectored_read(lw, vec)
+    }
+}
+
+impl AsyncWrite for OwnedWriteHalf {
+    fn poll_write(&mut self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        (&*self.inner).poll_write(lw, buf)
+    }
+
+    fn poll_vectored_write(&mut self, lw: &LocalWaker, vec: &[&IoVec]) -> Poll<io::Result<usize>> {
+        (&*self.inner).poll_vectored_write(lw, vec)
+    }
+
+    fn poll_flush(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        (&*self.inner).poll_flush(lw)
+    }
+
+    /// Shuts the write side of the connection down.
+    fn poll_close(&mut self, _: &LocalWaker) -> Poll<io::Result<()>> {
+        Poll::Ready(self.inner.shutdown(Shutdown::Write))
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tried to reunite halves that are not from the same stream")
+    }
+}
+
+impl Error for ReuniteError {
+    fn description(&self) -> &str {
+        "tried to reunite halves that are not from the same stream"
+    }
+}
@@ -11,7 +11,11 @@ use futures::{ready, Future, Poll};
 use iovec::IoVec;
 use mio;
 
-use crate::reactor::PollEvented;
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf};
+use super::ConnectTo;
+use crate::dns::sealed::Target;
+use crate::dns::ToSocketAddrs;
+use crate::reactor::{Handle, PollEvented, Ready};
 
 /// A TCP stream between a local and a remote socket.
 ///
@@ -43,6 +47,7 @@ pub struct ConnectFuture {
 #[derive(Debug)]
 enum ConnectFutureState {
     Waiting(TcpStream),
+    Connecting(ConnectTo),
     Error(io::Error),
     Empty,
 }
@@ -55,6 +60,14 @@ impl TcpStream {
     /// stream has successfully connected, or it will return an error if one
     /// occurs.
     ///
+    /// `addr` can be a host name and a port, such as `"db.internal:5432"`, in
+    /// which case the name is resolved without blocking, as by
+    /// [`dns::resolve`]. If it resolves to several addresses, they are tried as
+    /// by [`connect_to`].
+    ///
+    /// [`dns::resolve`]: ../dns/fn.resolve.html
+    /// [`connect_to`]: #method.connect_to
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -63,19 +76,78 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn connect_localhost() -> io::Result<TcpStream> {
-    /// let addr = "127.0.0.1".parse().unwrap();
-    /// await!(TcpStream::connect(&addr))
+    /// await!(TcpStream::connect("localhost:8080"))
     /// # }
     /// ```
-    pub fn connect(addr: &SocketAddr) -> ConnectFuture {
-        use self::ConnectFutureState::*;
+    pub fn connect(addr: impl ToSocketAddrs) -> ConnectFuture {
+        match addr.to_target() {
+            Ok(Target::Addrs(ref addrs)) if addrs.len() == 1 => {
+                ConnectFuture::new(mio::net::TcpStream::connect(&addrs[0]).map(TcpStream::new))
+            }
+            _ => ConnectFuture::connect_to(ConnectTo::new(addr, &Handle::default())),
+        }
+    }
 
-        let inner = match mio::net::TcpStream::connect(addr) {
-            Ok(tcp) => Waiting(TcpStream::new(tcp)),
-            Err(e) => Error(e),
-        };
+    /// Create a new TCP stream connected to the specified address, associated
+    /// with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`connect`], except that the stream is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`connect`]: #method.connect
+    pub fn connect_with_handle(addr: impl ToSocketAddrs, handle: &Handle) -> ConnectFuture {
+        match addr.to_target() {
+            Ok(Target::Addrs(ref addrs)) if addrs.len() == 1 => {
+                let stream = mio::net::TcpStream::connect(&addrs[0])
+                    .and_then(|tcp| TcpStream::new_with_handle(tcp, handle));
+                ConnectFuture::new(stream)
+            }
+            _ => ConnectFuture::connect_to(ConnectTo::new(addr, handle)),
+        }
+    }
 
-        ConnectFuture { inner }
+    /// Create a new TCP stream connected to the first of the given addresses
+    /// which accepts the connection.
+    ///
+    /// The addresses are tried concurrently, as described by the "Happy
+    /// Eyeballs" algorithm: see [`ConnectTo`] for details. The returned future
+    /// can also be configured with an overall timeout.
+    ///
+    /// If `addrs` is a host name, it is resolved without blocking when the
+    /// returned future is first polled, and the timeout includes the time
+    /// spent resolving it.
+    ///
+    /// [`ConnectTo`]: struct.ConnectTo.html
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// # use std::io;
+    /// use romio::tcp::TcpStream;
+    /// use std::net::SocketAddr;
+    /// use std::time::Duration;
+    ///
+    /// # async fn connect() -> io::Result<TcpStream> {
+    /// let addrs: [SocketAddr; 2] = ["[::1]:80".parse().unwrap(), "127.0.0.1:80".parse().unwrap()];
+    /// await!(TcpStream::connect_to(&addrs[..]).timeout(Duration::from_secs(5)))
+    /// # }
+    /// ```
+    pub fn connect_to(addrs: impl ToSocketAddrs) -> ConnectTo {
+        ConnectTo::new(addrs, &Handle::default())
+    }
+
+    /// Create a new TCP stream connected to the first of the given addresses
+    /// which accepts the connection, associated with the reactor referenced by
+    /// `handle`.
+    ///
+    /// This is the same as [`connect_to`], except that the stream and its
+    /// timers are registered with the given reactor instead of lazily binding
+    /// to the default one.
+    ///
+    /// [`connect_to`]: #method.connect_to
+    pub fn connect_to_with_handle(addrs: impl ToSocketAddrs, handle: &Handle) -> ConnectTo {
+        ConnectTo::new(addrs, handle)
     }
 
     pub(crate) fn new(connected: mio::net::TcpStream) -> TcpStream {
@@ -83,6 +155,14 @@ impl TcpStream {
         TcpStream { io }
     }
 
+    pub(crate) fn new_with_handle(
+        connected: mio::net::TcpStream,
+        handle: &Handle,
+    ) -> io::Result<TcpStream> {
+        let io = PollEvented::new_with_handle(connected, handle)?;
+        Ok(TcpStream { io })
+    }
+
     /// Poll the TCP stream's readiness for reading.
     ///
     /// If the stream is not ready for a read then the method will return `Poll::Pending`
@@ -90,10 +170,26 @@ impl TcpStream {
     ///
     /// Once the stream is ready for reading, it will remain so until all available
     /// bytes have been extracted (via `futures::io::AsyncRead` and related traits).
-    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_read_ready(lw)
     }
 
+    /// Poll whether the peer has shut down its write side of the stream.
+    ///
+    /// Unlike [`poll_read_ready`], this does not complete while data can be
+    /// read, so a half-close can be detected without consuming the data still
+    /// buffered in the stream. That data can be read afterwards, until the
+    /// end of the stream is reached.
+    ///
+    /// This shares its wakeup with [`poll_read_ready`], so it must be polled
+    /// from the task reading from the stream. On Windows, where half-closes
+    /// are not reported by the reactor, it never completes.
+    ///
+    /// [`poll_read_ready`]: #method.poll_read_ready
+    pub fn poll_read_closed(&self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.io.poll_read_closed(lw)
+    }
+
     /// Check the TCP stream's write readiness state.
     ///
     /// This always checks for writable readiness and also checks for HUP
@@ -108,7 +204,7 @@ impl TcpStream {
     /// # Panics
     ///
     /// This function panics if called from outside of a task context.
-    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_write_ready(lw)
     }
 
@@ -122,8 +218,7 @@ impl TcpStream {
     /// use std::net::{IpAddr, Ipv4Addr};
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// let expected = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
     /// assert_eq!(stream.local_addr()?.ip(), expected);
@@ -143,8 +238,7 @@ impl TcpStream {
     /// use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// let expected = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080);
     /// assert_eq!(stream.peer_addr()?, SocketAddr::V4(expected));
@@ -168,8 +262,7 @@ impl TcpStream {
     /// use std::net::Shutdown;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.shutdown(Shutdown::Both)?;
     /// # Ok(())}
@@ -191,8 +284,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_nodelay(true)?;
     /// assert_eq!(stream.nodelay()?, true);
@@ -217,8 +309,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_nodelay(true)?;
     /// # Ok(())}
@@ -240,8 +331,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_recv_buffer_size(100);
     /// assert_eq!(stream.recv_buffer_size()?, 100);
@@ -263,8 +353,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_recv_buffer_size(100);
     /// # Ok(())}
@@ -286,8 +375,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_send_buffer_size(100);
     /// assert_eq!(stream.send_buffer_size()?, 100);
@@ -309,8 +397,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_send_buffer_size(100);
     /// # Ok(())}
@@ -334,8 +421,7 @@ impl TcpStream {
     /// use std::time::Duration;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_keepalive(Some(Duration::from_secs(60)))?;
     /// assert_eq!(stream.keepalive()?, Some(Duration::from_secs(60)));
@@ -366,8 +452,7 @@ impl TcpStream {
     /// use std::time::Duration;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_keepalive(Some(Duration::from_secs(60)))?;
     /// # Ok(())}
@@ -389,8 +474,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_ttl(100)?;
     /// assert_eq!(stream.ttl()?, 100);
@@ -412,8 +496,7 @@ impl TcpStream {
     /// use romio::tcp::TcpStream;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_ttl(100)?;
     /// # Ok(())}
@@ -437,8 +520,7 @@ impl TcpStream {
     /// use std::time::Duration;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_linger(Some(Duration::from_millis(100)))?;
     /// assert_eq!(stream.linger()?, Some(Duration::from_millis(100)));
@@ -468,8 +550,7 @@ impl TcpStream {
     /// use std::time::Duration;
     ///
     /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
-    /// let addr = "127.0.0.1:8080".parse()?;
-    /// let stream = await!(TcpStream::connect(&addr))?;
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
     ///
     /// stream.set_linger(Some(Duration::from_millis(100)))?;
     /// # Ok(())}
@@ -477,6 +558,36 @@ impl TcpStream {
     pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
         self.io.get_ref().set_linger(dur)
     }
+
+    /// Splits this stream into a read half and a write half, which can be
+    /// moved to different tasks.
+    ///
+    /// Dropping the write half shuts the write side of the connection down.
+    /// The halves can be put back together with [`OwnedReadHalf::reunite`].
+    ///
+    /// [`OwnedReadHalf::reunite`]: struct.OwnedReadHalf.html#method.reunite
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use futures::prelude::*;
+    /// use romio::tcp::TcpStream;
+    ///
+    /// # async fn run () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let stream = await!(TcpStream::connect("127.0.0.1:8080"))?;
+    /// let (mut reader, mut writer) = stream.into_split();
+    ///
+    /// await!(writer.write_all(b"ping"))?;
+    /// drop(writer);
+    ///
+    /// let mut buf = vec![];
+    /// await!(reader.read_to_end(&mut buf))?;
+    /// # Ok(())}
+    /// ```
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::split(self)
+    }
 }
 
 // ===== impl Read / Write =====
@@ -570,10 +681,33 @@ impl fmt::Debug for TcpStream {
     }
 }
 
+impl ConnectFuture {
+    /// Creates a future which resolves once `stream` has finished connecting.
+    pub(crate) fn new(stream: io::Result<TcpStream>) -> ConnectFuture {
+        let inner = match stream {
+            Ok(stream) => ConnectFutureState::Waiting(stream),
+            Err(e) => ConnectFutureState::Error(e),
+        };
+
+        ConnectFuture { inner }
+    }
+
+    /// Creates a future which resolves once `connect` has connected to one of
+    /// its addresses.
+    fn connect_to(connect: ConnectTo) -> ConnectFuture {
+        ConnectFuture {
+            inner: ConnectFutureState::Connecting(connect),
+        }
+    }
+}
+
 impl Future for ConnectFuture {
     type Output = io::Result<TcpStream>;
 
     fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<io::Result<TcpStream>> {
+        if let ConnectFutureState::Connecting(ref mut connect) = self.inner {
+            return Pin::new(connect).poll(lw);
+        }
         Pin::new(&mut self.inner).poll(lw)
     }
 }
@@ -581,7 +715,7 @@ impl Future for ConnectFuture {
 impl ConnectFutureState {
     fn poll_inner<F>(&mut self, f: F) -> Poll<io::Result<TcpStream>>
     where
-        F: FnOnce(&mut PollEvented<mio::net::TcpStream>) -> Poll<io::Result<mio::Ready>>,
+        F: FnOnce(&mut PollEvented<mio::net::TcpStream>) -> Poll<io::Result<Ready>>,
     {
         {
             let stream = match *self {
@@ -593,6 +727,7 @@ impl ConnectFutureState {
                     };
                     return Poll::Ready(Err(e));
                 }
+                ConnectFutureState::Connecting(_) => unreachable!(),
                 ConnectFutureState::Empty => panic!("can't poll TCP stream twice"),
             };
 
@@ -629,13 +764,43 @@ impl Future for ConnectFutureState {
 #[cfg(unix)]
 mod sys {
     use super::TcpStream;
+    use std::io;
+    use std::net::Shutdown;
     use std::os::unix::prelude::*;
 
+    use futures::task::LocalWaker;
+    use futures::Poll;
+
+    use crate::io::RawStream;
+    use crate::reactor::Ready;
+
     impl AsRawFd for TcpStream {
         fn as_raw_fd(&self) -> RawFd {
             self.io.get_ref().as_raw_fd()
         }
     }
+
+    impl RawStream for TcpStream {
+        fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+            self.io.poll_read_ready(lw)
+        }
+
+        fn clear_read_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+            self.io.clear_read_ready(lw)
+        }
+
+        fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+            self.io.poll_write_ready(lw)
+        }
+
+        fn clear_write_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+            self.io.clear_write_ready(lw)
+        }
+
+        fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+            self.io.get_ref().shutdown(how)
+        }
+    }
 }
 
 fn is_wouldblock<T>(r: &io::Result<T>) -> bool {
@@ -0,0 +1,174 @@
+use crate::reactor::Handle;
+
+use parking_lot::Mutex;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A source of the current time for the timers of a reactor.
+///
+/// By default, reactors use the system clock. A reactor can be created with
+/// another clock using [`Reactor::new_with_clock`], which makes all timers
+/// registered with it measure time using that clock instead. This is mostly
+/// useful for testing code which uses timers:
+///
+/// - [`Clock::new_with_now`] uses a custom source of time.
+/// - [`Clock::paused`] creates a clock which is frozen, and only moves forward
+///   when [`advance`] is called. A reactor using a paused clock never blocks
+///   waiting for a timer, so timeouts can be tested without sleeping.
+///
+/// Cloning a clock returns a handle to the same clock.
+///
+/// [`Reactor::new_with_clock`]: ../reactor/struct.Reactor.html#method.new_with_clock
+/// [`Clock::new_with_now`]: #method.new_with_now
+/// [`Clock::paused`]: #method.paused
+/// [`advance`]: #method.advance
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::task::LocalSpawnExt;
+/// use romio::runtime::current_thread::Runtime;
+/// use romio::timer::{self, Clock};
+/// use std::time::Duration;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let clock = Clock::paused();
+/// let mut runtime = Runtime::new_with_clock(clock.clone())?;
+/// let mut spawner = runtime.spawner();
+///
+/// runtime.block_on(async move {
+///     let sleep = timer::sleep(Duration::from_secs(60 * 60));
+///
+///     // Completes without waiting for an hour.
+///     spawner.spawn_local(async move {
+///         clock.advance(Duration::from_secs(60 * 60));
+///     }).unwrap();
+///     await!(sleep);
+/// });
+/// # Ok(())}
+/// ```
+#[derive(Clone)]
+pub struct Clock {
+    source: Arc<Source>,
+}
+
+/// A source of time for a [`Clock`].
+///
+/// [`Clock`]: struct.Clock.html
+pub trait Now: Send + Sync + 'static {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+enum Source {
+    System,
+    Now(Box<dyn Now>),
+    Paused(Mutex<Paused>),
+}
+
+struct Paused {
+    now: Instant,
+
+    /// The reactors using this clock, which are woken up when it advances.
+    reactors: Vec<Handle>,
+}
+
+impl Clock {
+    /// Returns a clock which uses the system time, i.e. `Instant::now()`.
+    pub fn system() -> Clock {
+        Clock {
+            source: Arc::new(Source::System),
+        }
+    }
+
+    /// Returns a clock which gets the current time from `now`.
+    pub fn new_with_now(now: impl Now) -> Clock {
+        Clock {
+            source: Arc::new(Source::Now(Box::new(now))),
+        }
+    }
+
+    /// Returns a clock which is frozen at the current instant, and only moves
+    /// forward when [`advance`] is called.
+    ///
+    /// [`advance`]: #method.advance
+    pub fn paused() -> Clock {
+        Clock {
+            source: Arc::new(Source::Paused(Mutex::new(Paused {
+                now: Instant::now(),
+                reactors: vec![],
+            }))),
+        }
+    }
+
+    /// Returns the current instant according to this clock.
+    pub fn now(&self) -> Instant {
+        match *self.source {
+            Source::System => Instant::now(),
+            Source::Now(ref now) => now.now(),
+            Source::Paused(ref paused) => paused.lock().now,
+        }
+    }
+
+    /// Returns true if this clock was created with [`paused`].
+    ///
+    /// [`paused`]: #method.paused
+    pub fn is_paused(&self) -> bool {
+        match *self.source {
+            Source::Paused(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Moves a paused clock forward by `duration`, waking up the reactors
+    /// using it so that they fire the timers which have elapsed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the clock is not paused.
+    pub fn advance(&self, duration: Duration) {
+        let reactors = match *self.source {
+            Source::Paused(ref paused) => {
+                let mut paused = paused.lock();
+                paused.now += duration;
+                paused
+                    .reactors
+                    .retain(|handle| handle.with_timer(|_| ()).is_some());
+                paused.reactors.clone()
+            }
+            _ => panic!("only a paused clock can be advanced"),
+        };
+
+        for handle in reactors {
+            handle.wakeup();
+        }
+    }
+
+    /// Records that the reactor referenced by `handle` uses this clock.
+    pub(crate) fn register(&self, handle: Handle) {
+        if let Source::Paused(ref paused) = *self.source {
+            paused.lock().reactors.push(handle);
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::system()
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match *self.source {
+            Source::System => "system",
+            Source::Now(_) => "custom",
+            Source::Paused(_) => "paused",
+        };
+
+        f.debug_struct("Clock").field("kind", &kind).finish()
+    }
+}
@@ -0,0 +1,157 @@
+use crate::reactor::Handle;
+
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::time::Instant;
+
+/// A future that completes at a specified instant in time.
+///
+/// The delay registers with a reactor the first time it is polled, and is
+/// woken up by that reactor once the deadline has been reached.
+///
+/// If the reactor the delay is registered with is dropped, e.g. as it shuts
+/// down, the delay completes immediately instead of never.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::timer::Delay;
+/// use std::time::{Duration, Instant};
+///
+/// # async fn sleep() {
+/// await!(Delay::new(Instant::now() + Duration::from_millis(100)));
+/// # }
+/// ```
+pub struct Delay {
+    deadline: Instant,
+
+    /// The reactor to register with, which is bound on the first poll.
+    handle: Handle,
+
+    /// The key of the timer, once registered.
+    key: Option<usize>,
+}
+
+impl Delay {
+    /// Creates a new `Delay` that completes at `deadline`.
+    pub fn new(deadline: Instant) -> Delay {
+        Delay::new_with_handle(deadline, &Handle::default())
+    }
+
+    /// Creates a new `Delay` that completes at `deadline`, driven by the
+    /// reactor referenced by `handle`.
+    ///
+    /// This is the same as [`new`], except that the delay is registered with
+    /// the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_with_handle(deadline: Instant, handle: &Handle) -> Delay {
+        Delay {
+            deadline,
+            handle: handle.clone(),
+            key: None,
+        }
+    }
+
+    /// Returns the instant at which the delay completes.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns true if the deadline of the delay has been reached.
+    pub fn is_elapsed(&self) -> bool {
+        match self.key {
+            Some(key) => self
+                .handle
+                .with_timer(|timer| timer.is_expired(key))
+                .unwrap_or(true),
+            None => false,
+        }
+    }
+
+    /// Resets the delay to complete at `deadline` instead.
+    ///
+    /// This can be done whether or not the delay has already completed, in
+    /// which case it can be polled again until the new deadline is reached.
+    pub fn reset(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+
+        if let Some(key) = self.key {
+            let wakeup = self
+                .handle
+                .with_timer(|timer| timer.reset(key, deadline))
+                .unwrap_or(false);
+
+            if wakeup {
+                self.handle.wakeup();
+            }
+        }
+    }
+
+    /// Polls the delay, failing if the reactor driving it has gone away.
+    pub(crate) fn poll_timer(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        if self.key.is_none() {
+            self.register()?;
+        }
+
+        let key = self.key.unwrap();
+        match self.handle.with_timer(|timer| timer.poll_elapsed(key, lw)) {
+            Some(poll) => poll.map(Ok),
+            None => Poll::Ready(Err(gone())),
+        }
+    }
+
+    fn register(&mut self) -> io::Result<()> {
+        let handle = match self.handle.bind() {
+            Ok(handle) => handle,
+            Err(e) => panic!("failed to bind timer to a reactor: {}", e),
+        };
+
+        let (key, wakeup) = handle
+            .with_timer(|timer| timer.add(self.deadline))
+            .ok_or_else(gone)?;
+
+        if wakeup {
+            handle.wakeup();
+        }
+
+        self.handle = handle;
+        self.key = Some(key);
+        Ok(())
+    }
+}
+
+fn gone() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "reactor gone")
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<()> {
+        // a delay whose reactor is gone will never be woken up, so it
+        // completes now
+        self.poll_timer(lw).map(|_| ())
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.handle.with_timer(|timer| timer.remove(key));
+        }
+    }
+}
+
+impl fmt::Debug for Delay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Delay")
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
@@ -0,0 +1,289 @@
+use super::wheel::{self, Wheel};
+use super::{now_with_handle, Delay};
+use crate::reactor::Handle;
+
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll, Stream};
+
+use std::fmt;
+use std::marker::Unpin;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// A queue of values which are yielded once their deadline has been reached.
+///
+/// `DelayQueue` is a stream which yields each value inserted into it once its
+/// deadline has been reached, in the order in which the deadlines expire.
+/// Inserting a value returns a [`Key`], which can be used to change the
+/// deadline of the value with [`reset`], or to remove it with [`remove`].
+///
+/// The queue stores its values in a timing wheel of its own, and uses a
+/// single [`Delay`] for the next expiration, so it can manage a large number
+/// of values at the cost of a single timer in the reactor.
+///
+/// Once the queue is empty, the stream yields `None`. It can be polled again
+/// after more values have been inserted.
+///
+/// [`Key`]: struct.Key.html
+/// [`reset`]: #method.reset
+/// [`remove`]: #method.remove
+/// [`Delay`]: struct.Delay.html
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::StreamExt;
+/// use romio::timer::DelayQueue;
+/// use std::time::Duration;
+///
+/// # async fn expire() {
+/// let mut sessions = DelayQueue::new();
+/// let key = sessions.insert("alice", Duration::from_millis(10));
+/// sessions.insert("bob", Duration::from_millis(20));
+///
+/// // Alice was active again, so she should expire later.
+/// sessions.reset(&key, Duration::from_millis(30));
+///
+/// let expired = await!(sessions.next()).unwrap();
+/// assert_eq!(*expired.get_ref(), "bob");
+/// # }
+/// ```
+pub struct DelayQueue<T> {
+    wheel: Wheel<Entry<T>>,
+
+    /// The instant corresponding to `0` in the wheel's millisecond clock.
+    start: Instant,
+
+    /// The time up to which the wheel may be advanced, because `delay` has
+    /// been reached.
+    elapsed: u64,
+
+    /// Completes at the next expiration of the wheel.
+    delay: Option<Delay>,
+
+    handle: Handle,
+}
+
+/// A key identifying a value in a [`DelayQueue`].
+///
+/// [`DelayQueue`]: struct.DelayQueue.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: usize,
+}
+
+/// A value which has been removed from a [`DelayQueue`].
+///
+/// [`DelayQueue`]: struct.DelayQueue.html
+#[derive(Debug)]
+pub struct Expired<T> {
+    data: T,
+    deadline: Instant,
+    key: Key,
+}
+
+struct Entry<T> {
+    data: T,
+    deadline: Instant,
+}
+
+impl<T> DelayQueue<T> {
+    /// Creates a new, empty `DelayQueue`.
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue::new_with_handle(&Handle::default())
+    }
+
+    /// Creates a new, empty `DelayQueue`, driven by the reactor referenced by
+    /// `handle`.
+    ///
+    /// This is the same as [`new`], except that the queue's timer is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_with_handle(handle: &Handle) -> DelayQueue<T> {
+        DelayQueue {
+            wheel: Wheel::new(),
+            start: now_with_handle(handle),
+            elapsed: 0,
+            delay: None,
+            handle: handle.clone(),
+        }
+    }
+
+    /// Inserts `value` into the queue, to be yielded once `deadline` has been
+    /// reached.
+    pub fn insert_at(&mut self, value: T, deadline: Instant) -> Key {
+        let when = wheel::deadline_to_ms(self.start, deadline);
+        let index = self.wheel.insert(
+            when,
+            Entry {
+                data: value,
+                deadline,
+            },
+        );
+
+        self.reset_delay(when);
+        Key { index }
+    }
+
+    /// Inserts `value` into the queue, to be yielded once `timeout` has
+    /// passed.
+    pub fn insert(&mut self, value: T, timeout: Duration) -> Key {
+        let deadline = now_with_handle(&self.handle) + timeout;
+        self.insert_at(value, deadline)
+    }
+
+    /// Removes the value identified by `key` from the queue, whether or not
+    /// its deadline has been reached.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `key` does not identify a value in the queue.
+    pub fn remove(&mut self, key: &Key) -> Expired<T> {
+        let entry = self.wheel.remove(key.index);
+
+        Expired {
+            data: entry.data,
+            deadline: entry.deadline,
+            key: key.clone(),
+        }
+    }
+
+    /// Changes the deadline of the value identified by `key` to `deadline`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `key` does not identify a value in the queue.
+    pub fn reset_at(&mut self, key: &Key, deadline: Instant) {
+        let when = wheel::deadline_to_ms(self.start, deadline);
+
+        self.wheel.get_mut(key.index).expect("invalid key").deadline = deadline;
+        self.wheel.reset(key.index, when);
+        self.reset_delay(when);
+    }
+
+    /// Changes the deadline of the value identified by `key` to `timeout`
+    /// from now.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `key` does not identify a value in the queue.
+    pub fn reset(&mut self, key: &Key, timeout: Duration) {
+        let deadline = now_with_handle(&self.handle) + timeout;
+        self.reset_at(key, deadline)
+    }
+
+    /// Removes all values from the queue.
+    pub fn clear(&mut self) {
+        self.wheel.clear();
+        self.delay = None;
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> usize {
+        self.wheel.len()
+    }
+
+    /// Returns true if there are no values in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.wheel.is_empty()
+    }
+
+    /// Makes sure that the delay completes no later than `when`, so that the
+    /// task polling the queue is woken up for a new earliest value.
+    fn reset_delay(&mut self, when: u64) {
+        let deadline = self.start + Duration::from_millis(when);
+
+        if let Some(ref mut delay) = self.delay {
+            if deadline < delay.deadline() {
+                delay.reset(deadline);
+            }
+        }
+    }
+}
+
+impl<T> Stream for DelayQueue<T> {
+    type Item = Expired<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Expired<T>>> {
+        loop {
+            let elapsed = self.elapsed;
+            if let Some(index) = self.wheel.poll(elapsed) {
+                let entry = self.wheel.remove(index);
+
+                return Poll::Ready(Some(Expired {
+                    data: entry.data,
+                    deadline: entry.deadline,
+                    key: Key { index },
+                }));
+            }
+
+            let next = match self.wheel.next_expiration() {
+                Some(next) => next,
+                None => {
+                    self.delay = None;
+                    return Poll::Ready(None);
+                }
+            };
+
+            let deadline = self.start + Duration::from_millis(next);
+            match self.delay {
+                Some(ref mut delay) => {
+                    if delay.deadline() != deadline {
+                        delay.reset(deadline);
+                    }
+                }
+                None => self.delay = Some(Delay::new_with_handle(deadline, &self.handle)),
+            }
+
+            ready!(Pin::new(self.delay.as_mut().unwrap()).poll(lw));
+            self.elapsed = next;
+        }
+    }
+}
+
+// Values are never pinned.
+impl<T> Unpin for DelayQueue<T> {}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> DelayQueue<T> {
+        DelayQueue::new()
+    }
+}
+
+impl<T> fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DelayQueue")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> Expired<T> {
+    /// Returns a reference to the value.
+    pub fn get_ref(&self) -> &T {
+        &self.data
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    /// Consumes `self`, returning the value.
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    /// Returns the deadline of the value.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns the key which identified the value in the queue.
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+}
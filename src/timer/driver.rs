@@ -0,0 +1,158 @@
+//! The timer state of a reactor.
+
+use std::time::{Duration, Instant};
+
+use futures::task::{LocalWaker, Waker};
+use futures::Poll;
+use parking_lot::Mutex;
+
+use super::wheel::{self, Wheel};
+use super::Clock;
+
+/// Tracks the timers registered with a reactor.
+///
+/// The reactor takes the next expiration into account when it blocks in
+/// `turn`, and calls `process` afterwards to fire the timers that elapsed.
+pub(crate) struct Driver {
+    clock: Clock,
+
+    /// The instant corresponding to `0` in the wheel's millisecond clock.
+    start: Instant,
+
+    wheel: Mutex<Wheel<Option<Waker>>>,
+}
+
+impl Driver {
+    pub fn new(clock: Clock) -> Driver {
+        Driver {
+            start: clock.now(),
+            clock,
+            wheel: Mutex::new(Wheel::new()),
+        }
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Returns true if there are no timers registered.
+    pub fn is_empty(&self) -> bool {
+        self.wheel.lock().is_empty()
+    }
+
+    /// Registers a timer which fires at `deadline`.
+    ///
+    /// Returns the timer's key, and whether the reactor must be woken up
+    /// because the timer fires earlier than any other.
+    pub fn add(&self, deadline: Instant) -> (usize, bool) {
+        let when = wheel::deadline_to_ms(self.start, deadline);
+        let mut wheel = self.wheel.lock();
+
+        let earliest = is_earliest(&wheel, when);
+        (wheel.insert(when, None), earliest)
+    }
+
+    /// Changes the deadline of a registered timer, returning whether the
+    /// reactor must be woken up.
+    pub fn reset(&self, key: usize, deadline: Instant) -> bool {
+        let when = wheel::deadline_to_ms(self.start, deadline);
+        let mut wheel = self.wheel.lock();
+
+        let earliest = is_earliest(&wheel, when);
+        wheel.reset(key, when);
+        earliest
+    }
+
+    /// Deregisters a timer.
+    pub fn remove(&self, key: usize) {
+        self.wheel.lock().remove(key);
+    }
+
+    /// Returns true if the timer has fired.
+    pub fn is_expired(&self, key: usize) -> bool {
+        self.wheel.lock().is_expired(key)
+    }
+
+    /// Checks whether the timer has fired, registering the task to be woken
+    /// up when it fires otherwise.
+    pub fn poll_elapsed(&self, key: usize, lw: &LocalWaker) -> Poll<()> {
+        let mut wheel = self.wheel.lock();
+
+        if wheel.is_expired(key) {
+            return Poll::Ready(());
+        }
+
+        let waker = wheel.get_mut(key).unwrap();
+        match *waker {
+            Some(ref waker) if waker.will_wake_local(lw) => {}
+            _ => *waker = Some(lw.clone().into_waker()),
+        }
+
+        Poll::Pending
+    }
+
+    /// Returns how long the reactor may block before the next timer fires.
+    ///
+    /// A paused clock only moves when it is advanced, which wakes up the
+    /// reactor, so the reactor doesn't need to wake up for its timers unless
+    /// they have elapsed already.
+    pub fn next_timeout(&self) -> Option<Duration> {
+        let next = self.wheel.lock().next_expiration()?;
+        let deadline = self.start + Duration::from_millis(next);
+
+        let now = self.clock.now();
+        if deadline <= now {
+            Some(Duration::from_millis(0))
+        } else if self.clock.is_paused() {
+            None
+        } else {
+            Some(deadline - now)
+        }
+    }
+
+    /// Fires all timers whose deadline has been reached.
+    pub fn process(&self) {
+        let now = self.instant_to_ms(self.clock.now());
+        let mut wakers = vec![];
+
+        {
+            let mut wheel = self.wheel.lock();
+            while let Some(key) = wheel.poll(now) {
+                if let Some(waker) = wheel.get_mut(key).unwrap().take() {
+                    wakers.push(waker);
+                }
+            }
+        }
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Wakes up all tasks waiting on a timer, as the reactor is shutting down.
+    pub fn shutdown(&self) {
+        let mut wheel = self.wheel.lock();
+        for waker in wheel.values_mut() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn instant_to_ms(&self, instant: Instant) -> u64 {
+        if instant <= self.start {
+            return 0;
+        }
+
+        let dur = instant - self.start;
+        let ms = dur.as_secs().saturating_mul(1000);
+        ms.saturating_add(u64::from(dur.subsec_millis()))
+    }
+}
+
+fn is_earliest(wheel: &Wheel<Option<Waker>>, when: u64) -> bool {
+    match wheel.next_expiration() {
+        Some(next) => when < next,
+        None => true,
+    }
+}
@@ -0,0 +1,95 @@
+use super::Delay;
+use crate::reactor::Handle;
+
+use futures::task::LocalWaker;
+use futures::{ready, Poll, Stream};
+
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// A stream that yields an item at a fixed period.
+///
+/// Each item is the instant at which it was scheduled. If the stream is not
+/// polled for a while, the missed items are yielded immediately, one after
+/// another, rather than being skipped.
+///
+/// The stream ends if the reactor driving it is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::StreamExt;
+/// use romio::timer::Interval;
+/// use std::time::Duration;
+///
+/// # async fn tick() {
+/// let mut interval = Interval::new_interval(Duration::from_millis(10));
+/// for _ in 0..3 {
+///     let instant = await!(interval.next()).unwrap();
+///     println!("tick at {:?}", instant);
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Interval {
+    delay: Delay,
+    period: Duration,
+}
+
+impl Interval {
+    /// Creates a new `Interval` that yields its first item at `at`, and then
+    /// one item every `period`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `period` is zero.
+    pub fn new(at: Instant, period: Duration) -> Interval {
+        Interval::new_with_handle(at, period, &Handle::default())
+    }
+
+    /// Creates a new `Interval` that yields its first item after `period`,
+    /// and then one item every `period`, measured from [`timer::now`].
+    ///
+    /// [`timer::now`]: fn.now.html
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `period` is zero.
+    pub fn new_interval(period: Duration) -> Interval {
+        Interval::new(super::now() + period, period)
+    }
+
+    /// Creates a new `Interval`, driven by the reactor referenced by
+    /// `handle`.
+    ///
+    /// This is the same as [`new`], except that the interval is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`new`]: #method.new
+    pub fn new_with_handle(at: Instant, period: Duration, handle: &Handle) -> Interval {
+        assert!(period > Duration::new(0, 0), "`period` must be non-zero");
+
+        Interval {
+            delay: Delay::new_with_handle(at, handle),
+            period,
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Instant>> {
+        // the stream ends when the reactor driving it goes away
+        if ready!(self.delay.poll_timer(lw)).is_err() {
+            return Poll::Ready(None);
+        }
+
+        let at = self.delay.deadline();
+        let period = self.period;
+        self.delay.reset(at + period);
+
+        Poll::Ready(Some(at))
+    }
+}
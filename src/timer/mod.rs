@@ -0,0 +1,106 @@
+//! Utilities for tracking time.
+//!
+//! Timers are driven by the same reactor as romio's I/O objects: the reactor
+//! blocks no longer than until the next timer fires, and fires all elapsed
+//! timers each time it turns. Like I/O objects, timers bind lazily to the
+//! default reactor of the execution context in which they are first polled,
+//! or to the global fallback reactor.
+//!
+//! - [`Delay`] is a future that completes at a given instant.
+//! - [`Interval`] is a stream yielding values at a fixed period.
+//! - [`DelayQueue`] is a stream yielding many values, each once its own
+//!   deadline has been reached.
+//! - [`Timeout`] wraps a future, and fails with [`Elapsed`] if it does not
+//!   complete before a deadline.
+//!
+//! Timers have a resolution of one millisecond, and never fire before their
+//! deadline.
+//!
+//! Each reactor measures time using a [`Clock`], which is the system clock
+//! unless the reactor was created with another one. Use [`now`] to get the
+//! current time according to the clock of the current reactor, and [`sleep`]
+//! to wait for a duration measured by it.
+//!
+//! [`Delay`]: struct.Delay.html
+//! [`Interval`]: struct.Interval.html
+//! [`DelayQueue`]: struct.DelayQueue.html
+//! [`Timeout`]: struct.Timeout.html
+//! [`Elapsed`]: struct.Elapsed.html
+//! [`Clock`]: struct.Clock.html
+//! [`now`]: fn.now.html
+//! [`sleep`]: fn.sleep.html
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! #![feature(async_await, await_macro, futures_api)]
+//! use romio::tcp::TcpStream;
+//! use romio::timer::Timeout;
+//! use std::time::Duration;
+//!
+//! # async fn connect() -> std::io::Result<()> {
+//! let connect = TcpStream::connect("127.0.0.1:8080");
+//! let stream = await!(Timeout::new(connect, Duration::from_secs(5)))??;
+//! # drop(stream);
+//! # Ok(())}
+//! ```
+
+mod clock;
+mod delay;
+mod delay_queue;
+mod driver;
+mod interval;
+mod timeout;
+mod wheel;
+
+pub use self::clock::{Clock, Now};
+pub use self::delay::Delay;
+pub use self::delay_queue::{DelayQueue, Expired, Key};
+pub use self::interval::Interval;
+pub use self::timeout::{Elapsed, Timeout};
+
+pub(crate) use self::driver::Driver;
+
+use crate::reactor::Handle;
+
+use std::time::{Duration, Instant};
+
+/// Returns the current instant according to the clock of the default reactor
+/// of the current execution context.
+///
+/// Timers which bind lazily use this reactor, so deadlines for them should be
+/// computed from this function rather than from `Instant::now()`.
+///
+/// This doesn't start the global fallback reactor: if no reactor is set and
+/// the fallback isn't running, the system clock is read.
+pub fn now() -> Instant {
+    Handle::try_current()
+        .and_then(|handle| handle.with_timer(|timer| timer.clock().now()))
+        .unwrap_or_else(Instant::now)
+}
+
+/// Returns the current instant according to the clock of the reactor
+/// referenced by `handle`, or of the default reactor if it isn't bound.
+pub(crate) fn now_with_handle(handle: &Handle) -> Instant {
+    handle
+        .with_timer(|timer| timer.clock().now())
+        .unwrap_or_else(now)
+}
+
+/// Returns a future that completes once `duration` has passed, as measured
+/// by the clock of the default reactor of the current execution context.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::timer;
+/// use std::time::Duration;
+///
+/// # async fn wait() {
+/// await!(timer::sleep(Duration::from_millis(100)));
+/// # }
+/// ```
+pub fn sleep(duration: Duration) -> Delay {
+    Delay::new(now() + duration)
+}
@@ -0,0 +1,125 @@
+use super::Delay;
+use crate::reactor::Handle;
+
+use futures::task::LocalWaker;
+use futures::{Future, Poll};
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// A future that fails with [`Elapsed`] if the future it wraps does not
+/// complete before a deadline.
+///
+/// # Examples
+///
+/// ```rust
+/// #![feature(async_await, await_macro, futures_api)]
+/// use futures::future;
+/// use romio::timer::Timeout;
+/// use std::time::Duration;
+///
+/// # async fn wait() {
+/// let never = future::empty::<()>();
+/// let res = await!(Timeout::new(never, Duration::from_millis(10)));
+/// assert!(res.is_err());
+/// # }
+/// ```
+///
+/// [`Elapsed`]: struct.Elapsed.html
+#[derive(Debug)]
+pub struct Timeout<F> {
+    future: F,
+    delay: Delay,
+}
+
+/// Error returned by [`Timeout`] when the deadline has been reached.
+///
+/// It converts into an `io::Error` of kind `TimedOut`, so that it can be
+/// propagated with `?` from functions returning `io::Result`.
+///
+/// [`Timeout`]: struct.Timeout.html
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Elapsed {
+    _priv: (),
+}
+
+impl<F: Future> Timeout<F> {
+    /// Wraps `future`, failing if it does not complete within `timeout`,
+    /// measured from [`timer::now`].
+    ///
+    /// [`timer::now`]: fn.now.html
+    pub fn new(future: F, timeout: Duration) -> Timeout<F> {
+        Timeout::new_at(future, super::now() + timeout)
+    }
+
+    /// Wraps `future`, failing if it does not complete before `deadline`.
+    pub fn new_at(future: F, deadline: Instant) -> Timeout<F> {
+        Timeout::new_with_handle(future, deadline, &Handle::default())
+    }
+
+    /// Wraps `future`, failing if it does not complete before `deadline`,
+    /// with the deadline driven by the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`new_at`], except that the deadline is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`new_at`]: #method.new_at
+    pub fn new_with_handle(future: F, deadline: Instant, handle: &Handle) -> Timeout<F> {
+        Timeout {
+            future,
+            delay: Delay::new_with_handle(deadline, handle),
+        }
+    }
+
+    /// Returns a reference to the wrapped future.
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Returns a mutable reference to the wrapped future.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.future
+    }
+
+    /// Consumes the `Timeout`, returning the wrapped future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        // This is safe because `future` is never moved out of a pinned
+        // `Timeout`, and `delay` is `Unpin`.
+        let this = unsafe { Pin::get_mut_unchecked(self) };
+
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        if let Poll::Ready(output) = future.poll(lw) {
+            return Poll::Ready(Ok(output));
+        }
+
+        match Pin::new(&mut this.delay).poll(lw) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed { _priv: () })),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+impl From<Elapsed> for io::Error {
+    fn from(err: Elapsed) -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
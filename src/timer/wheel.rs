@@ -0,0 +1,358 @@
+//! A hierarchical timing wheel.
+//!
+//! The wheel tracks entries by the millisecond at which they expire. It is
+//! made up of levels of 64 slots each: a slot at level 0 covers a single
+//! millisecond, a slot at level 1 covers 64 milliseconds, and so on. An entry
+//! is stored at the lowest level whose slots are coarse enough to hold it
+//! without wrapping around, and is moved to lower levels as the wheel advances
+//! towards its expiration. Inserting, removing, and expiring an entry are all
+//! constant time.
+
+use std::cmp;
+use std::mem;
+use std::time::Instant;
+use std::{u64, usize};
+
+use slab::Slab;
+
+/// Number of bits of the expiration time covered by each level.
+const BITS_PER_LEVEL: usize = 6;
+
+/// Number of slots per level.
+const SLOTS: usize = 1 << BITS_PER_LEVEL;
+
+/// Number of levels, enough to cover the whole range of a `u64`.
+const LEVELS: usize = (64 + BITS_PER_LEVEL - 1) / BITS_PER_LEVEL;
+
+/// Marks an entry that is not linked into any slot.
+const UNLINKED: usize = usize::MAX;
+
+/// A hierarchical timing wheel, storing values of type `T`.
+pub(crate) struct Wheel<T> {
+    /// The time, in milliseconds, up to which the wheel has been advanced.
+    elapsed: u64,
+
+    levels: Vec<Level>,
+
+    entries: Slab<Entry<T>>,
+}
+
+struct Level {
+    /// Bit field of the slots that contain entries.
+    occupied: u64,
+
+    slots: Vec<Vec<usize>>,
+}
+
+struct Entry<T> {
+    when: u64,
+    value: T,
+
+    /// The position of the entry in the wheel, or `UNLINKED` for `level` if it
+    /// has expired.
+    level: usize,
+    slot: usize,
+    pos: usize,
+}
+
+/// Converts `deadline` to milliseconds since `start`, rounding up so that
+/// entries never expire early.
+pub(crate) fn deadline_to_ms(start: Instant, deadline: Instant) -> u64 {
+    if deadline <= start {
+        return 0;
+    }
+
+    let dur = deadline - start;
+    let ms = dur.as_secs().saturating_mul(1000);
+    ms.saturating_add((u64::from(dur.subsec_nanos()) + 999_999) / 1_000_000)
+}
+
+impl<T> Wheel<T> {
+    pub fn new() -> Wheel<T> {
+        Wheel {
+            elapsed: 0,
+            levels: (0..LEVELS).map(|_| Level::new()).collect(),
+            entries: Slab::new(),
+        }
+    }
+
+    /// Returns the number of entries in the wheel, expired or not.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        for level in &mut self.levels {
+            level.occupied = 0;
+            for slot in &mut level.slots {
+                slot.clear();
+            }
+        }
+    }
+
+    /// Returns true if the wheel holds no entries, expired or not.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Inserts a value which expires at `when`, returning its key.
+    ///
+    /// If `when` has already been reached, the entry expires on the next call
+    /// to `poll`.
+    pub fn insert(&mut self, when: u64, value: T) -> usize {
+        let key = self.entries.insert(Entry {
+            when,
+            value,
+            level: UNLINKED,
+            slot: 0,
+            pos: 0,
+        });
+
+        self.link(key);
+        key
+    }
+
+    /// Removes the entry for `key`, whether or not it has expired.
+    pub fn remove(&mut self, key: usize) -> T {
+        self.unlink(key);
+        self.entries.remove(key).value
+    }
+
+    /// Changes the expiration of the entry for `key`. An entry which has
+    /// already expired is linked into the wheel again.
+    pub fn reset(&mut self, key: usize, when: u64) {
+        self.unlink(key);
+        self.entries[key].when = when;
+        self.link(key);
+    }
+
+    /// Returns true if the entry for `key` has expired.
+    pub fn is_expired(&self, key: usize) -> bool {
+        self.entries[key].level == UNLINKED
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.entries.get_mut(key).map(|entry| &mut entry.value)
+    }
+
+    /// Returns an iterator over the values of all entries.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut().map(|(_, entry)| &mut entry.value)
+    }
+
+    /// Returns the time at which the wheel next needs to be polled, if it
+    /// holds any entries which have not expired.
+    ///
+    /// The returned time may be earlier than the expiration of any entry, when
+    /// entries need to be moved to a lower level.
+    pub fn next_expiration(&self) -> Option<u64> {
+        self.next_slot().map(|(_, _, deadline)| deadline)
+    }
+
+    /// Advances the wheel to `now`, returning the key of an entry which
+    /// expired on the way.
+    ///
+    /// Expired entries are not removed, but are marked as expired; this
+    /// function should be called repeatedly until it returns `None`.
+    pub fn poll(&mut self, now: u64) -> Option<usize> {
+        loop {
+            let (level, slot, deadline) = match self.next_slot() {
+                Some(next) if next.2 <= now => next,
+                _ => {
+                    self.elapsed = cmp::max(self.elapsed, now);
+                    return None;
+                }
+            };
+
+            self.elapsed = cmp::max(self.elapsed, deadline);
+
+            if level == 0 {
+                let key = *self.levels[0].slots[slot].last().unwrap();
+                self.unlink(key);
+                return Some(key);
+            }
+
+            // Move the entries of a higher level slot down, now that the
+            // wheel has reached it.
+            let keys = mem::replace(&mut self.levels[level].slots[slot], Vec::new());
+            self.levels[level].occupied &= !(1 << slot);
+
+            for key in keys {
+                self.entries[key].level = UNLINKED;
+                self.link(key);
+            }
+        }
+    }
+
+    /// Finds the first occupied slot, returning its level, its index, and
+    /// the time at which it is reached.
+    fn next_slot(&self) -> Option<(usize, usize, u64)> {
+        for (level, lvl) in self.levels.iter().enumerate() {
+            if lvl.occupied == 0 {
+                continue;
+            }
+
+            let shift = level * BITS_PER_LEVEL;
+            let now_slot = ((self.elapsed >> shift) as usize) % SLOTS;
+            let zeros = lvl.occupied.rotate_right(now_slot as u32).trailing_zeros() as usize;
+            let slot = (now_slot + zeros) % SLOTS;
+
+            // The time covered by a full rotation of this level, which
+            // overflows for the top level.
+            let rotation = 1u64.checked_shl((shift + BITS_PER_LEVEL) as u32);
+
+            let start = rotation.map_or(0, |rotation| self.elapsed & !(rotation - 1));
+            let mut deadline = start.saturating_add((slot as u64) << shift);
+            if slot < now_slot {
+                deadline = deadline.saturating_add(rotation.unwrap_or(u64::MAX));
+            }
+
+            return Some((level, slot, deadline));
+        }
+
+        None
+    }
+
+    fn link(&mut self, key: usize) {
+        let when = cmp::max(self.entries[key].when, self.elapsed);
+
+        // The level is determined by the most significant bit in which the
+        // expiration differs from the current time.
+        let masked = (self.elapsed ^ when) | (SLOTS as u64 - 1);
+        let significant = 63 - masked.leading_zeros() as usize;
+        let level = significant / BITS_PER_LEVEL;
+        let slot = ((when >> (level * BITS_PER_LEVEL)) as usize) % SLOTS;
+
+        let lvl = &mut self.levels[level];
+        lvl.occupied |= 1 << slot;
+        lvl.slots[slot].push(key);
+
+        let entry = &mut self.entries[key];
+        entry.level = level;
+        entry.slot = slot;
+        entry.pos = lvl.slots[slot].len() - 1;
+    }
+
+    fn unlink(&mut self, key: usize) {
+        let (level, slot, pos) = {
+            let entry = &mut self.entries[key];
+            if entry.level == UNLINKED {
+                return;
+            }
+            let pos = (entry.level, entry.slot, entry.pos);
+            entry.level = UNLINKED;
+            pos
+        };
+
+        let lvl = &mut self.levels[level];
+        lvl.slots[slot].swap_remove(pos);
+
+        if let Some(&moved) = lvl.slots[slot].get(pos) {
+            self.entries[moved].pos = pos;
+        }
+
+        if lvl.slots[slot].is_empty() {
+            lvl.occupied &= !(1 << slot);
+        }
+    }
+}
+
+impl Level {
+    fn new() -> Level {
+        Level {
+            occupied: 0,
+            slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Wheel;
+
+    fn drain(wheel: &mut Wheel<u64>, now: u64) -> Vec<u64> {
+        let mut expired = vec![];
+        while let Some(key) = wheel.poll(now) {
+            expired.push(*wheel.get_mut(key).unwrap());
+            wheel.remove(key);
+        }
+        expired.sort();
+        expired
+    }
+
+    #[test]
+    fn expires_in_order() {
+        let mut wheel = Wheel::new();
+        for &when in &[1, 63, 64, 100, 5_000, 300_000, 20_000_000] {
+            wheel.insert(when, when);
+        }
+
+        assert_eq!(drain(&mut wheel, 0), Vec::<u64>::new());
+        assert_eq!(drain(&mut wheel, 1), vec![1]);
+        assert_eq!(drain(&mut wheel, 99), vec![63, 64]);
+        assert_eq!(drain(&mut wheel, 100), vec![100]);
+        assert_eq!(drain(&mut wheel, 4_999), Vec::<u64>::new());
+        assert_eq!(drain(&mut wheel, 300_000), vec![5_000, 300_000]);
+        assert_eq!(drain(&mut wheel, u64::max_value()), vec![20_000_000]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn next_expiration_never_skips_entries() {
+        let mut wheel = Wheel::new();
+        wheel.insert(4_100, 4_100);
+
+        let mut now = 0;
+        while let Some(next) = wheel.next_expiration() {
+            assert!(next <= 4_100);
+            now = next;
+            if let Some(key) = wheel.poll(now) {
+                assert_eq!(wheel.remove(key), 4_100);
+            }
+        }
+
+        assert_eq!(now, 4_100);
+    }
+
+    #[test]
+    fn insert_in_the_past() {
+        let mut wheel = Wheel::new();
+        assert_eq!(drain(&mut wheel, 1_000), Vec::<u64>::new());
+
+        wheel.insert(10, 10);
+        assert_eq!(wheel.next_expiration(), Some(1_000));
+        assert_eq!(drain(&mut wheel, 1_000), vec![10]);
+    }
+
+    #[test]
+    fn remove_and_reset() {
+        let mut wheel = Wheel::new();
+        let a = wheel.insert(10, 1);
+        let b = wheel.insert(10, 2);
+        wheel.insert(10, 3);
+
+        assert_eq!(wheel.remove(a), 1);
+        wheel.reset(b, 2_000);
+        assert_eq!(drain(&mut wheel, 10), vec![3]);
+
+        assert!(!wheel.is_expired(b));
+        assert_eq!(drain(&mut wheel, 2_000), vec![2]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn expired_entries_stay_until_removed() {
+        let mut wheel = Wheel::new();
+        let key = wheel.insert(5, ());
+
+        assert_eq!(wheel.poll(5), Some(key));
+        assert!(wheel.is_expired(key));
+        assert_eq!(wheel.poll(5), None);
+
+        wheel.reset(key, 7);
+        assert!(!wheel.is_expired(key));
+        assert_eq!(wheel.poll(7), Some(key));
+    }
+}
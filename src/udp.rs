@@ -21,7 +21,7 @@ use futures::Future;
 use futures::{ready, Poll};
 use mio;
 
-use crate::reactor::PollEvented;
+use crate::reactor::{Handle, PollEvented, Ready};
 
 /// A UDP socket.
 pub struct UdpSocket {
@@ -52,11 +52,31 @@ impl UdpSocket {
         mio::net::UdpSocket::bind(addr).map(UdpSocket::new)
     }
 
-    fn new(socket: mio::net::UdpSocket) -> UdpSocket {
+    /// Creates a UDP socket from the given address, associated with the
+    /// reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the socket is registered with
+    /// the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(addr: &SocketAddr, handle: &Handle) -> io::Result<UdpSocket> {
+        let socket = mio::net::UdpSocket::bind(addr)?;
+        UdpSocket::new_with_handle(socket, handle)
+    }
+
+    pub(crate) fn new(socket: mio::net::UdpSocket) -> UdpSocket {
         let io = PollEvented::new(socket);
         UdpSocket { io: io }
     }
 
+    pub(crate) fn new_with_handle(
+        socket: mio::net::UdpSocket,
+        handle: &Handle,
+    ) -> io::Result<UdpSocket> {
+        let io = PollEvented::new_with_handle(socket, handle)?;
+        Ok(UdpSocket { io })
+    }
+
     /// Returns the local address that this listener is bound to.
     ///
     /// This can be useful, for example, when binding to port 0 to figure out
@@ -190,7 +210,7 @@ impl UdpSocket {
     ///
     /// The socket will remain in a read-ready state until calls to `poll_recv`
     /// return `Pending`.
-    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_read_ready(lw)
     }
 
@@ -201,7 +221,7 @@ impl UdpSocket {
     ///
     /// The I/O resource will remain in a write-ready state until calls to
     /// `poll_send` return `Pending`.
-    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<mio::Ready>> {
+    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_write_ready(lw)
     }
 
@@ -0,0 +1,277 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::Path;
+
+use super::OwnedFd;
+
+/// An address associated with a Unix socket.
+///
+/// Unlike `std::os::unix::net::SocketAddr`, it reports the names of sockets
+/// in the abstract namespace of Linux.
+#[derive(Clone)]
+pub struct SocketAddr {
+    addr: libc::sockaddr_un,
+    len: libc::socklen_t,
+}
+
+enum AddressKind<'a> {
+    Unnamed,
+    Pathname(&'a Path),
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Abstract(&'a [u8]),
+}
+
+/// Returns the offset of `sun_path` in `sockaddr_un`.
+fn path_offset(addr: &libc::sockaddr_un) -> usize {
+    let base = addr as *const _ as usize;
+    let path = &addr.sun_path as *const _ as usize;
+    path - base
+}
+
+impl SocketAddr {
+    /// Calls `f` to fill in an address, as `getsockname` or `accept` do.
+    pub(crate) fn new<F>(f: F) -> io::Result<SocketAddr>
+    where
+        F: FnOnce(*mut libc::sockaddr, *mut libc::socklen_t) -> libc::c_int,
+    {
+        unsafe {
+            let mut addr: libc::sockaddr_un = mem::zeroed();
+            let mut len = mem::size_of::<libc::sockaddr_un>() as libc::socklen_t;
+            if f(&mut addr as *mut _ as *mut _, &mut len) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(SocketAddr::from_parts(addr, len))
+        }
+    }
+
+    pub(crate) fn from_parts(addr: libc::sockaddr_un, mut len: libc::socklen_t) -> SocketAddr {
+        if len == 0 {
+            // When there is a datagram from an unnamed unix socket, Linux
+            // returns zero bytes of address.
+            len = path_offset(&addr) as libc::socklen_t;
+        }
+        SocketAddr { addr, len }
+    }
+
+    /// Creates the address of the socket at `path` in the filesystem.
+    ///
+    /// An error of kind `InvalidInput` is returned if `path` contains a NUL
+    /// byte or is too long.
+    pub fn from_pathname(path: impl AsRef<Path>) -> io::Result<SocketAddr> {
+        let path = path.as_ref().as_os_str().as_bytes();
+        unsafe {
+            let mut addr: libc::sockaddr_un = mem::zeroed();
+            addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+            if path.contains(&0) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "paths may not contain interior null bytes",
+                ));
+            }
+            if path.len() >= addr.sun_path.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "path must be shorter than SUN_LEN",
+                ));
+            }
+
+            for (dst, src) in addr.sun_path.iter_mut().zip(path) {
+                *dst = *src as libc::c_char;
+            }
+
+            // include the terminating NUL byte, as std does
+            let len = path_offset(&addr) + path.len() + 1;
+            Ok(SocketAddr {
+                addr,
+                len: len as libc::socklen_t,
+            })
+        }
+    }
+
+    /// Creates the address of the socket named `name` in the abstract
+    /// namespace.
+    ///
+    /// `name` doesn't include the leading NUL byte. An error of kind
+    /// `InvalidInput` is returned if it is too long.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn from_abstract_name(name: &[u8]) -> io::Result<SocketAddr> {
+        unsafe {
+            let mut addr: libc::sockaddr_un = mem::zeroed();
+            addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+            if name.len() + 1 > addr.sun_path.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "abstract socket name must be shorter than SUN_LEN",
+                ));
+            }
+
+            for (dst, src) in addr.sun_path[1..].iter_mut().zip(name) {
+                *dst = *src as libc::c_char;
+            }
+
+            let len = path_offset(&addr) + 1 + name.len();
+            Ok(SocketAddr {
+                addr,
+                len: len as libc::socklen_t,
+            })
+        }
+    }
+
+    /// Returns true if the address is unnamed.
+    pub fn is_unnamed(&self) -> bool {
+        match self.address() {
+            AddressKind::Unnamed => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the contents of this address if it is a `pathname` address.
+    pub fn as_pathname(&self) -> Option<&Path> {
+        match self.address() {
+            AddressKind::Pathname(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of this address, without the leading NUL byte, if it
+    /// is in the abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn as_abstract_name(&self) -> Option<&[u8]> {
+        match self.address() {
+            AddressKind::Abstract(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_parts(&self) -> (*const libc::sockaddr, libc::socklen_t) {
+        (&self.addr as *const _ as *const _, self.len)
+    }
+
+    fn address(&self) -> AddressKind<'_> {
+        let len = self.len as usize - path_offset(&self.addr);
+        let path = unsafe { &*(&self.addr.sun_path as *const [libc::c_char] as *const [u8]) };
+
+        if len == 0 {
+            return AddressKind::Unnamed;
+        }
+
+        if path[0] == 0 {
+            // a leading NUL byte names a socket in the abstract namespace
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                return AddressKind::Abstract(&path[1..len]);
+            }
+
+            // other platforms report unnamed sockets with a zeroed path
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            {
+                return AddressKind::Unnamed;
+            }
+        }
+
+        // The path may or may not be terminated by a NUL byte.
+        let path = &path[..len];
+        let path = match path.iter().position(|&b| b == 0) {
+            Some(end) => &path[..end],
+            None => path,
+        };
+        AddressKind::Pathname(OsStr::from_bytes(path).as_ref())
+    }
+}
+
+/// Returns the local address of the socket `fd`.
+pub(crate) fn local_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    SocketAddr::new(|addr, len| unsafe { libc::getsockname(fd, addr, len) })
+}
+
+/// Returns the address of the peer of the socket `fd`.
+pub(crate) fn peer_addr(fd: RawFd) -> io::Result<SocketAddr> {
+    SocketAddr::new(|addr, len| unsafe { libc::getpeername(fd, addr, len) })
+}
+
+/// Creates a nonblocking, close-on-exec Unix socket of type `ty`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn socket(ty: libc::c_int) -> io::Result<OwnedFd> {
+    let ty = ty | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+    let fd = unsafe { libc::socket(libc::AF_UNIX, ty, 0) };
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Creates a nonblocking, close-on-exec Unix socket of type `ty`.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn socket(ty: libc::c_int) -> io::Result<OwnedFd> {
+    unsafe {
+        let fd = libc::socket(libc::AF_UNIX, ty, 0);
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = OwnedFd::from_raw_fd(fd);
+
+        if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) == -1
+            || libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK) == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(fd)
+    }
+}
+
+/// Binds the socket `fd` to `addr`.
+pub(crate) fn bind(fd: &impl AsRawFd, addr: &SocketAddr) -> io::Result<()> {
+    let (addr, len) = addr.as_parts();
+    if unsafe { libc::bind(fd.as_raw_fd(), addr, len) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Connects the socket `fd` to `addr`.
+///
+/// A nonblocking connection still in progress isn't an error.
+pub(crate) fn connect(fd: &impl AsRawFd, addr: &SocketAddr) -> io::Result<()> {
+    let (addr, len) = addr.as_parts();
+    if unsafe { libc::connect(fd.as_raw_fd(), addr, len) } == -1 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EINPROGRESS) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for SocketAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.address() {
+            AddressKind::Unnamed => write!(f, "(unnamed)"),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddressKind::Abstract(name) => write!(f, "{:?} (abstract)", AsciiEscaped(name)),
+            AddressKind::Pathname(path) => write!(f, "{:?} (pathname)", path),
+        }
+    }
+}
+
+struct AsciiEscaped<'a>(&'a [u8]);
+
+impl<'a> fmt::Debug for AsciiEscaped<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for byte in self
+            .0
+            .iter()
+            .cloned()
+            .flat_map(::std::ascii::escape_default)
+        {
+            write!(f, "{}", byte as char)?;
+        }
+        write!(f, "\"")
+    }
+}
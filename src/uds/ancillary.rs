@@ -0,0 +1,367 @@
+//! Ancillary data carried by the messages of Unix sockets.
+
+use super::{OwnedFd, UCred, UnixDatagram, UnixStream};
+
+use crate::reactor::PollEvented;
+
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll};
+use mio::Evented;
+
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::pin::Pin;
+use std::ptr;
+
+/// The largest number of file descriptors in a message (`SCM_MAX_FD` on
+/// Linux), for which space is reserved when receiving.
+const MAX_FDS: usize = 253;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SEND_FLAGS: libc::c_int = libc::MSG_NOSIGNAL;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SEND_FLAGS: libc::c_int = 0;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_FLAGS: libc::c_int = libc::MSG_CMSG_CLOEXEC;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_FLAGS: libc::c_int = 0;
+
+/// The future returned by `send_with_fds`, on `UnixStream` and
+/// `UnixDatagram`.
+#[derive(Debug)]
+pub struct SendWithFds<'a, S> {
+    socket: &'a mut S,
+    buf: &'a [u8],
+    fds: &'a [RawFd],
+}
+
+/// The future returned by `recv_with_fds`, on `UnixStream` and
+/// `UnixDatagram`.
+#[derive(Debug)]
+pub struct RecvWithFds<'a, S> {
+    socket: &'a mut S,
+    buf: &'a mut [u8],
+}
+
+impl<'a, S> SendWithFds<'a, S> {
+    pub(crate) fn new(socket: &'a mut S, buf: &'a [u8], fds: &'a [RawFd]) -> SendWithFds<'a, S> {
+        SendWithFds { socket, buf, fds }
+    }
+}
+
+impl<'a, S> RecvWithFds<'a, S> {
+    pub(crate) fn new(socket: &'a mut S, buf: &'a mut [u8]) -> RecvWithFds<'a, S> {
+        RecvWithFds { socket, buf }
+    }
+}
+
+impl<'a> Future for SendWithFds<'a, UnixStream> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let SendWithFds { socket, buf, fds } = &mut *self;
+        socket.poll_send_with_fds(lw, buf, fds)
+    }
+}
+
+impl<'a> Future for SendWithFds<'a, UnixDatagram> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let SendWithFds { socket, buf, fds } = &mut *self;
+        socket.poll_send_with_fds(lw, buf, fds)
+    }
+}
+
+impl<'a> Future for RecvWithFds<'a, UnixStream> {
+    type Output = io::Result<(usize, Vec<OwnedFd>, bool)>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let RecvWithFds { socket, buf } = &mut *self;
+        socket.poll_recv_with_fds(lw, buf)
+    }
+}
+
+impl<'a> Future for RecvWithFds<'a, UnixDatagram> {
+    type Output = io::Result<(usize, Vec<OwnedFd>, bool)>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let RecvWithFds { socket, buf } = &mut *self;
+        socket.poll_recv_with_fds(lw, buf)
+    }
+}
+
+/// The future returned by `send_with_cred`, on `UnixStream` and
+/// `UnixDatagram`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct SendWithCred<'a, S> {
+    socket: &'a mut S,
+    buf: &'a [u8],
+}
+
+/// The future returned by `recv_with_cred`, on `UnixStream` and
+/// `UnixDatagram`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug)]
+pub struct RecvWithCred<'a, S> {
+    socket: &'a mut S,
+    buf: &'a mut [u8],
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, S> SendWithCred<'a, S> {
+    pub(crate) fn new(socket: &'a mut S, buf: &'a [u8]) -> SendWithCred<'a, S> {
+        SendWithCred { socket, buf }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a, S> RecvWithCred<'a, S> {
+    pub(crate) fn new(socket: &'a mut S, buf: &'a mut [u8]) -> RecvWithCred<'a, S> {
+        RecvWithCred { socket, buf }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Future for SendWithCred<'a, UnixStream> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let SendWithCred { socket, buf } = &mut *self;
+        socket.poll_send_with_cred(lw, buf)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Future for SendWithCred<'a, UnixDatagram> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let SendWithCred { socket, buf } = &mut *self;
+        socket.poll_send_with_cred(lw, buf)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Future for RecvWithCred<'a, UnixStream> {
+    type Output = io::Result<(usize, Option<UCred>)>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let RecvWithCred { socket, buf } = &mut *self;
+        socket.poll_recv_with_cred(lw, buf)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<'a> Future for RecvWithCred<'a, UnixDatagram> {
+    type Output = io::Result<(usize, Option<UCred>)>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let RecvWithCred { socket, buf } = &mut *self;
+        socket.poll_recv_with_cred(lw, buf)
+    }
+}
+
+/// The ancillary data received with a message.
+#[derive(Debug, Default)]
+pub(crate) struct Ancillary {
+    pub fds: Vec<OwnedFd>,
+    pub cred: Option<UCred>,
+
+    /// Whether some of the ancillary data was dropped (`MSG_CTRUNC`).
+    pub truncated: bool,
+}
+
+/// Sends `buf` along with the file descriptors `fds` and, if `cred` is set,
+/// the credentials of this process.
+pub(crate) fn poll_send<E>(
+    io: &PollEvented<E>,
+    lw: &LocalWaker,
+    buf: &[u8],
+    fds: &[RawFd],
+    cred: bool,
+) -> Poll<io::Result<usize>>
+where
+    E: Evented + AsRawFd,
+{
+    ready!(io.poll_write_ready(lw)?);
+
+    match send(io.get_ref().as_raw_fd(), buf, fds, cred) {
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+            io.clear_write_ready(lw)?;
+            Poll::Pending
+        }
+        r => Poll::Ready(r),
+    }
+}
+
+pub(crate) fn poll_recv<E>(
+    io: &PollEvented<E>,
+    lw: &LocalWaker,
+    buf: &mut [u8],
+) -> Poll<io::Result<(usize, Ancillary)>>
+where
+    E: Evented + AsRawFd,
+{
+    ready!(io.poll_read_ready(lw)?);
+
+    match recv(io.get_ref().as_raw_fd(), buf) {
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+            io.clear_read_ready(lw)?;
+            Poll::Pending
+        }
+        r => Poll::Ready(r),
+    }
+}
+
+/// Returns a buffer of at least `len` bytes, aligned for a `cmsghdr`.
+fn control_buffer(len: usize) -> Vec<usize> {
+    let word = mem::size_of::<usize>();
+    vec![0; (len + word - 1) / word]
+}
+
+fn cmsg_space(len: usize) -> usize {
+    unsafe { libc::CMSG_SPACE(len as libc::c_uint) as usize }
+}
+
+/// Copies `data` into the control message `cmsg`.
+unsafe fn write_cmsg<T>(cmsg: *mut libc::cmsghdr, ty: libc::c_int, data: &[T]) {
+    let len = mem::size_of_val(data);
+    (*cmsg).cmsg_level = libc::SOL_SOCKET;
+    (*cmsg).cmsg_type = ty;
+    (*cmsg).cmsg_len = libc::CMSG_LEN(len as libc::c_uint) as _;
+    ptr::copy_nonoverlapping(data.as_ptr() as *const u8, libc::CMSG_DATA(cmsg), len);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cred_space() -> usize {
+    cmsg_space(mem::size_of::<libc::ucred>())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn cred_space() -> usize {
+    0
+}
+
+fn send(socket: RawFd, buf: &[u8], fds: &[RawFd], cred: bool) -> io::Result<usize> {
+    unsafe {
+        let mut iov = libc::iovec {
+            iov_base: buf.as_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+
+        let mut space = 0;
+        if !fds.is_empty() {
+            space += cmsg_space(mem::size_of_val(fds));
+        }
+        if cred {
+            space += cred_space();
+        }
+        let mut control = control_buffer(space);
+
+        if space > 0 {
+            msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            msg.msg_controllen = space as _;
+
+            let first = libc::CMSG_FIRSTHDR(&msg);
+            if !fds.is_empty() {
+                write_cmsg(first, libc::SCM_RIGHTS, fds);
+            }
+
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                if cred {
+                    let cmsg = if fds.is_empty() {
+                        first
+                    } else {
+                        libc::CMSG_NXTHDR(&msg, first)
+                    };
+                    let ucred = libc::ucred {
+                        pid: libc::getpid(),
+                        uid: libc::getuid(),
+                        gid: libc::getgid(),
+                    };
+                    write_cmsg(cmsg, libc::SCM_CREDENTIALS, &[ucred]);
+                }
+            }
+        }
+
+        let n = libc::sendmsg(socket, &msg, SEND_FLAGS);
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+}
+
+fn recv(socket: RawFd, buf: &mut [u8]) -> io::Result<(usize, Ancillary)> {
+    unsafe {
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+
+        let space = cmsg_space(MAX_FDS * mem::size_of::<RawFd>()) + cred_space();
+        let mut control = control_buffer(space);
+
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = space as _;
+
+        let n = libc::recvmsg(socket, &mut msg, RECV_FLAGS);
+        if n == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut ancillary = Ancillary::default();
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            let data = libc::CMSG_DATA(cmsg);
+            let len = (*cmsg).cmsg_len as usize - (data as usize - cmsg as usize);
+
+            match ((*cmsg).cmsg_level, (*cmsg).cmsg_type) {
+                (libc::SOL_SOCKET, libc::SCM_RIGHTS) => {
+                    let data = data as *const RawFd;
+                    for i in 0..len / mem::size_of::<RawFd>() {
+                        let fd = OwnedFd::from_raw_fd(ptr::read_unaligned(data.add(i)));
+                        ancillary.fds.push(fd);
+                    }
+                }
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                (libc::SOL_SOCKET, libc::SCM_CREDENTIALS) => {
+                    let ucred = ptr::read_unaligned(data as *const libc::ucred);
+                    ancillary.cred = Some(UCred::from_ucred(&ucred));
+                }
+                _ => {}
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+
+        // `MSG_CMSG_CLOEXEC` sets close-on-exec atomically where it exists.
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        {
+            for fd in &ancillary.fds {
+                if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+        }
+
+        // The file descriptors which didn't fit, or couldn't be installed in
+        // this process, are lost. The data has been consumed already, so it
+        // is returned along with the rest.
+        ancillary.truncated = msg.msg_flags & libc::MSG_CTRUNC != 0;
+
+        Ok((n as usize, ancillary))
+    }
+}
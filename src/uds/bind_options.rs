@@ -0,0 +1,239 @@
+use super::addr::{self, SocketAddr};
+use super::UnixListener;
+
+use crate::reactor::Handle;
+
+use log::debug;
+use mio_uds;
+
+use std::ffi::CString;
+use std::fs::{self, Permissions};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+use std::os::unix::net;
+use std::path::{Path, PathBuf};
+
+/// Options for binding a [`UnixListener`] to a path.
+///
+/// By default, these behave like [`UnixListener::bind`]: binding fails if
+/// the path exists, and the socket file is left behind when the listener is
+/// dropped.
+///
+/// [`UnixListener`]: struct.UnixListener.html
+/// [`UnixListener::bind`]: struct.UnixListener.html#method.bind
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use romio::uds::BindOptions;
+///
+/// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+/// let listener = BindOptions::new()
+///     .remove_stale(true)
+///     .unlink_on_drop(true)
+///     .mode(0o660)
+///     .bind("/run/app/sock")?;
+/// # Ok(())}
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BindOptions {
+    remove_stale: bool,
+    unlink_on_drop: bool,
+    mode: Option<u32>,
+    uid: Option<libc::uid_t>,
+    gid: Option<libc::gid_t>,
+}
+
+/// Removes a socket file when it is dropped.
+#[derive(Debug)]
+pub(crate) struct Unlink(SocketFile);
+
+/// Identifies the socket file at a path, so that it is only removed if it
+/// hasn't been replaced.
+#[derive(Debug)]
+struct SocketFile {
+    path: PathBuf,
+    dev: u64,
+    ino: u64,
+}
+
+impl BindOptions {
+    /// Returns the default options.
+    pub fn new() -> BindOptions {
+        BindOptions::default()
+    }
+
+    /// Sets whether a stale socket left at the path is removed.
+    ///
+    /// When the path is in use, binding tries to connect to it. If the
+    /// connection is refused, no process listens on the socket anymore: it
+    /// is removed, and binding is retried. Files which aren't sockets, and
+    /// sockets which accept connections, are never removed.
+    ///
+    /// A process still listening on the socket accepts the probing connection,
+    /// which is closed without sending anything.
+    pub fn remove_stale(&mut self, remove: bool) -> &mut Self {
+        self.remove_stale = remove;
+        self
+    }
+
+    /// Sets whether the socket file is removed when the listener is dropped.
+    ///
+    /// The file is left in place if it has been replaced by another one in
+    /// the meantime.
+    pub fn unlink_on_drop(&mut self, unlink: bool) -> &mut Self {
+        self.unlink_on_drop = unlink;
+        self
+    }
+
+    /// Sets the permissions of the socket file, e.g. `0o660`.
+    ///
+    /// Connecting to a socket requires write permission on its file. The
+    /// permissions are set before the socket starts listening, so no
+    /// connection is accepted with the permissions given by the umask.
+    pub fn mode(&mut self, mode: u32) -> &mut Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Sets the owner and group of the socket file; `None` leaves them
+    /// unchanged.
+    ///
+    /// Changing the owner usually requires privileges.
+    pub fn owner(&mut self, uid: Option<u32>, gid: Option<u32>) -> &mut Self {
+        self.uid = uid;
+        self.gid = gid;
+        self
+    }
+
+    /// Creates a new `UnixListener` bound to `path` with these options.
+    pub fn bind(&self, path: impl AsRef<Path>) -> io::Result<UnixListener> {
+        let (listener, unlink) = self.bind_mio(path.as_ref())?;
+        Ok(UnixListener::new(listener).unlink_on_drop(unlink))
+    }
+
+    /// Creates a new `UnixListener` bound to `path` with these options,
+    /// associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the listener is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(
+        &self,
+        path: impl AsRef<Path>,
+        handle: &Handle,
+    ) -> io::Result<UnixListener> {
+        let (listener, unlink) = self.bind_mio(path.as_ref())?;
+        Ok(UnixListener::new_with_handle(listener, handle)?.unlink_on_drop(unlink))
+    }
+
+    fn bind_mio(&self, path: &Path) -> io::Result<(mio_uds::UnixListener, Option<Unlink>)> {
+        let addr = SocketAddr::from_pathname(path)?;
+        let fd = addr::socket(libc::SOCK_STREAM)?;
+
+        if let Err(e) = addr::bind(&fd, &addr) {
+            if !self.remove_stale || e.kind() != io::ErrorKind::AddrInUse {
+                return Err(e);
+            }
+            remove_stale(path, &addr, e)?;
+            addr::bind(&fd, &addr)?;
+        }
+
+        // the socket file is ours from here, and removed if anything fails
+        let file = SocketFile::new(path)?;
+        if let Err(e) = self.configure(path, &fd) {
+            drop(file.remove());
+            return Err(e);
+        }
+
+        let listener = unsafe { net::UnixListener::from_raw_fd(fd.into_raw_fd()) };
+        let listener = mio_uds::UnixListener::from_listener(listener)?;
+
+        let unlink = if self.unlink_on_drop {
+            Some(Unlink(file))
+        } else {
+            None
+        };
+        Ok((listener, unlink))
+    }
+
+    /// Sets up the socket file, then starts listening.
+    fn configure(&self, path: &Path, fd: &impl AsRawFd) -> io::Result<()> {
+        if let Some(mode) = self.mode {
+            fs::set_permissions(path, Permissions::from_mode(mode))?;
+        }
+
+        if self.uid.is_some() || self.gid.is_some() {
+            let path = CString::new(path.as_os_str().as_bytes())?;
+            let uid = self.uid.unwrap_or(!0);
+            let gid = self.gid.unwrap_or(!0);
+            if unsafe { libc::chown(path.as_ptr(), uid, gid) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        if unsafe { libc::listen(fd.as_raw_fd(), 128) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// Removes the socket at `path` if no process listens on it anymore,
+/// otherwise returns `err`.
+fn remove_stale(path: &Path, addr: &SocketAddr, err: io::Error) -> io::Result<()> {
+    let file = match SocketFile::new(path) {
+        Ok(file) => file,
+        Err(_) => return Err(err),
+    };
+
+    let probe = addr::socket(libc::SOCK_STREAM)?;
+    match addr::connect(&probe, addr) {
+        Err(ref e) if e.raw_os_error() == Some(libc::ECONNREFUSED) => {}
+        // the socket has been removed in the meantime
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        _ => return Err(err),
+    }
+
+    debug!("removing stale socket {}", path.display());
+    file.remove()
+}
+
+impl SocketFile {
+    /// Returns the socket file at `path`, failing if it isn't a socket.
+    fn new(path: &Path) -> io::Result<SocketFile> {
+        let metadata = fs::symlink_metadata(path)?;
+        if !metadata.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "path exists and isn't a socket",
+            ));
+        }
+
+        Ok(SocketFile {
+            path: path.to_owned(),
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+
+    /// Removes the file, unless it has been replaced by another one.
+    fn remove(&self) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(&self.path)?;
+        if metadata.dev() != self.dev || metadata.ino() != self.ino {
+            return Ok(());
+        }
+        fs::remove_file(&self.path)
+    }
+}
+
+impl Drop for Unlink {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.remove() {
+            debug!("failed to remove socket {}: {}", self.0.path.display(), e);
+        }
+    }
+}
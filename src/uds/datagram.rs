@@ -1,15 +1,24 @@
-use crate::reactor::PollEvented;
+use super::addr::{self, SocketAddr};
+use super::ancillary::{self, RecvWithFds, SendWithFds};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::ancillary::{RecvWithCred, SendWithCred};
+use super::ucred::{self, UCred};
+use super::OwnedFd;
+
+use crate::reactor::{Handle, PollEvented, Ready};
 
 use futures::task::LocalWaker;
 use futures::{ready, Poll};
-use mio::Ready;
 use mio_uds;
 
 use std::fmt;
 use std::io;
 use std::net::Shutdown;
 use std::os::unix::io::{AsRawFd, RawFd};
-use std::os::unix::net::SocketAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::net;
 use std::path::Path;
 
 /// An I/O object representing a Unix datagram socket.
@@ -34,6 +43,52 @@ impl UnixDatagram {
         Ok(UnixDatagram::new(socket))
     }
 
+    /// Creates a new `UnixDatagram` bound to the specified path, associated
+    /// with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the socket is registered with
+    /// the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(path: impl AsRef<Path>, handle: &Handle) -> io::Result<UnixDatagram> {
+        let socket = mio_uds::UnixDatagram::bind(path)?;
+        UnixDatagram::new_with_handle(socket, handle)
+    }
+
+    /// Creates a new `UnixDatagram` bound to `name` in the abstract namespace
+    /// of Linux.
+    ///
+    /// `name` doesn't include the leading NUL byte.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let sock = UnixDatagram::bind_abstract(b"romio-example")?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixDatagram> {
+        let socket = bind_abstract(name)?;
+        Ok(UnixDatagram::new(socket))
+    }
+
+    /// Creates a new `UnixDatagram` bound to `name` in the abstract namespace
+    /// of Linux, associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind_abstract`], except that the socket is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`bind_abstract`]: #method.bind_abstract
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract_with_handle(name: &[u8], handle: &Handle) -> io::Result<UnixDatagram> {
+        let socket = bind_abstract(name)?;
+        UnixDatagram::new_with_handle(socket, handle)
+    }
+
     /// Creates an unnamed pair of connected sockets.
     ///
     /// This function will create a pair of interconnected Unix sockets for
@@ -57,11 +112,34 @@ impl UnixDatagram {
         Ok((a, b))
     }
 
-    fn new(socket: mio_uds::UnixDatagram) -> UnixDatagram {
+    /// Creates an unnamed pair of connected sockets, associated with the
+    /// reactor referenced by `handle`.
+    ///
+    /// This is the same as [`pair`], except that the sockets are registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`pair`]: #method.pair
+    pub fn pair_with_handle(handle: &Handle) -> io::Result<(UnixDatagram, UnixDatagram)> {
+        let (a, b) = mio_uds::UnixDatagram::pair()?;
+        let a = UnixDatagram::new_with_handle(a, handle)?;
+        let b = UnixDatagram::new_with_handle(b, handle)?;
+
+        Ok((a, b))
+    }
+
+    pub(crate) fn new(socket: mio_uds::UnixDatagram) -> UnixDatagram {
         let io = PollEvented::new(socket);
         UnixDatagram { io }
     }
 
+    pub(crate) fn new_with_handle(
+        socket: mio_uds::UnixDatagram,
+        handle: &Handle,
+    ) -> io::Result<UnixDatagram> {
+        let io = PollEvented::new_with_handle(socket, handle)?;
+        Ok(UnixDatagram { io })
+    }
+
     /// Creates a new `UnixDatagram` which is not bound to any address.
     ///
     /// # Examples
@@ -78,6 +156,18 @@ impl UnixDatagram {
         Ok(UnixDatagram::new(socket))
     }
 
+    /// Creates a new `UnixDatagram` which is not bound to any address,
+    /// associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`unbound`], except that the socket is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`unbound`]: #method.unbound
+    pub fn unbound_with_handle(handle: &Handle) -> io::Result<UnixDatagram> {
+        let socket = mio_uds::UnixDatagram::unbound()?;
+        UnixDatagram::new_with_handle(socket, handle)
+    }
+
     /// Test whether this socket is ready to be read or not.
     pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_read_ready(lw)
@@ -100,7 +190,7 @@ impl UnixDatagram {
     /// # Ok(()) }
     /// ```
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.io.get_ref().local_addr()
+        addr::local_addr(self.as_raw_fd())
     }
 
     /// Returns the address of this socket's peer.
@@ -118,7 +208,28 @@ impl UnixDatagram {
     /// # Ok(()) }
     /// ```
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
-        self.io.get_ref().peer_addr()
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    /// Returns the credentials of the process which created the peer of this
+    /// socket, when it was created with `pair` or connected.
+    ///
+    /// On Linux and Android, these are the credentials at the time of the
+    /// `connect` or `socketpair` call. Other platforms may only support this
+    /// for stream sockets.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # fn run() -> std::io::Result<()> {
+    /// let (sock, _peer) = UnixDatagram::pair()?;
+    /// let cred = sock.peer_cred()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        ucred::get_peer_cred(self)
     }
 
     /// Receives data from the socket.
@@ -132,7 +243,17 @@ impl UnixDatagram {
     ) -> Poll<io::Result<(usize, SocketAddr)>> {
         ready!(self.io.poll_read_ready(lw)?);
 
-        let r = self.io.get_ref().recv_from(buf);
+        let fd = self.as_raw_fd();
+        let mut n = 0;
+        let r = SocketAddr::new(|addr, addr_len| unsafe {
+            n = libc::recvfrom(fd, buf.as_mut_ptr() as *mut _, buf.len(), 0, addr, addr_len);
+            if n == -1 {
+                -1
+            } else {
+                0
+            }
+        })
+        .map(|addr| (n as usize, addr));
 
         if is_wouldblock(&r) {
             self.io.clear_read_ready(lw)?;
@@ -163,6 +284,214 @@ impl UnixDatagram {
         }
     }
 
+    /// Sends data on the socket to the address `addr`.
+    ///
+    /// Unlike [`poll_send_to`], this can send to sockets in the abstract
+    /// namespace of Linux, e.g. to reply to the address returned by
+    /// [`poll_recv_from`]. On success, returns the number of bytes written.
+    ///
+    /// [`poll_send_to`]: #method.poll_send_to
+    /// [`poll_recv_from`]: #method.poll_recv_from
+    pub fn poll_send_to_addr(
+        &self,
+        lw: &LocalWaker,
+        buf: &[u8],
+        addr: &SocketAddr,
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(lw)?);
+
+        let (addr, addr_len) = addr.as_parts();
+        let n = unsafe {
+            libc::sendto(
+                self.as_raw_fd(),
+                buf.as_ptr() as *const _,
+                buf.len(),
+                0,
+                addr,
+                addr_len,
+            )
+        };
+        let r = if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        };
+
+        if is_wouldblock(&r) {
+            self.io.clear_write_ready(lw)?;
+            Poll::Pending
+        } else {
+            Poll::Ready(r)
+        }
+    }
+
+    /// Sends data on the socket to its peer, along with the file descriptors
+    /// `fds`, which the peer receives with [`recv_with_fds`].
+    ///
+    /// The socket must be connected. On success, returns the number of bytes
+    /// written.
+    ///
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixDatagram;
+    /// use std::fs::File;
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let (mut sock, _peer) = UnixDatagram::pair()?;
+    /// let file = File::open("/etc/hosts")?;
+    /// let fds = [file.as_raw_fd()];
+    /// await!(sock.send_with_fds(b"hosts", &fds))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn send_with_fds<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+        fds: &'a [RawFd],
+    ) -> SendWithFds<'a, UnixDatagram> {
+        SendWithFds::new(self, buf, fds)
+    }
+
+    /// Receives a datagram from the socket along with the file descriptors
+    /// sent with it.
+    ///
+    /// On success, returns the number of bytes read, the file descriptors,
+    /// which are set to close on exec, and whether some file descriptors were
+    /// dropped. File descriptors which don't fit in the process, e.g. because
+    /// it reached its limit of open files, are dropped by the kernel: those
+    /// which arrived are returned along with the datagram, and the flag is
+    /// set.
+    pub fn recv_with_fds<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvWithFds<'a, UnixDatagram> {
+        RecvWithFds::new(self, buf)
+    }
+
+    /// Sends data on the socket to its peer, along with the file descriptors
+    /// `fds`.
+    ///
+    /// See [`send_with_fds`] for details.
+    ///
+    /// [`send_with_fds`]: #method.send_with_fds
+    pub fn poll_send_with_fds(
+        &self,
+        lw: &LocalWaker,
+        buf: &[u8],
+        fds: &[RawFd],
+    ) -> Poll<io::Result<usize>> {
+        ancillary::poll_send(&self.io, lw, buf, fds, false)
+    }
+
+    /// Receives a datagram from the socket along with the file descriptors
+    /// sent with it.
+    ///
+    /// See [`recv_with_fds`] for details.
+    ///
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    pub fn poll_recv_with_fds(
+        &self,
+        lw: &LocalWaker,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Vec<OwnedFd>, bool)>> {
+        let (n, received) = ready!(ancillary::poll_recv(&self.io, lw, buf))?;
+        Poll::Ready(Ok((n, received.fds, received.truncated)))
+    }
+
+    /// Sets the value of the `SO_PASSCRED` option on this socket.
+    ///
+    /// When it is set, the credentials of the sender are received with each
+    /// message by [`recv_with_cred`], even if the sender doesn't send them
+    /// explicitly.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        ucred::set_passcred(self, passcred)
+    }
+
+    /// Gets the value of the `SO_PASSCRED` option on this socket.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        ucred::get_passcred(self)
+    }
+
+    /// Sends data on the socket to its peer, along with the credentials of this
+    /// process (`SCM_CREDENTIALS`).
+    ///
+    /// The peer receives them with [`recv_with_cred`] if it has set
+    /// [`set_passcred`]. On success, returns the number of bytes written.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    /// [`set_passcred`]: #method.set_passcred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_with_cred<'a>(&'a mut self, buf: &'a [u8]) -> SendWithCred<'a, UnixDatagram> {
+        SendWithCred::new(self, buf)
+    }
+
+    /// Receives data from the socket along with the credentials of the
+    /// process which sent it (`SCM_CREDENTIALS`).
+    ///
+    /// The credentials are only received if [`set_passcred`] has been set
+    /// before the data was sent. They are checked by the kernel, so they can
+    /// be used to authorize the sender of each message.
+    ///
+    /// File descriptors sent along with the data are closed; use
+    /// [`recv_with_fds`] to receive them.
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixDatagram;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut sock = UnixDatagram::bind("/tmp/sock")?;
+    /// sock.set_passcred(true)?;
+    ///
+    /// let mut buf = [0; 1024];
+    /// let (n, cred) = await!(sock.recv_with_cred(&mut buf))?;
+    /// if let Some(cred) = cred {
+    ///     println!("{} bytes from pid {:?}, uid {}", n, cred.pid(), cred.uid);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_with_cred<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvWithCred<'a, UnixDatagram> {
+        RecvWithCred::new(self, buf)
+    }
+
+    /// Sends data on the socket to its peer, along with the credentials of this
+    /// process.
+    ///
+    /// See [`send_with_cred`] for details.
+    ///
+    /// [`send_with_cred`]: #method.send_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_send_with_cred(&self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        ancillary::poll_send(&self.io, lw, buf, &[], true)
+    }
+
+    /// Receives data from the socket along with the credentials of the
+    /// process which sent it.
+    ///
+    /// See [`recv_with_cred`] for details.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_with_cred(
+        &self,
+        lw: &LocalWaker,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<UCred>)>> {
+        let (n, received) = ready!(ancillary::poll_recv(&self.io, lw, buf))?;
+        Poll::Ready(Ok((n, received.cred)))
+    }
+
     /// Returns the value of the `SO_ERROR` option.
     ///
     /// # Examples
@@ -215,6 +544,16 @@ impl AsRawFd for UnixDatagram {
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn bind_abstract(name: &[u8]) -> io::Result<mio_uds::UnixDatagram> {
+    let addr = SocketAddr::from_abstract_name(name)?;
+    let fd = addr::socket(libc::SOCK_DGRAM)?;
+    addr::bind(&fd, &addr)?;
+
+    let socket = unsafe { net::UnixDatagram::from_raw_fd(fd.into_raw_fd()) };
+    mio_uds::UnixDatagram::from_datagram(socket)
+}
+
 fn is_wouldblock<T>(r: &io::Result<T>) -> bool {
     match *r {
         Ok(_) => false,
@@ -1,6 +1,11 @@
+use super::addr::{self, SocketAddr};
+use super::bind_options::Unlink;
 use super::UnixStream;
 
-use crate::reactor::PollEvented;
+use crate::reactor::{Handle, PollEvented};
+use crate::server::{
+    Accept, AcceptPolicy, ConnectionLimit, LimitedIncoming, ShutdownController, UntilShutdown,
+};
 
 use futures::task::LocalWaker;
 use futures::{ready, Poll, Stream};
@@ -9,7 +14,9 @@ use mio_uds;
 use std::fmt;
 use std::io;
 use std::os::unix::io::{AsRawFd, RawFd};
-use std::os::unix::net::{self, SocketAddr};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::os::unix::net;
 use std::path::Path;
 use std::pin::Pin;
 
@@ -39,11 +46,24 @@ use std::pin::Pin;
 /// ```
 pub struct UnixListener {
     io: PollEvented<mio_uds::UnixListener>,
+
+    /// The reactor the accepted streams are registered with, if the listener
+    /// was created with one.
+    handle: Option<Handle>,
+
+    /// Removes the socket file, when bound with `BindOptions::unlink_on_drop`.
+    unlink: Option<Unlink>,
 }
 
 impl UnixListener {
     /// Creates a new `UnixListener` bound to the specified path.
     ///
+    /// Binding fails if the path exists, e.g. when a previous process left
+    /// its socket behind. [`BindOptions`] can remove stale sockets, and the
+    /// socket file when the listener is dropped.
+    ///
+    /// [`BindOptions`]: struct.BindOptions.html
+    ///
     /// # Examples
     /// Create a Unix Domain Socket on `/tmp/sock`.
     ///
@@ -57,8 +77,82 @@ impl UnixListener {
     ///
     pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixListener> {
         let listener = mio_uds::UnixListener::bind(path)?;
+        Ok(UnixListener::new(listener))
+    }
+
+    /// Creates a new `UnixListener` bound to the specified path and associated
+    /// with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the listener, and the streams
+    /// it accepts, are registered with the given reactor instead of lazily
+    /// binding to the default one.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(path: impl AsRef<Path>, handle: &Handle) -> io::Result<UnixListener> {
+        let listener = mio_uds::UnixListener::bind(path)?;
+        UnixListener::new_with_handle(listener, handle)
+    }
+
+    /// Creates a new `UnixListener` bound to `name` in the abstract namespace
+    /// of Linux.
+    ///
+    /// `name` doesn't include the leading NUL byte. Abstract sockets have no
+    /// file in the filesystem, and their name is released when the last
+    /// socket bound to it is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let socket = UnixListener::bind_abstract(b"romio-example")?;
+    /// # Ok(())}
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixListener> {
+        let listener = bind_abstract(name)?;
+        Ok(UnixListener::new(listener))
+    }
+
+    /// Creates a new `UnixListener` bound to `name` in the abstract namespace
+    /// of Linux, associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind_abstract`], except that the listener is
+    /// registered with the given reactor, along with the streams it accepts,
+    /// instead of lazily binding to the default one.
+    ///
+    /// [`bind_abstract`]: #method.bind_abstract
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract_with_handle(name: &[u8], handle: &Handle) -> io::Result<UnixListener> {
+        let listener = bind_abstract(name)?;
+        UnixListener::new_with_handle(listener, handle)
+    }
+
+    pub(crate) fn new(listener: mio_uds::UnixListener) -> UnixListener {
         let io = PollEvented::new(listener);
-        Ok(UnixListener { io })
+        UnixListener {
+            io,
+            handle: None,
+            unlink: None,
+        }
+    }
+
+    pub(crate) fn new_with_handle(
+        listener: mio_uds::UnixListener,
+        handle: &Handle,
+    ) -> io::Result<UnixListener> {
+        let io = PollEvented::new_with_handle(listener, handle)?;
+        Ok(UnixListener {
+            io,
+            handle: Some(handle.clone()),
+            unlink: None,
+        })
+    }
+
+    pub(crate) fn unlink_on_drop(mut self, unlink: Option<Unlink>) -> UnixListener {
+        self.unlink = unlink;
+        self
     }
 
     /// Returns the local socket address of this listener.
@@ -74,7 +168,7 @@ impl UnixListener {
     /// # Ok(())}
     /// ```
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.io.get_ref().local_addr()
+        addr::local_addr(self.as_raw_fd())
     }
 
     /// Returns the value of the `SO_ERROR` option.
@@ -128,18 +222,22 @@ impl UnixListener {
         Incoming::new(self)
     }
 
-    fn poll_accept(&self, lw: &LocalWaker) -> Poll<io::Result<(UnixStream, SocketAddr)>> {
-        let (io, addr) = ready!(self.poll_accept_std(lw)?);
+    fn poll_accept(&self, lw: &LocalWaker) -> Poll<io::Result<UnixStream>> {
+        let io = ready!(self.poll_accept_std(lw)?);
 
         let io = mio_uds::UnixStream::from_stream(io)?;
-        Poll::Ready(Ok((UnixStream::new(io), addr)))
+        let io = match self.handle {
+            Some(ref handle) => UnixStream::new_with_handle(io, handle)?,
+            None => UnixStream::new(io),
+        };
+        Poll::Ready(Ok(io))
     }
 
-    fn poll_accept_std(&self, lw: &LocalWaker) -> Poll<io::Result<(net::UnixStream, SocketAddr)>> {
+    fn poll_accept_std(&self, lw: &LocalWaker) -> Poll<io::Result<net::UnixStream>> {
         ready!(self.io.poll_read_ready(lw)?);
 
         match self.io.get_ref().accept_std() {
-            Ok(Some((sock, addr))) => Poll::Ready(Ok((sock, addr))),
+            Ok(Some((sock, _))) => Poll::Ready(Ok(sock)),
             Ok(None) => {
                 self.io.clear_read_ready(lw)?;
                 Poll::Pending
@@ -165,23 +263,72 @@ impl AsRawFd for UnixListener {
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn bind_abstract(name: &[u8]) -> io::Result<mio_uds::UnixListener> {
+    let addr = SocketAddr::from_abstract_name(name)?;
+    let fd = addr::socket(libc::SOCK_STREAM)?;
+    addr::bind(&fd, &addr)?;
+    if unsafe { libc::listen(fd.as_raw_fd(), 128) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let listener = unsafe { net::UnixListener::from_raw_fd(fd.into_raw_fd()) };
+    mio_uds::UnixListener::from_listener(listener)
+}
+
 /// Stream of listeners
 #[derive(Debug)]
 pub struct Incoming {
     inner: UnixListener,
+    accept: Accept,
 }
 
 impl Incoming {
     pub(crate) fn new(listener: UnixListener) -> Incoming {
-        Incoming { inner: listener }
+        Incoming {
+            inner: listener,
+            accept: Accept::default(),
+        }
+    }
+
+    /// Sets how errors returned by `accept` are handled.
+    ///
+    /// By default, every error is yielded by the stream. See [`AcceptPolicy`]
+    /// for details.
+    ///
+    /// [`AcceptPolicy`]: ../server/struct.AcceptPolicy.html
+    pub fn accept_policy(mut self, policy: AcceptPolicy) -> Incoming {
+        self.accept.set_policy(policy);
+        self
+    }
+
+    /// Limits the number of live connections accepted by this stream.
+    ///
+    /// See [`ConnectionLimit`] for details.
+    ///
+    /// [`ConnectionLimit`]: ../server/struct.ConnectionLimit.html
+    pub fn limit(self, limit: &ConnectionLimit) -> LimitedIncoming<Incoming> {
+        LimitedIncoming::new(self, limit)
+    }
+
+    /// Ends this stream when the shutdown of `controller` begins.
+    ///
+    /// See [`ShutdownController`] for details.
+    ///
+    /// [`ShutdownController`]: ../server/struct.ShutdownController.html
+    pub fn until_shutdown(self, controller: &ShutdownController) -> UntilShutdown<Incoming> {
+        UntilShutdown::new(self, controller)
     }
 }
 
 impl Stream for Incoming {
     type Item = io::Result<UnixStream>;
 
-    fn poll_next(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
-        let (socket, _) = ready!(self.inner.poll_accept(lw)?);
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = &mut this.inner;
+
+        let socket = ready!(this.accept.poll_accept(lw, |lw| inner.poll_accept(lw))?);
         Poll::Ready(Some(Ok(socket)))
     }
 }
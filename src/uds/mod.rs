@@ -23,12 +23,31 @@
 //! }
 //! ```
 
+mod addr;
+mod ancillary;
+mod bind_options;
 mod datagram;
 mod listener;
+mod owned_fd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod seqpacket;
+mod split;
 mod stream;
 mod ucred;
 
+pub use self::addr::SocketAddr;
+pub use self::ancillary::{RecvWithFds, SendWithFds};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::ancillary::{RecvWithCred, SendWithCred};
+pub use self::bind_options::BindOptions;
 pub use self::datagram::UnixDatagram;
 pub use self::listener::{Incoming, UnixListener};
+pub use self::owned_fd::OwnedFd;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::seqpacket::{
+    RecvPacket, SendPacket, SeqpacketConnectFuture, SeqpacketIncoming, UnixSeqpacket,
+    UnixSeqpacketListener,
+};
+pub use self::split::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
 pub use self::stream::{ConnectFuture, UnixStream};
 pub use self::ucred::UCred;
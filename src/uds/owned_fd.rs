@@ -0,0 +1,58 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+/// An owned file descriptor, closed when it is dropped.
+///
+/// File descriptors received over a Unix socket are returned as `OwnedFd`s.
+/// They can be converted into the type they stand for with `into_raw_fd` and
+/// the `FromRawFd` implementation of that type, e.g. `std::fs::File`.
+pub struct OwnedFd {
+    fd: RawFd,
+}
+
+impl OwnedFd {
+    /// Creates a new `OwnedFd` referring to the same file description, with
+    /// close-on-exec set.
+    pub fn try_clone(&self) -> io::Result<OwnedFd> {
+        let fd = unsafe { libc::fcntl(self.fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if fd == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(OwnedFd { fd })
+        }
+    }
+}
+
+impl AsRawFd for OwnedFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl IntoRawFd for OwnedFd {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for OwnedFd {
+    unsafe fn from_raw_fd(fd: RawFd) -> OwnedFd {
+        OwnedFd { fd }
+    }
+}
+
+impl Drop for OwnedFd {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl fmt::Debug for OwnedFd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwnedFd").field(&self.fd).finish()
+    }
+}
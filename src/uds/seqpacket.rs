@@ -0,0 +1,695 @@
+//! Unix sockets of type `SOCK_SEQPACKET`.
+
+use super::addr::{self, SocketAddr};
+use super::ucred::{self, UCred};
+use super::OwnedFd;
+
+use crate::reactor::{Handle, PollEvented, Ready};
+use crate::server::{
+    Accept, AcceptPolicy, ConnectionLimit, LimitedIncoming, ShutdownController, UntilShutdown,
+};
+
+use futures::task::LocalWaker;
+use futures::{ready, Future, Poll, Stream};
+use mio::event::Evented;
+use mio::unix::EventedFd;
+use mio::{PollOpt, Token};
+
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::Shutdown;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::Path;
+use std::pin::Pin;
+use std::ptr;
+
+/// A Unix socket of type `SOCK_SEQPACKET` which can accept connections from
+/// other `SOCK_SEQPACKET` sockets.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(async_await, await_macro, futures_api)]
+/// use romio::uds::{UnixSeqpacket, UnixSeqpacketListener};
+/// use futures::prelude::*;
+///
+/// async fn echo(mut socket: UnixSeqpacket) -> std::io::Result<()> {
+///     let mut buf = [0; 1024];
+///     loop {
+///         let n = await!(socket.recv(&mut buf))?;
+///         if n == 0 {
+///             return Ok(());
+///         }
+///         await!(socket.send(&buf[..n]))?;
+///     }
+/// }
+///
+/// async fn listen() -> Result<(), Box<dyn std::error::Error + 'static>> {
+///     let listener = UnixSeqpacketListener::bind("/tmp/sock")?;
+///     let mut incoming = listener.incoming();
+///
+///     // accept connections and process them serially
+///     while let Some(socket) = await!(incoming.next()) {
+///         await!(echo(socket?))?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct UnixSeqpacketListener {
+    io: PollEvented<Socket>,
+
+    /// The reactor the accepted sockets are registered with, if the listener
+    /// was created with one.
+    handle: Option<Handle>,
+}
+
+/// A connected Unix socket of type `SOCK_SEQPACKET`.
+///
+/// Like a stream socket, it is reliable and ordered. Like a datagram socket,
+/// it preserves message boundaries: each call to `send` sends one message,
+/// and each call to `recv` receives one whole message.
+pub struct UnixSeqpacket {
+    io: PollEvented<Socket>,
+}
+
+/// Future returned by `UnixSeqpacket::connect` which will resolve to a
+/// `UnixSeqpacket` when the socket is connected.
+#[derive(Debug)]
+pub struct SeqpacketConnectFuture {
+    inner: Option<io::Result<UnixSeqpacket>>,
+}
+
+/// The future returned by `UnixSeqpacket::send`.
+#[derive(Debug)]
+pub struct SendPacket<'a> {
+    socket: &'a mut UnixSeqpacket,
+    buf: &'a [u8],
+}
+
+/// The future returned by `UnixSeqpacket::recv`.
+#[derive(Debug)]
+pub struct RecvPacket<'a> {
+    socket: &'a mut UnixSeqpacket,
+    buf: &'a mut [u8],
+}
+
+/// Stream of the sockets accepted by a `UnixSeqpacketListener`.
+#[derive(Debug)]
+pub struct SeqpacketIncoming {
+    inner: UnixSeqpacketListener,
+    accept: Accept,
+}
+
+/// Owns the file descriptor of a socket, registering it with the reactor.
+struct Socket {
+    fd: OwnedFd,
+}
+
+impl UnixSeqpacketListener {
+    /// Creates a new `UnixSeqpacketListener` bound to the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use romio::uds::UnixSeqpacketListener;
+    ///
+    /// # fn main () -> Result<(), Box<dyn std::error::Error + 'static>> {
+    /// let listener = UnixSeqpacketListener::bind("/tmp/sock")?;
+    /// # Ok(())}
+    /// ```
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixSeqpacketListener> {
+        let addr = SocketAddr::from_pathname(path)?;
+        let socket = Socket::listen(&addr)?;
+        Ok(UnixSeqpacketListener::new(socket))
+    }
+
+    /// Creates a new `UnixSeqpacketListener` bound to the specified path and
+    /// associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind`], except that the listener, and the sockets
+    /// it accepts, are registered with the given reactor instead of lazily
+    /// binding to the default one.
+    ///
+    /// [`bind`]: #method.bind
+    pub fn bind_with_handle(
+        path: impl AsRef<Path>,
+        handle: &Handle,
+    ) -> io::Result<UnixSeqpacketListener> {
+        let addr = SocketAddr::from_pathname(path)?;
+        let socket = Socket::listen(&addr)?;
+        UnixSeqpacketListener::new_with_handle(socket, handle)
+    }
+
+    /// Creates a new `UnixSeqpacketListener` bound to `name` in the abstract
+    /// namespace.
+    ///
+    /// `name` doesn't include the leading NUL byte.
+    pub fn bind_abstract(name: &[u8]) -> io::Result<UnixSeqpacketListener> {
+        let addr = SocketAddr::from_abstract_name(name)?;
+        let socket = Socket::listen(&addr)?;
+        Ok(UnixSeqpacketListener::new(socket))
+    }
+
+    /// Creates a new `UnixSeqpacketListener` bound to `name` in the abstract
+    /// namespace, associated with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`bind_abstract`], except that the listener is
+    /// registered with the given reactor, along with the sockets it accepts,
+    /// instead of lazily binding to the default one.
+    ///
+    /// [`bind_abstract`]: #method.bind_abstract
+    pub fn bind_abstract_with_handle(
+        name: &[u8],
+        handle: &Handle,
+    ) -> io::Result<UnixSeqpacketListener> {
+        let addr = SocketAddr::from_abstract_name(name)?;
+        let socket = Socket::listen(&addr)?;
+        UnixSeqpacketListener::new_with_handle(socket, handle)
+    }
+
+    fn new(socket: Socket) -> UnixSeqpacketListener {
+        let io = PollEvented::new(socket);
+        UnixSeqpacketListener { io, handle: None }
+    }
+
+    fn new_with_handle(socket: Socket, handle: &Handle) -> io::Result<UnixSeqpacketListener> {
+        let io = PollEvented::new_with_handle(socket, handle)?;
+        Ok(UnixSeqpacketListener {
+            io,
+            handle: Some(handle.clone()),
+        })
+    }
+
+    /// Returns the local socket address of this listener.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    /// Returns the value of the `SO_ERROR` option.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.get_ref().take_error()
+    }
+
+    /// Consumes this listener, returning a stream of the sockets this listener
+    /// accepts.
+    pub fn incoming(self) -> SeqpacketIncoming {
+        SeqpacketIncoming {
+            inner: self,
+            accept: Accept::default(),
+        }
+    }
+
+    fn poll_accept(&self, lw: &LocalWaker) -> Poll<io::Result<UnixSeqpacket>> {
+        ready!(self.io.poll_read_ready(lw)?);
+
+        match self.io.get_ref().accept() {
+            Ok(socket) => Poll::Ready(match self.handle {
+                Some(ref handle) => UnixSeqpacket::new_with_handle(socket, handle),
+                None => Ok(UnixSeqpacket::new(socket)),
+            }),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(lw)?;
+                Poll::Pending
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+impl fmt::Debug for UnixSeqpacketListener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnixSeqpacketListener")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for UnixSeqpacketListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.get_ref().as_raw_fd()
+    }
+}
+
+impl SeqpacketIncoming {
+    /// Sets how errors returned by `accept` are handled.
+    ///
+    /// By default, every error is yielded by the stream. See [`AcceptPolicy`]
+    /// for details.
+    ///
+    /// [`AcceptPolicy`]: ../server/struct.AcceptPolicy.html
+    pub fn accept_policy(mut self, policy: AcceptPolicy) -> SeqpacketIncoming {
+        self.accept.set_policy(policy);
+        self
+    }
+
+    /// Limits the number of live connections accepted by this stream.
+    ///
+    /// See [`ConnectionLimit`] for details.
+    ///
+    /// [`ConnectionLimit`]: ../server/struct.ConnectionLimit.html
+    pub fn limit(self, limit: &ConnectionLimit) -> LimitedIncoming<SeqpacketIncoming> {
+        LimitedIncoming::new(self, limit)
+    }
+
+    /// Ends this stream when the shutdown of `controller` begins.
+    ///
+    /// See [`ShutdownController`] for details.
+    ///
+    /// [`ShutdownController`]: ../server/struct.ShutdownController.html
+    pub fn until_shutdown(
+        self,
+        controller: &ShutdownController,
+    ) -> UntilShutdown<SeqpacketIncoming> {
+        UntilShutdown::new(self, controller)
+    }
+}
+
+impl Stream for SeqpacketIncoming {
+    type Item = io::Result<UnixSeqpacket>;
+
+    fn poll_next(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let inner = &mut this.inner;
+
+        let socket = ready!(this.accept.poll_accept(lw, |lw| inner.poll_accept(lw))?);
+        Poll::Ready(Some(Ok(socket)))
+    }
+}
+
+impl UnixSeqpacket {
+    /// Connects to the socket named by `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixSeqpacket;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let socket = await!(UnixSeqpacket::connect("/tmp/sock"))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn connect(path: impl AsRef<Path>) -> SeqpacketConnectFuture {
+        let res = SocketAddr::from_pathname(path).and_then(|addr| Socket::connect(&addr));
+        SeqpacketConnectFuture {
+            inner: Some(res.map(UnixSeqpacket::new)),
+        }
+    }
+
+    /// Connects to the socket named by `path`, associating the returned socket
+    /// with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`connect`], except that the socket is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`connect`]: #method.connect
+    pub fn connect_with_handle(path: impl AsRef<Path>, handle: &Handle) -> SeqpacketConnectFuture {
+        let res = SocketAddr::from_pathname(path)
+            .and_then(|addr| Socket::connect(&addr))
+            .and_then(|socket| UnixSeqpacket::new_with_handle(socket, handle));
+        SeqpacketConnectFuture { inner: Some(res) }
+    }
+
+    /// Connects to the socket named `name` in the abstract namespace.
+    ///
+    /// `name` doesn't include the leading NUL byte.
+    pub fn connect_abstract(name: &[u8]) -> SeqpacketConnectFuture {
+        let res = SocketAddr::from_abstract_name(name).and_then(|addr| Socket::connect(&addr));
+        SeqpacketConnectFuture {
+            inner: Some(res.map(UnixSeqpacket::new)),
+        }
+    }
+
+    /// Connects to the socket named `name` in the abstract namespace,
+    /// associating the returned socket with the reactor referenced by
+    /// `handle`.
+    ///
+    /// This is the same as [`connect_abstract`], except that the socket is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`connect_abstract`]: #method.connect_abstract
+    pub fn connect_abstract_with_handle(name: &[u8], handle: &Handle) -> SeqpacketConnectFuture {
+        let res = SocketAddr::from_abstract_name(name)
+            .and_then(|addr| Socket::connect(&addr))
+            .and_then(|socket| UnixSeqpacket::new_with_handle(socket, handle));
+        SeqpacketConnectFuture { inner: Some(res) }
+    }
+
+    /// Creates an unnamed pair of connected sockets.
+    pub fn pair() -> io::Result<(UnixSeqpacket, UnixSeqpacket)> {
+        let (a, b) = Socket::pair()?;
+        Ok((UnixSeqpacket::new(a), UnixSeqpacket::new(b)))
+    }
+
+    /// Creates an unnamed pair of connected sockets, associated with the
+    /// reactor referenced by `handle`.
+    ///
+    /// This is the same as [`pair`], except that the sockets are registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`pair`]: #method.pair
+    pub fn pair_with_handle(handle: &Handle) -> io::Result<(UnixSeqpacket, UnixSeqpacket)> {
+        let (a, b) = Socket::pair()?;
+        let a = UnixSeqpacket::new_with_handle(a, handle)?;
+        let b = UnixSeqpacket::new_with_handle(b, handle)?;
+
+        Ok((a, b))
+    }
+
+    fn new(socket: Socket) -> UnixSeqpacket {
+        let io = PollEvented::new(socket);
+        UnixSeqpacket { io }
+    }
+
+    fn new_with_handle(socket: Socket, handle: &Handle) -> io::Result<UnixSeqpacket> {
+        let io = PollEvented::new_with_handle(socket, handle)?;
+        Ok(UnixSeqpacket { io })
+    }
+
+    /// Test whether this socket is ready to be read or not.
+    pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.io.poll_read_ready(lw)
+    }
+
+    /// Test whether this socket is ready to be written to or not.
+    pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.io.poll_write_ready(lw)
+    }
+
+    /// Returns the socket address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        addr::local_addr(self.as_raw_fd())
+    }
+
+    /// Returns the socket address of the remote half of this connection.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    /// Returns effective credentials of the process which called `connect` or
+    /// `socketpair`.
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        ucred::get_peer_cred(self)
+    }
+
+    /// Sends `buf` as one message to the peer.
+    ///
+    /// The message is sent whole or not at all: a message which is larger
+    /// than the send buffer of the socket fails with `EMSGSIZE`. On success,
+    /// returns the number of bytes sent, which is `buf.len()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixSeqpacket;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut socket = await!(UnixSeqpacket::connect("/tmp/sock"))?;
+    /// await!(socket.send(b"hello"))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> SendPacket<'a> {
+        SendPacket { socket: self, buf }
+    }
+
+    /// Receives one message from the peer into `buf`.
+    ///
+    /// On success, returns the length of the message. Zero is returned both
+    /// for an empty message and once the peer has shut down the connection.
+    ///
+    /// If the message is larger than `buf`, the rest of the message is
+    /// discarded and an error of kind `InvalidData` is returned; `buf` then
+    /// holds the start of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixSeqpacket;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut socket = await!(UnixSeqpacket::connect("/tmp/sock"))?;
+    /// let mut buf = [0; 1024];
+    /// let n = await!(socket.recv(&mut buf))?;
+    /// println!("received {:?}", &buf[..n]);
+    /// # Ok(()) }
+    /// ```
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvPacket<'a> {
+        RecvPacket { socket: self, buf }
+    }
+
+    /// Sends `buf` as one message to the peer.
+    ///
+    /// See [`send`] for details.
+    ///
+    /// [`send`]: #method.send
+    pub fn poll_send(&self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_write_ready(lw)?);
+
+        match self.io.get_ref().send(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_write_ready(lw)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    /// Receives one message from the peer into `buf`.
+    ///
+    /// See [`recv`] for details.
+    ///
+    /// [`recv`]: #method.recv
+    pub fn poll_recv(&self, lw: &LocalWaker, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        ready!(self.io.poll_read_ready(lw)?);
+
+        match self.io.get_ref().recv(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.io.clear_read_ready(lw)?;
+                Poll::Pending
+            }
+            r => Poll::Ready(r),
+        }
+    }
+
+    /// Returns the value of the `SO_ERROR` option.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.io.get_ref().take_error()
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        let how = match how {
+            Shutdown::Read => libc::SHUT_RD,
+            Shutdown::Write => libc::SHUT_WR,
+            Shutdown::Both => libc::SHUT_RDWR,
+        };
+        cvt(unsafe { libc::shutdown(self.as_raw_fd(), how) })?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for UnixSeqpacket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnixSeqpacket")
+            .field("fd", &self.as_raw_fd())
+            .finish()
+    }
+}
+
+impl AsRawFd for UnixSeqpacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.get_ref().as_raw_fd()
+    }
+}
+
+impl Future for SeqpacketConnectFuture {
+    type Output = io::Result<UnixSeqpacket>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<io::Result<UnixSeqpacket>> {
+        if let Some(Ok(ref socket)) = self.inner {
+            ready!(socket.io.poll_write_ready(lw)?);
+
+            if let Some(e) = socket.take_error()? {
+                return Poll::Ready(Err(e));
+            }
+        }
+
+        match self.inner.take() {
+            Some(res) => Poll::Ready(res),
+            None => panic!("can't poll socket twice"),
+        }
+    }
+}
+
+impl<'a> Future for SendPacket<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let SendPacket { socket, buf } = &mut *self;
+        socket.poll_send(lw, buf)
+    }
+}
+
+impl<'a> Future for RecvPacket<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        let RecvPacket { socket, buf } = &mut *self;
+        socket.poll_recv(lw, buf)
+    }
+}
+
+// ===== impl Socket =====
+
+impl Socket {
+    fn new() -> io::Result<Socket> {
+        let fd = addr::socket(libc::SOCK_SEQPACKET)?;
+        Ok(Socket { fd })
+    }
+
+    fn listen(addr: &SocketAddr) -> io::Result<Socket> {
+        let socket = Socket::new()?;
+        addr::bind(&socket, addr)?;
+        cvt(unsafe { libc::listen(socket.as_raw_fd(), 128) })?;
+        Ok(socket)
+    }
+
+    fn connect(addr: &SocketAddr) -> io::Result<Socket> {
+        let socket = Socket::new()?;
+        addr::connect(&socket, addr)?;
+        Ok(socket)
+    }
+
+    fn pair() -> io::Result<(Socket, Socket)> {
+        let ty = libc::SOCK_SEQPACKET | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+        let mut fds = [0; 2];
+        cvt(unsafe { libc::socketpair(libc::AF_UNIX, ty, 0, fds.as_mut_ptr()) })?;
+        unsafe {
+            Ok((
+                Socket {
+                    fd: OwnedFd::from_raw_fd(fds[0]),
+                },
+                Socket {
+                    fd: OwnedFd::from_raw_fd(fds[1]),
+                },
+            ))
+        }
+    }
+
+    fn accept(&self) -> io::Result<Socket> {
+        let flags = libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+        let fd = cvt(unsafe {
+            libc::accept4(self.as_raw_fd(), ptr::null_mut(), ptr::null_mut(), flags)
+        })?;
+        Ok(Socket {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        let n = unsafe {
+            libc::send(
+                self.as_raw_fd(),
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+                libc::MSG_NOSIGNAL,
+            )
+        };
+        if n == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(n as usize)
+        }
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        unsafe {
+            let mut iov = libc::iovec {
+                iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+                iov_len: buf.len(),
+            };
+
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+
+            let n = libc::recvmsg(self.as_raw_fd(), &mut msg, 0);
+            if n == -1 {
+                return Err(io::Error::last_os_error());
+            }
+
+            if msg.msg_flags & libc::MSG_TRUNC != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "message received over a Unix socket was truncated",
+                ));
+            }
+
+            Ok(n as usize)
+        }
+    }
+
+    fn take_error(&self) -> io::Result<Option<io::Error>> {
+        let mut err: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+        cvt(unsafe {
+            libc::getsockopt(
+                self.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut err as *mut _ as *mut libc::c_void,
+                &mut len,
+            )
+        })?;
+
+        if err == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(io::Error::from_raw_os_error(err)))
+        }
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Evented for Socket {
+    fn register(
+        &self,
+        poll: &mio::Poll,
+        token: Token,
+        interest: mio::Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &mio::Poll,
+        token: Token,
+        interest: mio::Ready,
+        opts: PollOpt,
+    ) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+fn cvt(r: libc::c_int) -> io::Result<libc::c_int> {
+    if r == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(r)
+    }
+}
@@ -1,18 +1,27 @@
+use super::addr::{self, SocketAddr};
+use super::ancillary::{self, RecvWithFds, SendWithFds};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::ancillary::{RecvWithCred, SendWithCred};
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf};
 use super::ucred::{self, UCred};
+use super::OwnedFd;
 
-use crate::reactor::PollEvented;
+use crate::io::RawStream;
+use crate::reactor::{Handle, PollEvented, Ready};
 
 use futures::io::{AsyncRead, AsyncWrite};
 use futures::task::LocalWaker;
 use futures::{ready, Future, Poll};
 use iovec::IoVec;
-use mio::Ready;
 
 use std::fmt;
 use std::io;
 use std::net::Shutdown;
 use std::os::unix::io::{AsRawFd, RawFd};
-use std::os::unix::net::SocketAddr;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::io::{FromRawFd, IntoRawFd};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::os::unix::net;
 use std::path::Path;
 use std::pin::Pin;
 
@@ -67,6 +76,74 @@ impl UnixStream {
         ConnectFuture { inner }
     }
 
+    /// Connects to the socket named by `path`, associating the returned stream
+    /// with the reactor referenced by `handle`.
+    ///
+    /// This is the same as [`connect`], except that the stream is registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`connect`]: #method.connect
+    pub fn connect_with_handle(path: impl AsRef<Path>, handle: &Handle) -> ConnectFuture {
+        let res = mio_uds::UnixStream::connect(path)
+            .and_then(|stream| UnixStream::new_with_handle(stream, handle));
+
+        let inner = match res {
+            Ok(stream) => State::Waiting(stream),
+            Err(e) => State::Error(e),
+        };
+
+        ConnectFuture { inner }
+    }
+
+    /// Connects to the socket named `name` in the abstract namespace of
+    /// Linux.
+    ///
+    /// `name` doesn't include the leading NUL byte.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let stream = await!(UnixStream::connect_abstract(b"romio-example"));
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_abstract(name: &[u8]) -> ConnectFuture {
+        let res = connect_abstract(name).map(UnixStream::new);
+
+        let inner = match res {
+            Ok(stream) => State::Waiting(stream),
+            Err(e) => State::Error(e),
+        };
+
+        ConnectFuture { inner }
+    }
+
+    /// Connects to the socket named `name` in the abstract namespace of
+    /// Linux, associating the returned stream with the reactor referenced by
+    /// `handle`.
+    ///
+    /// This is the same as [`connect_abstract`], except that the stream is
+    /// registered with the given reactor instead of lazily binding to the
+    /// default one.
+    ///
+    /// [`connect_abstract`]: #method.connect_abstract
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_abstract_with_handle(name: &[u8], handle: &Handle) -> ConnectFuture {
+        let res =
+            connect_abstract(name).and_then(|stream| UnixStream::new_with_handle(stream, handle));
+
+        let inner = match res {
+            Ok(stream) => State::Waiting(stream),
+            Err(e) => State::Error(e),
+        };
+
+        ConnectFuture { inner }
+    }
+
     /// Creates an unnamed pair of connected sockets.
     ///
     /// This function will create a pair of interconnected Unix sockets for
@@ -91,16 +168,45 @@ impl UnixStream {
         Ok((a, b))
     }
 
+    /// Creates an unnamed pair of connected sockets, associated with the
+    /// reactor referenced by `handle`.
+    ///
+    /// This is the same as [`pair`], except that the sockets are registered
+    /// with the given reactor instead of lazily binding to the default one.
+    ///
+    /// [`pair`]: #method.pair
+    pub fn pair_with_handle(handle: &Handle) -> io::Result<(UnixStream, UnixStream)> {
+        let (a, b) = mio_uds::UnixStream::pair()?;
+        let a = UnixStream::new_with_handle(a, handle)?;
+        let b = UnixStream::new_with_handle(b, handle)?;
+
+        Ok((a, b))
+    }
+
     pub(crate) fn new(stream: mio_uds::UnixStream) -> UnixStream {
         let io = PollEvented::new(stream);
         UnixStream { io }
     }
 
+    pub(crate) fn new_with_handle(stream: mio_uds::UnixStream, handle: &Handle) -> io::Result<UnixStream> {
+        let io = PollEvented::new_with_handle(stream, handle)?;
+        Ok(UnixStream { io })
+    }
+
     /// Test whether this socket is ready to be read or not.
     pub fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_read_ready(lw)
     }
 
+    /// Test whether the peer has shut down its write side of the socket.
+    ///
+    /// This does not complete while data can be read, so a half-close can be
+    /// detected without consuming the data still buffered in the socket. It
+    /// must be polled from the task reading from the socket.
+    pub fn poll_read_closed(&self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.io.poll_read_closed(lw)
+    }
+
     /// Test whether this socket is ready to be written to or not.
     pub fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
         self.io.poll_write_ready(lw)
@@ -120,7 +226,7 @@ impl UnixStream {
     /// # Ok(()) }
     /// ```
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.io.get_ref().local_addr()
+        addr::local_addr(self.as_raw_fd())
     }
 
     /// Returns the socket address of the remote half of this connection.
@@ -137,7 +243,105 @@ impl UnixStream {
     /// # Ok(()) }
     /// ```
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
-        self.io.get_ref().peer_addr()
+        addr::peer_addr(self.as_raw_fd())
+    }
+
+    /// Sends data on the socket along with the file descriptors `fds`, which
+    /// the peer receives with [`recv_with_fds`].
+    ///
+    /// The file descriptors are attached to the data written, which must not
+    /// be empty. On success, returns the number of bytes written.
+    ///
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixStream;
+    /// use std::fs::File;
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = await!(UnixStream::connect("/tmp/sock"))?;
+    /// let file = File::open("/etc/hosts")?;
+    /// let fds = [file.as_raw_fd()];
+    /// await!(stream.send_with_fds(b"hosts", &fds))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn send_with_fds<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+        fds: &'a [RawFd],
+    ) -> SendWithFds<'a, UnixStream> {
+        SendWithFds::new(self, buf, fds)
+    }
+
+    /// Receives data from the socket along with the file descriptors sent
+    /// with it.
+    ///
+    /// On success, returns the number of bytes read, the file descriptors,
+    /// which are set to close on exec, and whether some file descriptors were
+    /// dropped. File descriptors sent to a stream which is read with
+    /// `AsyncRead` instead are closed.
+    ///
+    /// File descriptors which don't fit in the process, e.g. because it
+    /// reached its limit of open files, are dropped by the kernel: those which
+    /// arrived are returned along with the data, and the flag is set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixStream;
+    /// use std::fs::File;
+    /// use std::os::unix::io::{FromRawFd, IntoRawFd};
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut stream = await!(UnixStream::connect("/tmp/sock"))?;
+    /// let mut buf = [0; 1024];
+    /// let (n, fds, truncated) = await!(stream.recv_with_fds(&mut buf))?;
+    /// if truncated {
+    ///     eprintln!("some file descriptors were dropped");
+    /// }
+    ///
+    /// let files: Vec<File> = fds
+    ///     .into_iter()
+    ///     .map(|fd| unsafe { File::from_raw_fd(fd.into_raw_fd()) })
+    ///     .collect();
+    /// # Ok(()) }
+    /// ```
+    pub fn recv_with_fds<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvWithFds<'a, UnixStream> {
+        RecvWithFds::new(self, buf)
+    }
+
+    /// Sends data on the socket along with the file descriptors `fds`.
+    ///
+    /// See [`send_with_fds`] for details.
+    ///
+    /// [`send_with_fds`]: #method.send_with_fds
+    pub fn poll_send_with_fds(
+        &self,
+        lw: &LocalWaker,
+        buf: &[u8],
+        fds: &[RawFd],
+    ) -> Poll<io::Result<usize>> {
+        ancillary::poll_send(&self.io, lw, buf, fds, false)
+    }
+
+    /// Receives data from the socket along with the file descriptors sent
+    /// with it.
+    ///
+    /// See [`recv_with_fds`] for details.
+    ///
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    pub fn poll_recv_with_fds(
+        &self,
+        lw: &LocalWaker,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Vec<OwnedFd>, bool)>> {
+        let (n, received) = ready!(ancillary::poll_recv(&self.io, lw, buf))?;
+        Poll::Ready(Ok((n, received.fds, received.truncated)))
     }
 
     /// Returns effective credentials of the process which called `connect` or `socketpair`.
@@ -157,6 +361,99 @@ impl UnixStream {
         ucred::get_peer_cred(self)
     }
 
+    /// Sets the value of the `SO_PASSCRED` option on this socket.
+    ///
+    /// When it is set, the credentials of the sender are received with each
+    /// message by [`recv_with_cred`], even if the sender doesn't send them
+    /// explicitly.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        ucred::set_passcred(self, passcred)
+    }
+
+    /// Gets the value of the `SO_PASSCRED` option on this socket.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        ucred::get_passcred(self)
+    }
+
+    /// Sends data on the socket, along with the credentials of this
+    /// process (`SCM_CREDENTIALS`).
+    ///
+    /// The peer receives them with [`recv_with_cred`] if it has set
+    /// [`set_passcred`]. On success, returns the number of bytes written.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    /// [`set_passcred`]: #method.set_passcred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_with_cred<'a>(&'a mut self, buf: &'a [u8]) -> SendWithCred<'a, UnixStream> {
+        SendWithCred::new(self, buf)
+    }
+
+    /// Receives data from the socket along with the credentials of the
+    /// process which sent it (`SCM_CREDENTIALS`).
+    ///
+    /// The credentials are only received if [`set_passcred`] has been set
+    /// before the data was sent. They are checked by the kernel, so they can
+    /// be used to authorize the sender of each message.
+    ///
+    /// File descriptors sent along with the data are closed; use
+    /// [`recv_with_fds`] to receive them.
+    ///
+    /// [`set_passcred`]: #method.set_passcred
+    /// [`recv_with_fds`]: #method.recv_with_fds
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let mut sock = await!(UnixStream::connect("/tmp/sock"))?;
+    /// sock.set_passcred(true)?;
+    ///
+    /// let mut buf = [0; 1024];
+    /// let (n, cred) = await!(sock.recv_with_cred(&mut buf))?;
+    /// if let Some(cred) = cred {
+    ///     println!("{} bytes from pid {:?}, uid {}", n, cred.pid(), cred.uid);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_with_cred<'a>(&'a mut self, buf: &'a mut [u8]) -> RecvWithCred<'a, UnixStream> {
+        RecvWithCred::new(self, buf)
+    }
+
+    /// Sends data on the socket, along with the credentials of this
+    /// process.
+    ///
+    /// See [`send_with_cred`] for details.
+    ///
+    /// [`send_with_cred`]: #method.send_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_send_with_cred(&self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        ancillary::poll_send(&self.io, lw, buf, &[], true)
+    }
+
+    /// Receives data from the socket along with the credentials of the
+    /// process which sent it.
+    ///
+    /// See [`recv_with_cred`] for details.
+    ///
+    /// [`recv_with_cred`]: #method.recv_with_cred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn poll_recv_with_cred(
+        &self,
+        lw: &LocalWaker,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, Option<UCred>)>> {
+        let (n, received) = ready!(ancillary::poll_recv(&self.io, lw, buf))?;
+        Poll::Ready(Ok((n, received.cred)))
+    }
+
     /// Returns the value of the `SO_ERROR` option.
     ///
     /// # Examples
@@ -195,6 +492,29 @@ impl UnixStream {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         self.io.get_ref().shutdown(how)
     }
+
+    /// Splits this stream into a read half and a write half, which can be
+    /// moved to different tasks.
+    ///
+    /// Dropping the write half shuts the write side of the connection down.
+    /// The halves can be put back together with [`OwnedReadHalf::reunite`].
+    ///
+    /// [`OwnedReadHalf::reunite`]: struct.OwnedReadHalf.html#method.reunite
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// #![feature(async_await, await_macro, futures_api)]
+    /// use romio::uds::UnixStream;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let stream = await!(UnixStream::connect("/tmp/sock"))?;
+    /// let (reader, writer) = stream.into_split();
+    /// # Ok(()) }
+    /// ```
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::split(self)
+    }
 }
 
 impl AsyncRead for UnixStream {
@@ -290,6 +610,28 @@ impl AsRawFd for UnixStream {
     }
 }
 
+impl RawStream for UnixStream {
+    fn poll_read_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.io.poll_read_ready(lw)
+    }
+
+    fn clear_read_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+        self.io.clear_read_ready(lw)
+    }
+
+    fn poll_write_ready(&self, lw: &LocalWaker) -> Poll<io::Result<Ready>> {
+        self.io.poll_write_ready(lw)
+    }
+
+    fn clear_write_ready(&self, lw: &LocalWaker) -> io::Result<()> {
+        self.io.clear_write_ready(lw)
+    }
+
+    fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.io.get_ref().shutdown(how)
+    }
+}
+
 impl Future for ConnectFuture {
     type Output = io::Result<UnixStream>;
 
@@ -322,6 +664,16 @@ impl Future for ConnectFuture {
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn connect_abstract(name: &[u8]) -> io::Result<mio_uds::UnixStream> {
+    let addr = SocketAddr::from_abstract_name(name)?;
+    let fd = addr::socket(libc::SOCK_STREAM)?;
+    addr::connect(&fd, &addr)?;
+
+    let stream = unsafe { net::UnixStream::from_raw_fd(fd.into_raw_fd()) };
+    mio_uds::UnixStream::from_stream(stream)
+}
+
 fn is_wouldblock<T>(r: &io::Result<T>) -> bool {
     match *r {
         Ok(_) => false,
@@ -1,4 +1,4 @@
-use libc::{gid_t, uid_t};
+use libc::{gid_t, pid_t, uid_t};
 
 /// Credentials of a process
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -7,10 +7,29 @@ pub struct UCred {
     pub uid: uid_t,
     /// GID (group ID) of the process
     pub gid: gid_t,
+    pid: Option<pid_t>,
+}
+
+impl UCred {
+    /// Returns the PID (process ID) of the process.
+    ///
+    /// This is only known on Linux and Android, and `None` elsewhere.
+    pub fn pid(&self) -> Option<pid_t> {
+        self.pid
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn from_ucred(ucred: &libc::ucred) -> UCred {
+        UCred {
+            uid: ucred.uid,
+            gid: ucred.gid,
+            pid: Some(ucred.pid),
+        }
+    }
 }
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
-pub(crate) use self::impl_linux::get_peer_cred;
+pub(crate) use self::impl_linux::{get_passcred, get_peer_cred, set_passcred};
 
 #[cfg(any(
     target_os = "dragonfly",
@@ -24,14 +43,13 @@ pub(crate) use self::impl_macos::get_peer_cred;
 
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub(crate) mod impl_linux {
-    use crate::uds::UnixStream;
     use libc::{c_void, getsockopt, socklen_t, SOL_SOCKET, SO_PEERCRED};
     use std::os::unix::io::AsRawFd;
     use std::{io, mem};
 
     use libc::ucred;
 
-    pub(crate) fn get_peer_cred(sock: &UnixStream) -> io::Result<super::UCred> {
+    pub(crate) fn get_peer_cred(sock: &impl AsRawFd) -> io::Result<super::UCred> {
         unsafe {
             let raw_fd = sock.as_raw_fd();
 
@@ -57,15 +75,51 @@ pub(crate) mod impl_linux {
                 &mut ucred_size,
             );
             if ret == 0 && ucred_size as usize == mem::size_of::<ucred>() {
-                Ok(super::UCred {
-                    uid: ucred.uid,
-                    gid: ucred.gid,
-                })
+                Ok(super::UCred::from_ucred(&ucred))
             } else {
                 Err(io::Error::last_os_error())
             }
         }
     }
+
+    pub(crate) fn set_passcred(sock: &impl AsRawFd, passcred: bool) -> io::Result<()> {
+        let val = passcred as libc::c_int;
+        let ret = unsafe {
+            libc::setsockopt(
+                sock.as_raw_fd(),
+                SOL_SOCKET,
+                libc::SO_PASSCRED,
+                &val as *const libc::c_int as *const c_void,
+                mem::size_of::<libc::c_int>() as socklen_t,
+            )
+        };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub(crate) fn get_passcred(sock: &impl AsRawFd) -> io::Result<bool> {
+        let mut val: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as socklen_t;
+        let ret = unsafe {
+            getsockopt(
+                sock.as_raw_fd(),
+                SOL_SOCKET,
+                libc::SO_PASSCRED,
+                &mut val as *mut libc::c_int as *mut c_void,
+                &mut len,
+            )
+        };
+
+        if ret == 0 {
+            Ok(val != 0)
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
 }
 
 #[cfg(any(
@@ -77,16 +131,19 @@ pub(crate) mod impl_linux {
     target_os = "openbsd"
 ))]
 pub(crate) mod impl_macos {
-    use crate::uds::UnixStream;
     use libc::getpeereid;
+    use std::io;
     use std::os::unix::io::AsRawFd;
-    use std::{io, mem};
 
-    pub(crate) fn get_peer_cred(sock: &UnixStream) -> io::Result<super::UCred> {
+    pub(crate) fn get_peer_cred(sock: &impl AsRawFd) -> io::Result<super::UCred> {
         unsafe {
             let raw_fd = sock.as_raw_fd();
 
-            let mut cred: super::UCred = mem::uninitialized();
+            let mut cred = super::UCred {
+                uid: 0,
+                gid: 0,
+                pid: None,
+            };
 
             let ret = getpeereid(raw_fd, &mut cred.uid, &mut cred.gid);
 
@@ -103,7 +160,7 @@ pub(crate) mod impl_macos {
 #[cfg(not(target_os = "dragonfly"))]
 #[cfg(test)]
 mod test {
-    use crate::uds::UnixStream;
+    use crate::uds::{UnixDatagram, UnixStream};
     use libc::getegid;
     use libc::geteuid;
 
@@ -127,5 +184,18 @@ mod test {
 
         assert_eq!(cred_a.uid, uid);
         assert_eq!(cred_a.gid, gid);
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert_eq!(cred_a.pid(), Some(std::process::id() as libc::pid_t));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_datagram_pair() {
+        let (a, b) = UnixDatagram::pair().unwrap();
+        let cred_a = a.peer_cred().unwrap();
+        assert_eq!(cred_a, b.peer_cred().unwrap());
+        assert_eq!(cred_a.uid, unsafe { geteuid() });
+        assert_eq!(cred_a.pid(), Some(std::process::id() as libc::pid_t));
     }
 }
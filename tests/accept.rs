@@ -0,0 +1,68 @@
+#![cfg(unix)]
+#![feature(async_await, await_macro, futures_api)]
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::executor;
+use futures::StreamExt;
+
+use romio::server::AcceptPolicy;
+use romio::{timer, TcpListener};
+
+fn set_fd_limit(limit: libc::rlimit) {
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) }, 0);
+}
+
+/// Returns the lowest file descriptor which isn't open.
+fn lowest_free_fd() -> libc::rlim_t {
+    let fd = unsafe { libc::dup(0) };
+    assert!(fd >= 0);
+    unsafe { libc::close(fd) };
+    fd as libc::rlim_t
+}
+
+// This test changes the file descriptor limit of the process, so it is the
+// only test of this file.
+#[test]
+fn backs_off_on_fd_exhaustion() {
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut original = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut original) }, 0);
+
+    let exhausted = Arc::new(AtomicUsize::new(0));
+    let mut policy = AcceptPolicy::new();
+    {
+        let exhausted = exhausted.clone();
+        policy
+            .set_backoff(Duration::from_millis(50))
+            .on_fd_exhaustion(move |_| {
+                exhausted.fetch_add(1, Ordering::SeqCst);
+                set_fd_limit(original);
+            });
+    }
+    let mut incoming = listener.incoming().accept_policy(policy);
+
+    executor::block_on(async {
+        // initialize the reactor and its timer before running out of fds
+        await!(timer::sleep(Duration::from_millis(1)));
+        let client = TcpStream::connect(&addr).unwrap();
+
+        set_fd_limit(libc::rlimit {
+            rlim_cur: lowest_free_fd(),
+            rlim_max: original.rlim_max,
+        });
+
+        let stream = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), client.local_addr().unwrap());
+        assert_eq!(exhausted.load(Ordering::SeqCst), 1);
+    });
+}
+
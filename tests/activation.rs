@@ -0,0 +1,130 @@
+#![cfg(unix)]
+#![feature(async_await, await_macro, futures_api)]
+use std::env;
+use std::fs;
+use std::io;
+use std::net;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net as unix;
+use std::os::unix::process::CommandExt;
+use std::process::{self, Command};
+
+use futures::executor;
+use futures::StreamExt;
+
+use romio::activation::{self, SocketKind};
+
+// The passed file descriptors start at 3, which the test harness already
+// uses, so the test runs `adopts_listen_fds_child` in a child process with the
+// sockets moved into place.
+#[test]
+fn adopts_listen_fds() {
+    drop(env_logger::try_init());
+    let tmp_dir = env::temp_dir().join(format!("romio-activation-{}", process::id()));
+    fs::create_dir_all(&tmp_dir).unwrap();
+
+    let tcp = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let udp = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let uds = unix::UnixListener::bind(tmp_dir.join("stream")).unwrap();
+    let datagram = unix::UnixDatagram::bind(tmp_dir.join("datagram")).unwrap();
+
+    let fds = [
+        tcp.as_raw_fd(),
+        udp.as_raw_fd(),
+        uds.as_raw_fd(),
+        datagram.as_raw_fd(),
+    ];
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(&["--exact", "adopts_listen_fds_child", "--ignored"])
+        .env("ROMIO_TEST_LISTEN_FDS", fds.len().to_string())
+        .before_exec(move || {
+            // move the sockets above the passed range first, so that none of
+            // them is overwritten
+            let mut high = [0; 4];
+            for (i, &fd) in fds.iter().enumerate() {
+                high[i] = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 100) };
+                if high[i] == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            for (i, &fd) in high.iter().enumerate() {
+                if unsafe { libc::dup2(fd, 3 + i as RawFd) } == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        })
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&tmp_dir).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+#[ignore]
+fn adopts_listen_fds_child() {
+    drop(env_logger::try_init());
+    let count = env::var("ROMIO_TEST_LISTEN_FDS").unwrap();
+
+    // sockets passed to another process are ignored
+    env::set_var("LISTEN_PID", "1");
+    env::set_var("LISTEN_FDS", &count);
+    assert!(activation::listen_fds().unwrap().is_empty());
+
+    // counts overflowing the file descriptors are rejected
+    env::set_var("LISTEN_PID", process::id().to_string());
+    env::set_var("LISTEN_FDS", "2147483645");
+    let err = activation::listen_fds().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    // so are closed file descriptors, without closing the open ones
+    env::set_var("LISTEN_PID", process::id().to_string());
+    env::set_var("LISTEN_FDS", "1000");
+    assert!(activation::listen_fds().is_err());
+    assert_ne!(unsafe { libc::fcntl(3, libc::F_GETFD) }, -1);
+
+    env::set_var("LISTEN_PID", process::id().to_string());
+    env::set_var("LISTEN_FDS", &count);
+    env::set_var("LISTEN_FDNAMES", "http:dns:control:log");
+    let mut fds = activation::listen_fds().unwrap();
+    assert!(env::var_os("LISTEN_FDS").is_none());
+    assert!(activation::listen_fds().unwrap().is_empty());
+
+    let names: Vec<_> = fds.iter().map(|fd| fd.name().unwrap()).collect();
+    assert_eq!(names, ["http", "dns", "control", "log"]);
+
+    let kinds: Vec<_> = fds.iter().map(|fd| fd.kind().unwrap()).collect();
+    assert_eq!(
+        kinds,
+        [
+            SocketKind::TcpListener,
+            SocketKind::UdpSocket,
+            SocketKind::UnixListener,
+            SocketKind::UnixDatagram,
+        ]
+    );
+
+    let datagram = fds.pop().unwrap().into_unix_datagram().unwrap();
+    assert_eq!(datagram.as_raw_fd(), 6);
+    let uds = fds.pop().unwrap().into_unix_listener().unwrap();
+    assert_eq!(uds.as_raw_fd(), 5);
+
+    let err = fds.pop().unwrap().into_tcp_listener().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let listener = fds.pop().unwrap().into_tcp_listener().unwrap();
+    assert_eq!(listener.as_raw_fd(), 3);
+
+    let client = net::TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+    executor::block_on(async {
+        let mut incoming = listener.incoming();
+        let stream = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), client.local_addr().unwrap());
+    });
+}
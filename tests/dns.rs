@@ -0,0 +1,135 @@
+#![feature(async_await, await_macro, futures_api)]
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+use futures::executor;
+use futures::StreamExt;
+
+use romio::dns::{self, Config, DnsResolver};
+use romio::{TcpListener, TcpStream};
+
+/// Starts a name server answering A queries for `records`, with NXDOMAIN for
+/// other names, and returns its address.
+fn stub_server(records: &'static [(&'static str, [u8; 4])]) -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let mut buf = [0; 512];
+        loop {
+            let (n, from) = socket.recv_from(&mut buf).unwrap();
+            let query = &buf[..n];
+
+            // Decode the question, which follows the 12-byte header.
+            let mut labels = Vec::new();
+            let mut pos = 12;
+            while query[pos] != 0 {
+                let len = query[pos] as usize;
+                labels.push(String::from_utf8_lossy(&query[pos + 1..pos + 1 + len]).into_owned());
+                pos += 1 + len;
+            }
+            let question_end = pos + 5;
+            let qtype = u16::from(query[pos + 1]) << 8 | u16::from(query[pos + 2]);
+
+            let name = labels.join(".");
+            let found = records.iter().find(|&&(record, _)| record == name);
+
+            let mut response = query[..question_end].to_vec();
+            response[2] = 0x81; // QR, RD
+            response[3] = if found.is_some() { 0x80 } else { 0x83 }; // RA, RCODE
+
+            if let (Some(&(_, ip)), 1) = (found, qtype) {
+                response[7] = 1; // ANCOUNT
+                response.extend_from_slice(b"\xc0\x0c\x00\x01\x00\x01\x00\x00\x00\x3c\x00\x04");
+                response.extend_from_slice(&ip);
+            }
+
+            socket.send_to(&response, from).unwrap();
+        }
+    });
+
+    addr
+}
+
+#[test]
+fn resolve_addresses() {
+    drop(env_logger::try_init());
+
+    executor::block_on(async {
+        let addrs = await!(dns::resolve("127.0.0.1:80")).unwrap();
+        assert_eq!(addrs, vec!["127.0.0.1:80".parse().unwrap()]);
+
+        let addrs = await!(dns::resolve(("localhost", 80))).unwrap();
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback() && addr.port() == 80));
+        assert!(!addrs.is_empty());
+
+        let err = await!(dns::resolve("localhost")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+fn connect_to_host_name() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let port = server.local_addr().unwrap().port();
+
+    executor::block_on(async {
+        let mut incoming = server.incoming();
+        let stream = await!(TcpStream::connect(("localhost", port))).unwrap();
+        let accepted = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(stream.local_addr().unwrap(), accepted.peer_addr().unwrap());
+    });
+}
+
+#[test]
+fn dns_resolver_queries_name_server() {
+    drop(env_logger::try_init());
+    static RECORDS: &[(&str, [u8; 4])] = &[
+        ("db.internal", [10, 0, 0, 1]),
+        ("cache.corp.internal", [10, 0, 0, 2]),
+    ];
+
+    let mut config = Config::new();
+    config
+        .add_nameserver(stub_server(RECORDS))
+        .add_search_domain("corp.internal")
+        .add_host("gateway.internal", "10.0.0.254".parse().unwrap());
+    let resolver = DnsResolver::new(config);
+
+    executor::block_on(async {
+        let addrs = await!(resolver.lookup("db.internal", 5432)).unwrap();
+        assert_eq!(addrs, vec!["10.0.0.1:5432".parse().unwrap()]);
+
+        // qualified with the search list
+        let addrs = await!(resolver.lookup("cache", 6379)).unwrap();
+        assert_eq!(addrs, vec!["10.0.0.2:6379".parse().unwrap()]);
+
+        // from the hosts entries, without querying
+        let addrs = await!(resolver.lookup("Gateway.Internal.", 80)).unwrap();
+        assert_eq!(addrs, vec!["10.0.0.254:80".parse().unwrap()]);
+
+        let err = await!(resolver.lookup("missing.internal", 80)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    });
+}
+
+#[test]
+fn dns_resolver_timeout() {
+    drop(env_logger::try_init());
+    let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let mut config = Config::new();
+    config
+        .add_nameserver(silent.local_addr().unwrap())
+        .set_timeout(Duration::from_millis(100))
+        .set_attempts(1);
+    let resolver = DnsResolver::new(config);
+
+    executor::block_on(async {
+        let err = await!(resolver.lookup("db.internal", 5432)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    });
+}
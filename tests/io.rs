@@ -0,0 +1,166 @@
+#![feature(async_await, await_macro, futures_api)]
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::thread::{self, JoinHandle};
+
+use futures::executor;
+use futures::io::{AllowStdIo, AsyncRead, AsyncReadExt, AsyncWrite};
+use futures::task::LocalWaker;
+use futures::{Poll, StreamExt};
+use tempdir::TempDir;
+
+use romio::io::{copy_bidirectional, copy_file};
+use romio::server::ConnectionLimit;
+use romio::TcpListener;
+
+const THE_WINTERS_TALE: &[u8] = b"
+                    Each your doing,
+    So singular in each particular,
+    Crowns what you are doing in the present deed,
+    That all your acts are queens.
+";
+
+/// Starts a client connecting to the returned listener, and a backend which
+/// the proxy connects to at the returned address.
+///
+/// The backend reverses all it reads, once the client has shut its side down.
+/// The client checks that it reads back what it sent, reversed.
+fn proxy_peers() -> (TcpListener, SocketAddr, Vec<JoinHandle<()>>) {
+    let proxy = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    let backend = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let backend_addr = backend.local_addr().unwrap();
+
+    let backend = thread::spawn(move || {
+        let (mut stream, _) = backend.accept().unwrap();
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).unwrap();
+        buf.reverse();
+        stream.write_all(&buf).unwrap();
+    });
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(&proxy_addr).unwrap();
+        stream.write_all(THE_WINTERS_TALE).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).unwrap();
+        buf.reverse();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    (proxy, backend_addr, vec![backend, client])
+}
+
+#[test]
+fn copy_bidirectional_proxies_streams() {
+    drop(env_logger::try_init());
+    let (proxy, backend_addr, peers) = proxy_peers();
+
+    executor::block_on(async {
+        let mut incoming = proxy.incoming();
+        let mut client = await!(incoming.next()).unwrap().unwrap();
+        let mut server = await!(romio::TcpStream::connect(&backend_addr)).unwrap();
+
+        let amounts = await!(copy_bidirectional(&mut client, &mut server)).unwrap();
+        let len = THE_WINTERS_TALE.len() as u64;
+        assert_eq!(amounts, (len, len));
+    });
+
+    for peer in peers {
+        peer.join().unwrap();
+    }
+}
+
+#[test]
+fn copy_bidirectional_limited_streams() {
+    drop(env_logger::try_init());
+    let (proxy, backend_addr, peers) = proxy_peers();
+    let limit = ConnectionLimit::new(1);
+
+    executor::block_on(async {
+        let mut incoming = proxy.incoming().limit(&limit);
+        let mut client = await!(incoming.next()).unwrap().unwrap();
+        let mut server = await!(romio::TcpStream::connect(&backend_addr)).unwrap();
+
+        let amounts = await!(copy_bidirectional(&mut client, &mut server)).unwrap();
+        let len = THE_WINTERS_TALE.len() as u64;
+        assert_eq!(amounts, (len, len));
+    });
+
+    for peer in peers {
+        peer.join().unwrap();
+    }
+}
+
+/// A stream romio doesn't know about, which is copied through a buffer.
+struct Wrapped(romio::TcpStream);
+
+impl AsyncRead for Wrapped {
+    fn poll_read(&mut self, lw: &LocalWaker, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.0.poll_read(lw, buf)
+    }
+}
+
+impl AsyncWrite for Wrapped {
+    fn poll_write(&mut self, lw: &LocalWaker, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.0.poll_write(lw, buf)
+    }
+
+    fn poll_flush(&mut self, lw: &LocalWaker) -> Poll<io::Result<()>> {
+        self.0.poll_flush(lw)
+    }
+
+    fn poll_close(&mut self, _: &LocalWaker) -> Poll<io::Result<()>> {
+        Poll::Ready(self.0.shutdown(Shutdown::Write))
+    }
+}
+
+#[test]
+fn copy_bidirectional_buffered() {
+    drop(env_logger::try_init());
+    let (proxy, backend_addr, peers) = proxy_peers();
+
+    executor::block_on(async {
+        let mut incoming = proxy.incoming();
+        let mut client = Wrapped(await!(incoming.next()).unwrap().unwrap());
+        let mut server = await!(romio::TcpStream::connect(&backend_addr)).unwrap();
+
+        let amounts = await!(copy_bidirectional(&mut client, &mut server)).unwrap();
+        let len = THE_WINTERS_TALE.len() as u64;
+        assert_eq!(amounts, (len, len));
+    });
+
+    for peer in peers {
+        peer.join().unwrap();
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn copy_file_to_stream() {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("copy_file_to_stream").unwrap();
+    let mut file = File::create(tmp_dir.path().join("tale")).unwrap();
+    file.write_all(THE_WINTERS_TALE).unwrap();
+    let mut file = File::open(tmp_dir.path().join("tale")).unwrap();
+
+    executor::block_on(async {
+        let (mut a, mut b) = romio::uds::UnixStream::pair().unwrap();
+        let n = await!(copy_file(&mut file, &mut a)).unwrap();
+        assert_eq!(n, THE_WINTERS_TALE.len() as u64);
+        drop(a);
+
+        let mut buf = vec![];
+        await!(b.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+
+        // copied through a buffer to other writers
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut writer = AllowStdIo::new(vec![]);
+        await!(copy_file(&mut file, &mut writer)).unwrap();
+        assert_eq!(writer.into_inner(), THE_WINTERS_TALE);
+    });
+}
@@ -0,0 +1,103 @@
+#![feature(async_await, await_macro, futures_api, pin)]
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+
+use futures::executor;
+use futures::future::{self, poll_fn};
+use futures::{ready, Poll, Stream};
+
+use romio::reactor::{self, Reactor};
+use romio::TcpListener;
+
+#[test]
+fn lazy_binding_uses_default_reactor() {
+    drop(env_logger::try_init());
+    let reactor = Reactor::new().unwrap();
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let mut incoming = listener.incoming();
+
+    assert!(reactor.is_idle());
+
+    // the listener registers with a reactor the first time it is polled
+    reactor::with_default(&reactor.handle(), || {
+        executor::block_on(future::poll_fn(|lw| {
+            assert!(Pin::new(&mut incoming).poll_next(lw).is_pending());
+            Poll::Ready(())
+        }))
+    });
+
+    assert!(!reactor.is_idle());
+}
+
+#[test]
+#[should_panic(expected = "default reactor already set")]
+fn nested_default_panics() {
+    let reactor = Reactor::new().unwrap();
+    let handle = reactor.handle();
+
+    reactor::with_default(&handle, || {
+        reactor::with_default(&handle, || {});
+    });
+}
+
+#[test]
+fn default_is_reset_on_panic() {
+    let reactor = Reactor::new().unwrap();
+    let handle = reactor.handle();
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        reactor::with_default(&handle, || panic!("boom"));
+    }));
+    assert!(res.is_err());
+
+    // a new default can be set since the previous one was reset
+    reactor::with_default(&handle, || {});
+}
+
+#[test]
+#[cfg(unix)]
+fn async_fd_read() {
+    use romio::reactor::AsyncFd;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+
+    drop(env_logger::try_init());
+    let (a, mut b) = UnixStream::pair().unwrap();
+    a.set_nonblocking(true).unwrap();
+
+    let fd = AsyncFd::new(a);
+
+    let writer = thread::spawn(move || {
+        b.write_all(b"hello").unwrap();
+        b
+    });
+
+    let mut buf = [0; 5];
+    let mut read = 0;
+    while read < buf.len() {
+        read += executor::block_on(poll_fn(|lw| {
+            let guard = ready!(fd.poll_read_ready(lw))?;
+            assert!(guard.ready().is_readable());
+            guard.try_io(|mut stream| stream.read(&mut buf[read..]))
+        }))
+        .unwrap();
+    }
+
+    assert_eq!(&buf, b"hello");
+    let _b = writer.join().unwrap();
+
+    // Nothing is left to read, so the readiness is cleared and the task is
+    // parked until the next event.
+    let pending = executor::block_on(future::lazy(|lw| {
+        let guard = match fd.poll_read_ready(lw) {
+            Poll::Ready(guard) => guard.unwrap(),
+            Poll::Pending => return true,
+        };
+        guard.try_io(|mut stream| stream.read(&mut buf)).is_pending()
+    }));
+    assert!(pending);
+
+    let stream = fd.into_inner().unwrap();
+    drop(stream);
+}
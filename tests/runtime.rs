@@ -0,0 +1,109 @@
+#![feature(async_await, await_macro, futures_api)]
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use futures::task::{LocalSpawnExt, SpawnExt};
+use futures::StreamExt;
+
+use romio::runtime::current_thread::Runtime;
+use romio::runtime::multi_thread;
+use romio::{TcpListener, TcpStream};
+
+const THE_WINTERS_TALE: &[u8] = b"
+                    Each your doing,
+    So singular in each particular,
+    Crowns what you are doing in the present deed,
+    That all your acts are queens.
+";
+
+#[test]
+fn current_thread_block_on() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let mut runtime = Runtime::new().unwrap();
+    let mut spawner = runtime.spawner();
+
+    let buf = runtime.block_on(async move {
+        spawner
+            .spawn_local(async move {
+                let mut client = await!(TcpStream::connect(&addr)).unwrap();
+                await!(client.write_all(THE_WINTERS_TALE)).unwrap();
+            })
+            .unwrap();
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let mut incoming = server.incoming();
+        let mut stream = await!(incoming.next()).unwrap().unwrap();
+        await!(stream.read_exact(&mut buf)).unwrap();
+        buf
+    });
+
+    assert_eq!(buf, THE_WINTERS_TALE);
+}
+
+#[test]
+fn current_thread_run_spawned() {
+    drop(env_logger::try_init());
+    let done = Rc::new(Cell::new(0));
+
+    let mut runtime = Runtime::new().unwrap();
+    for _ in 0..3 {
+        let done = done.clone();
+        runtime.spawn(async move {
+            done.set(done.get() + 1);
+        });
+    }
+    runtime.run();
+
+    assert_eq!(done.get(), 3);
+}
+
+#[test]
+fn multi_thread_block_on() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let runtime = multi_thread::Builder::new().workers(2).build().unwrap();
+    let mut spawner = runtime.spawner();
+
+    let buf = runtime.block_on(async move {
+        spawner
+            .spawn(async move {
+                let mut client = await!(TcpStream::connect(&addr)).unwrap();
+                await!(client.write_all(THE_WINTERS_TALE)).unwrap();
+            })
+            .unwrap();
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let mut incoming = server.incoming();
+        let mut stream = await!(incoming.next()).unwrap().unwrap();
+        await!(stream.read_exact(&mut buf)).unwrap();
+        buf
+    });
+
+    assert_eq!(buf, THE_WINTERS_TALE);
+    runtime.shutdown_now();
+}
+
+#[test]
+fn multi_thread_shutdown_on_idle() {
+    drop(env_logger::try_init());
+    let done = Arc::new(AtomicUsize::new(0));
+
+    let runtime = multi_thread::Builder::new().workers(3).build().unwrap();
+    for _ in 0..10 {
+        let done = done.clone();
+        runtime.spawn(async move {
+            done.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    runtime.shutdown_on_idle();
+
+    assert_eq!(done.load(Ordering::SeqCst), 10);
+}
@@ -0,0 +1,192 @@
+#![feature(async_await, await_macro, futures_api, pin)]
+use std::io::Read;
+use std::net::TcpStream;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use futures::executor;
+use futures::future;
+use futures::task::{self, Wake};
+use futures::{Future, Poll, Stream, StreamExt};
+use net2::TcpBuilder;
+
+use romio::reactor::Reactor;
+use romio::server::{ConnectionLimit, ShutdownController};
+use romio::timer::{self, Timeout};
+use romio::TcpListener;
+
+#[test]
+fn limit_pauses_accept() {
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let limit = ConnectionLimit::new(1);
+
+    let _first = TcpStream::connect(&addr).unwrap();
+    let second = TcpStream::connect(&addr).unwrap();
+
+    executor::block_on(async {
+        let mut incoming = listener.incoming().limit(&limit);
+
+        let first = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(limit.active(), 1);
+
+        let paused = Timeout::new(incoming.next(), Duration::from_millis(100));
+        assert!(await!(paused).is_err());
+
+        drop(first);
+        assert_eq!(limit.active(), 0);
+
+        let accepted = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(accepted.peer_addr().unwrap(), second.local_addr().unwrap());
+        assert_eq!(limit.active(), 1);
+    });
+}
+
+#[test]
+fn limit_per_ip() {
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut limit = ConnectionLimit::new(10);
+    limit.set_per_ip_limit(1);
+
+    let _first = TcpStream::connect(&addr).unwrap();
+    let mut second = TcpStream::connect(&addr).unwrap();
+
+    executor::block_on(async {
+        let mut incoming = listener.incoming().limit(&limit);
+        let _first = await!(incoming.next()).unwrap().unwrap();
+
+        // the second connection is closed as soon as it is accepted
+        let next = Timeout::new(incoming.next(), Duration::from_millis(100));
+        assert!(await!(next).is_err());
+        assert_eq!(limit.active(), 1);
+    });
+
+    let mut buf = [0; 1];
+    match second.read(&mut buf) {
+        Ok(n) => assert_eq!(n, 0),
+        Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset),
+    }
+}
+
+#[test]
+fn limit_per_ip_yields_during_flood() {
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut limit = ConnectionLimit::new(100);
+    limit.set_per_ip_limit(1);
+
+    executor::block_on(async {
+        let mut incoming = listener.incoming().limit(&limit);
+        let _first = TcpStream::connect(&addr).unwrap();
+        let _first = await!(incoming.next()).unwrap().unwrap();
+
+        // more connections over the limit than are closed in one poll, then
+        // one from another address
+        let flood: Vec<_> = (0..40).map(|_| TcpStream::connect(&addr).unwrap()).collect();
+        let other = TcpBuilder::new_v4()
+            .unwrap()
+            .bind("127.0.0.2:0")
+            .unwrap()
+            .connect(&addr)
+            .unwrap();
+
+        let polled = await!(future::poll_fn(|lw| Poll::Ready(
+            Pin::new(&mut incoming).poll_next(lw).is_pending()
+        )));
+        assert!(polled);
+
+        let accepted = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(accepted.peer_addr().unwrap(), other.local_addr().unwrap());
+        assert_eq!(limit.active(), 2);
+        drop(flood);
+    });
+}
+
+#[test]
+fn shutdown_drains_connections() {
+    drop(env_logger::try_init());
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let controller = ShutdownController::new();
+
+    let _client = TcpStream::connect(&addr).unwrap();
+
+    executor::block_on(async {
+        let mut incoming = listener.incoming().until_shutdown(&controller);
+        let stream = await!(incoming.next()).unwrap().unwrap();
+        let signal = controller.signal();
+
+        let task = thread::spawn(move || {
+            executor::block_on(async {
+                let mut signal = signal;
+                await!(&mut signal);
+                thread::sleep(Duration::from_millis(50));
+                drop(stream);
+            })
+        });
+
+        let drain = controller.shutdown(Duration::from_secs(5));
+        assert!(controller.is_shutting_down());
+        assert!(await!(incoming.next()).is_none());
+
+        await!(drain).unwrap();
+        assert_eq!(controller.active(), 0);
+        task.join().unwrap();
+    });
+}
+
+struct NoopWake;
+
+impl Wake for NoopWake {
+    fn wake(_: &Arc<Self>) {}
+}
+
+#[test]
+fn shutdown_drop_releases_drain_wakers() {
+    drop(env_logger::try_init());
+    let controller = ShutdownController::new();
+    let _signal = controller.signal();
+
+    // each drain, polled by a different task, is dropped before the
+    // connections are drained
+    for _ in 0..4 {
+        let wake = Arc::new(NoopWake);
+        let lw = task::local_waker_from_nonlocal(wake.clone());
+
+        let mut drain = controller.shutdown(Duration::from_secs(5));
+        assert!(Pin::new(&mut drain).poll(&lw).is_pending());
+        drop(drain);
+
+        drop(lw);
+        assert_eq!(Arc::strong_count(&wake), 1);
+    }
+}
+
+#[test]
+fn shutdown_deadline_and_reactor() {
+    drop(env_logger::try_init());
+    let reactor = Reactor::new().unwrap();
+    let handle = reactor.handle();
+    let background = reactor.background().unwrap();
+
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let listener = TcpListener::bind_with_handle(&addr, &handle).unwrap();
+    let controller = ShutdownController::new();
+    let _signal = controller.signal();
+
+    executor::block_on(async {
+        let start = timer::now();
+        let drain = controller.shutdown(Duration::from_millis(50));
+        assert!(await!(drain.shutdown_reactor(background)).is_err());
+        assert!(timer::now() - start >= Duration::from_millis(50));
+        assert_eq!(controller.active(), 1);
+    });
+
+    drop(listener);
+}
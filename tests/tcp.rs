@@ -1,14 +1,18 @@
-#![feature(async_await, await_macro, pin)]
+#![feature(async_await, await_macro, futures_api, pin)]
 use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::net::{Shutdown, TcpStream};
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
-use futures::{StreamExt};
+use futures::{Future, Poll, StreamExt};
 use futures::executor;
-use futures::future::FutureObj;
+use futures::future::{poll_fn, FutureObj};
 use futures::io::{AsyncReadExt, AsyncWriteExt};
-use futures::task::Spawn;
+use futures::task::{self, Wake};
 
+use romio::reactor::{self, Reactor};
+use romio::tcp::TcpSocket;
 use romio::TcpListener;
 
 const THE_WINTERS_TALE: &[u8] = b"
@@ -81,3 +85,367 @@ fn both_sides_async_using_threadpool() {
         assert_eq!(buf, THE_WINTERS_TALE);
     })));
 }
+
+#[test]
+fn listener_with_handle() {
+    drop(env_logger::try_init());
+    let mut reactor = Reactor::new().unwrap();
+    let handle = reactor.handle();
+
+    let server = TcpListener::bind_with_handle(&"127.0.0.1:0".parse().unwrap(), &handle).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // client thread
+    thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+    });
+
+    // a stream binding lazily would be registered with this reactor, which
+    // is never turned
+    let idle = Reactor::new().unwrap();
+    let _guard = reactor::set_default(&idle.handle());
+
+    let mut task = Box::pinned(async {
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        let mut incoming = server.incoming();
+        let mut stream = await!(incoming.next()).unwrap().unwrap();
+        await!(stream.read_exact(&mut buf)).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+    });
+
+    // only the listener's reactor is turned
+    let lw = task::local_waker_from_nonlocal(Arc::new(NoopWake));
+    for _ in 0..100 {
+        if task.as_mut().poll(&lw).is_ready() {
+            return;
+        }
+        reactor.turn(Some(Duration::from_millis(50))).unwrap();
+    }
+    panic!("the accepted stream isn't driven by the listener's reactor");
+}
+
+struct NoopWake;
+
+impl Wake for NoopWake {
+    fn wake(_: &Arc<Self>) {}
+}
+
+#[test]
+fn half_close() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    // the client shuts down its write side, and waits for the server's reply
+    let client = thread::spawn(move || {
+        let mut client = TcpStream::connect(&addr).unwrap();
+        client.write_all(THE_WINTERS_TALE).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+
+        let mut buf = vec![];
+        client.read_to_end(&mut buf).unwrap();
+        buf
+    });
+
+    executor::block_on(async {
+        let mut incoming = server.incoming();
+        let mut stream = await!(incoming.next()).unwrap().unwrap();
+
+        // the half-close is seen before any data is read
+        await!(poll_fn(|lw| stream.poll_read_closed(lw))).unwrap();
+        let ready = await!(poll_fn(|lw| stream.poll_read_ready(lw))).unwrap();
+        assert!(ready.is_read_closed());
+
+        let mut buf = vec![];
+        await!(stream.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, THE_WINTERS_TALE);
+
+        await!(stream.write_all(b"Exit, pursued by a bear.")).unwrap();
+    });
+
+    assert_eq!(client.join().unwrap(), b"Exit, pursued by a bear.");
+}
+
+#[test]
+#[cfg(unix)]
+fn tcp_socket_reuseport() {
+    drop(env_logger::try_init());
+    let bind = |addr| {
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_reuseport(true).unwrap();
+        socket.bind(&addr).unwrap();
+        socket.listen(16).unwrap()
+    };
+
+    let first = bind("127.0.0.1:0".parse().unwrap());
+    let addr = first.local_addr().unwrap();
+    let second = bind(addr);
+    assert_eq!(second.local_addr().unwrap(), addr);
+}
+
+#[test]
+fn tcp_socket_connect_from_bound_addr() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_reuseaddr(true).unwrap();
+    socket.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let local_addr = socket.local_addr().unwrap();
+
+    executor::block_on(async {
+        let client = await!(socket.connect(&addr)).unwrap();
+        assert_eq!(client.local_addr().unwrap(), local_addr);
+
+        let mut incoming = server.incoming();
+        let stream = await!(incoming.next()).unwrap().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), local_addr);
+    });
+}
+
+#[test]
+#[cfg(unix)]
+fn listener_group_accepts_on_all_reactors() {
+    use romio::tcp::TcpListenerGroup;
+
+    drop(env_logger::try_init());
+    let reactors: Vec<_> = (0..2).map(|_| Reactor::new().unwrap()).collect();
+    let handles: Vec<_> = reactors.iter().map(|r| r.handle()).collect();
+
+    let group =
+        TcpListenerGroup::bind_with_handles(&"127.0.0.1:0".parse().unwrap(), &handles).unwrap();
+    let addr = group.local_addr().unwrap();
+
+    let backgrounds: Vec<_> = reactors
+        .into_iter()
+        .map(|r| r.background().unwrap())
+        .collect();
+
+    // The OS picks a listener by hashing the address of the client: with
+    // enough connections, each listener gets some.
+    const CLIENTS: usize = 64;
+    let clients = thread::spawn(move || {
+        (0..CLIENTS)
+            .map(|_| {
+                let mut client = TcpStream::connect(&addr).unwrap();
+                client.write_all(b"hi").unwrap();
+                client
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut incoming: Vec<_> = group
+        .into_listeners()
+        .into_iter()
+        .map(TcpListener::incoming)
+        .collect();
+    let mut accepted = vec![0; incoming.len()];
+
+    executor::block_on(async {
+        for _ in 0..CLIENTS {
+            // accept from whichever listener, driven by its own reactor, is ready
+            let (i, mut stream) = await!(poll_fn(|lw| {
+                for (i, incoming) in incoming.iter_mut().enumerate() {
+                    if let Poll::Ready(stream) = incoming.poll_next_unpin(lw) {
+                        return Poll::Ready((i, stream.unwrap().unwrap()));
+                    }
+                }
+                Poll::Pending
+            }));
+            accepted[i] += 1;
+
+            let mut buf = [0; 2];
+            await!(stream.read_exact(&mut buf)).unwrap();
+            assert_eq!(&buf, b"hi");
+        }
+    });
+
+    assert!(
+        accepted.iter().all(|&n| n > 0),
+        "connections accepted per listener: {:?}",
+        accepted
+    );
+
+    drop(clients.join().unwrap());
+    for background in backgrounds {
+        executor::block_on(background.shutdown_now()).unwrap();
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn listener_group_incoming() {
+    use romio::server::{AcceptPolicy, ConnectionLimit, ShutdownController};
+    use romio::tcp::TcpListenerGroup;
+
+    drop(env_logger::try_init());
+    let reactors: Vec<_> = (0..2).map(|_| Reactor::new().unwrap()).collect();
+    let handles: Vec<_> = reactors.iter().map(|r| r.handle()).collect();
+
+    let group =
+        TcpListenerGroup::bind_with_handles(&"127.0.0.1:0".parse().unwrap(), &handles).unwrap();
+    let addr = group.local_addr().unwrap();
+
+    let backgrounds: Vec<_> = reactors
+        .into_iter()
+        .map(|r| r.background().unwrap())
+        .collect();
+
+    // as above, each listener gets some of the connections, so accepting all
+    // of them takes polling every listener of the group
+    const CLIENTS: usize = 64;
+    let clients = thread::spawn(move || {
+        (0..CLIENTS)
+            .map(|_| {
+                let mut client = TcpStream::connect(&addr).unwrap();
+                client.write_all(b"hi").unwrap();
+                client
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let limit = ConnectionLimit::new(CLIENTS);
+    let controller = ShutdownController::new();
+    let mut incoming = group
+        .incoming()
+        .accept_policy(AcceptPolicy::new())
+        .limit(&limit)
+        .until_shutdown(&controller);
+
+    let streams = executor::block_on(async {
+        let mut streams = vec![];
+        for _ in 0..CLIENTS {
+            let mut stream = await!(incoming.next()).unwrap().unwrap();
+            let mut buf = [0; 2];
+            await!(stream.read_exact(&mut buf)).unwrap();
+            assert_eq!(&buf, b"hi");
+            streams.push(stream);
+        }
+
+        drop(controller.shutdown(Duration::from_secs(5)));
+        assert!(await!(incoming.next()).is_none());
+        streams
+    });
+
+    assert_eq!(limit.active(), CLIENTS);
+    drop(streams);
+    assert_eq!(limit.active(), 0);
+
+    drop(clients.join().unwrap());
+    for background in backgrounds {
+        executor::block_on(background.shutdown_now()).unwrap();
+    }
+}
+
+/// Returns a listener whose accept queue is full, so that connecting to it
+/// hangs, along with the connection filling the queue.
+fn unresponsive_listener() -> (romio::TcpListener, TcpStream) {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let listener = socket.listen(0).unwrap();
+    let filler = TcpStream::connect(&listener.local_addr().unwrap()).unwrap();
+    (listener, filler)
+}
+
+#[test]
+fn connect_to_skips_failed_addresses() {
+    drop(env_logger::try_init());
+    let refused = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let addrs = [refused, addr];
+        let stream = await!(romio::TcpStream::connect_to(&addrs[..])).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        let addrs = [refused, refused];
+        let err = await!(romio::TcpStream::connect_to(&addrs[..])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        assert!(err.to_string().contains(&refused.to_string()));
+    });
+}
+
+#[test]
+fn connect_to_races_slow_addresses() {
+    drop(env_logger::try_init());
+    let (slow, _filler) = unresponsive_listener();
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let addrs = [slow.local_addr().unwrap(), addr];
+        let connect = romio::TcpStream::connect_to(&addrs[..])
+            .attempt_delay(Duration::from_millis(50))
+            .timeout(Duration::from_secs(5));
+        let stream = await!(connect).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    });
+}
+
+#[test]
+fn connect_to_timeout() {
+    drop(env_logger::try_init());
+    let (slow, _filler) = unresponsive_listener();
+
+    executor::block_on(async {
+        let connect = romio::TcpStream::connect_to(slow.local_addr().unwrap())
+            .timeout(Duration::from_millis(100));
+        let err = await!(connect).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    });
+}
+
+#[test]
+fn into_split_halves() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let mut incoming = server.incoming();
+        let client = await!(romio::TcpStream::connect(&addr)).unwrap();
+        let mut accepted = await!(incoming.next()).unwrap().unwrap();
+        let (mut reader, mut writer) = client.into_split();
+
+        // dropping the write half shuts the connection down for writing only
+        await!(writer.write_all(b"hello")).unwrap();
+        drop(writer);
+
+        let mut buf = vec![];
+        await!(accepted.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, b"hello");
+
+        await!(accepted.write_all(b"world")).unwrap();
+        let mut buf = [0; 5];
+        await!(reader.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"world");
+    });
+}
+
+#[test]
+fn reunite_halves() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    executor::block_on(async {
+        let a = await!(romio::TcpStream::connect(&addr)).unwrap();
+        let b = await!(romio::TcpStream::connect(&addr)).unwrap();
+        let local_addr = a.local_addr().unwrap();
+
+        let (a_read, a_write) = a.into_split();
+        let (b_read, b_write) = b.into_split();
+
+        let romio::tcp::ReuniteError(a_read, b_write) = a_read.reunite(b_write).unwrap_err();
+        drop((b_read, b_write));
+
+        let a = a_read.reunite(a_write).unwrap();
+        assert_eq!(a.local_addr().unwrap(), local_addr);
+    });
+}
@@ -0,0 +1,316 @@
+#![feature(arbitrary_self_types, async_await, await_macro, futures_api, pin)]
+use std::io;
+use std::pin::Pin;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures::executor::block_on;
+use futures::future;
+use futures::io::AsyncReadExt;
+use futures::task::{LocalSpawnExt, LocalWaker};
+use futures::{Future, Poll, StreamExt};
+
+use romio::reactor::Reactor;
+use romio::runtime::current_thread::Runtime;
+use romio::runtime::multi_thread;
+use romio::timer::{self, Clock, Delay, DelayQueue, Interval, Now, Timeout};
+use romio::{TcpListener, TcpStream};
+
+#[test]
+fn delay_with_runtime() {
+    drop(env_logger::try_init());
+    let when = Instant::now() + Duration::from_millis(100);
+
+    let mut runtime = Runtime::new().unwrap();
+    runtime.block_on(Delay::new(when));
+
+    assert!(Instant::now() >= when);
+}
+
+#[test]
+fn delay_with_fallback_reactor() {
+    drop(env_logger::try_init());
+    let when = Instant::now() + Duration::from_millis(20);
+
+    block_on(Delay::new(when));
+
+    assert!(Instant::now() >= when);
+}
+
+#[test]
+fn starving() {
+    drop(env_logger::try_init());
+
+    struct Starve(Delay, u64);
+
+    impl Future for Starve {
+        type Output = u64;
+
+        fn poll(mut self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<u64> {
+            if Pin::new(&mut self.0).poll(lw).is_ready() {
+                return Poll::Ready(self.1);
+            }
+
+            self.1 += 1;
+            lw.wake();
+
+            Poll::Pending
+        }
+    }
+
+    let when = Instant::now() + Duration::from_millis(20);
+    let mut runtime = Runtime::new().unwrap();
+    runtime.block_on(Starve(Delay::new(when), 0));
+
+    assert!(Instant::now() >= when);
+}
+
+#[test]
+fn reset_delay() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+
+    let mut runtime = Runtime::new().unwrap();
+    runtime.block_on(async move {
+        let mut delay = Delay::new(start + Duration::from_secs(60));
+
+        let res = await!(Timeout::new(&mut delay, Duration::from_millis(10)));
+        assert!(res.is_err());
+
+        delay.reset(start + Duration::from_millis(20));
+        await!(&mut delay);
+        assert!(delay.is_elapsed());
+    });
+
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(20));
+    assert!(elapsed < Duration::from_secs(60));
+}
+
+#[test]
+fn interval() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+    let period = Duration::from_millis(10);
+
+    let mut runtime = Runtime::new().unwrap();
+    let ticks: Vec<Instant> = runtime.block_on(Interval::new(start, period).take(3).collect());
+
+    assert_eq!(ticks, vec![start, start + period, start + period * 2]);
+    assert!(Instant::now() >= start + period * 2);
+}
+
+#[test]
+fn reactor_dropped_while_delay_pending() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+    let reactor = Reactor::new().unwrap();
+    let handle = reactor.handle();
+    let background = reactor.background().unwrap();
+
+    let shutdown = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        block_on(background.shutdown_now()).unwrap();
+    });
+
+    // the pending delay is woken up as the reactor shuts down, and completes
+    let deadline = start + Duration::from_secs(60);
+    block_on(Delay::new_with_handle(deadline, &handle));
+    assert!(start.elapsed() < Duration::from_secs(60));
+    shutdown.join().unwrap();
+
+    // so do the timers created afterwards
+    block_on(Delay::new_with_handle(deadline, &handle));
+    let res = block_on(Timeout::new_with_handle(future::empty::<()>(), deadline, &handle));
+    assert!(res.is_err());
+
+    let period = Duration::from_secs(1);
+    let mut interval = Interval::new_with_handle(deadline, period, &handle);
+    assert_eq!(block_on(interval.next()), None);
+}
+
+#[test]
+fn timeout() {
+    drop(env_logger::try_init());
+
+    let mut runtime = Runtime::new().unwrap();
+    let res = runtime.block_on(Timeout::new(future::empty::<()>(), Duration::from_millis(20)));
+    assert!(res.is_err());
+
+    let res = runtime.block_on(Timeout::new(future::ready(5), Duration::from_millis(20)));
+    assert_eq!(res.unwrap(), 5);
+}
+
+#[test]
+fn timeout_read() {
+    drop(env_logger::try_init());
+    let server = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let mut runtime = Runtime::new().unwrap();
+    let err = runtime.block_on(async move {
+        let mut incoming = server.incoming();
+        let _client = await!(TcpStream::connect(&addr)).unwrap();
+        let mut stream = await!(incoming.next()).unwrap().unwrap();
+
+        let mut buf = [0; 16];
+        let read = stream.read(&mut buf);
+        let res: io::Result<usize> = await!(Timeout::new(read, Duration::from_millis(20)))
+            .map_err(io::Error::from)
+            .and_then(|res| res);
+        res.unwrap_err()
+    });
+
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
+
+struct MockNow(Instant);
+
+impl Now for MockNow {
+    fn now(&self) -> Instant {
+        self.0
+    }
+}
+
+#[test]
+fn clock_and_timer_single_threaded() {
+    drop(env_logger::try_init());
+    let when = Instant::now() + Duration::from_millis(5_000);
+    let clock = Clock::new_with_now(MockNow(when));
+
+    let mut runtime = Runtime::new_with_clock(clock).unwrap();
+    runtime.block_on(Delay::new(when));
+
+    assert!(Instant::now() < when);
+}
+
+#[test]
+fn clock_and_timer_concurrent() {
+    drop(env_logger::try_init());
+    let when = Instant::now() + Duration::from_millis(5_000);
+    let clock = Clock::new_with_now(MockNow(when));
+
+    let runtime = multi_thread::Builder::new()
+        .workers(2)
+        .clock(clock)
+        .build()
+        .unwrap();
+    runtime.block_on(Delay::new(when));
+
+    assert!(Instant::now() < when);
+}
+
+#[test]
+fn paused_clock_sleep() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+    let clock = Clock::paused();
+
+    let mut runtime = Runtime::new_with_clock(clock.clone()).unwrap();
+    let mut spawner = runtime.spawner();
+
+    let slept = runtime.block_on(async move {
+        let before = timer::now();
+        let sleep = timer::sleep(Duration::from_secs(60 * 60));
+
+        spawner
+            .spawn_local(async move {
+                let halfway = timer::sleep(Duration::from_secs(30 * 60));
+                clock.advance(Duration::from_secs(30 * 60));
+                await!(halfway);
+                clock.advance(Duration::from_secs(30 * 60));
+            })
+            .unwrap();
+
+        await!(sleep);
+        timer::now() - before
+    });
+
+    assert!(slept >= Duration::from_secs(60 * 60));
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
+
+#[test]
+fn paused_clock_timeout() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+    let clock = Clock::paused();
+
+    let mut runtime = Runtime::new_with_clock(clock.clone()).unwrap();
+    let mut spawner = runtime.spawner();
+
+    let res = runtime.block_on(async move {
+        let timeout = Timeout::new(future::empty::<()>(), Duration::from_secs(30));
+
+        spawner
+            .spawn_local(async move {
+                let sleep = timer::sleep(Duration::from_secs(29));
+                clock.advance(Duration::from_secs(29));
+                await!(sleep);
+                clock.advance(Duration::from_secs(2));
+            })
+            .unwrap();
+
+        await!(timeout)
+    });
+
+    assert!(res.is_err());
+    assert!(start.elapsed() < Duration::from_secs(29));
+}
+
+#[test]
+fn delay_queue() {
+    drop(env_logger::try_init());
+    let clock = Clock::paused();
+
+    let mut runtime = Runtime::new_with_clock(clock.clone()).unwrap();
+    let mut spawner = runtime.spawner();
+
+    let expired = runtime.block_on(async move {
+        let mut queue = DelayQueue::new();
+        let a = queue.insert("a", Duration::from_secs(10));
+        let b = queue.insert("b", Duration::from_secs(20));
+        queue.insert("c", Duration::from_secs(30));
+        queue.insert("d", Duration::from_secs(40));
+
+        assert_eq!(queue.remove(&b).into_inner(), "b");
+        queue.reset(&a, Duration::from_secs(35));
+        assert_eq!(queue.len(), 3);
+
+        spawner
+            .spawn_local(async move {
+                clock.advance(Duration::from_secs(60));
+            })
+            .unwrap();
+
+        let mut expired = vec![];
+        while let Some(entry) = await!(queue.next()) {
+            expired.push(entry.into_inner());
+        }
+        expired
+    });
+
+    assert_eq!(expired, vec!["c", "a", "d"]);
+}
+
+#[test]
+fn delay_queue_reset_earlier() {
+    drop(env_logger::try_init());
+    let start = Instant::now();
+
+    let mut runtime = Runtime::new().unwrap();
+    runtime.block_on(async move {
+        let mut queue = DelayQueue::new();
+        let key = queue.insert(1, Duration::from_secs(60));
+
+        let res = await!(Timeout::new(queue.next(), Duration::from_millis(10)));
+        assert!(res.is_err());
+
+        queue.reset(&key, Duration::from_millis(10));
+        let expired = await!(queue.next()).unwrap();
+        assert_eq!(expired.into_inner(), 1);
+        assert!(queue.is_empty());
+    });
+
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
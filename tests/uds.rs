@@ -1,16 +1,23 @@
 #![cfg(unix)]
 #![feature(async_await, await_macro, pin)]
-use std::io::{Read, Write};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::os::unix::net::UnixStream as StdStream;
+use std::process::Command;
 use std::thread;
 
 use futures::executor;
-use futures::future::FutureObj;
+use futures::future::{poll_fn, FutureObj};
 use futures::io::{AsyncReadExt, AsyncWriteExt};
 use futures::StreamExt;
 use tempdir::TempDir;
 
-use romio::uds::{UnixListener, UnixStream};
+use romio::uds::{BindOptions, UnixDatagram, UnixListener, UnixStream};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use romio::uds::{UnixSeqpacket, UnixSeqpacketListener};
 
 type Error = Box<dyn std::error::Error + 'static>;
 
@@ -102,3 +109,405 @@ fn both_sides_async_using_threadpool() -> Result<(), Error>{
 
     Ok(())
 }
+
+#[test]
+fn into_split_halves() {
+    drop(env_logger::try_init());
+
+    executor::block_on(async {
+        let (a, mut b) = UnixStream::pair().unwrap();
+        let (mut reader, mut writer) = a.into_split();
+
+        await!(writer.write_all(b"hello")).unwrap();
+        drop(writer);
+
+        let mut buf = vec![];
+        await!(b.read_to_end(&mut buf)).unwrap();
+        assert_eq!(buf, b"hello");
+
+        await!(b.write_all(b"world")).unwrap();
+        let mut buf = [0; 5];
+        await!(reader.read_exact(&mut buf)).unwrap();
+        assert_eq!(&buf, b"world");
+
+        let (c, _d) = UnixStream::pair().unwrap();
+        let (c_read, c_write) = c.into_split();
+        let romio::uds::ReuniteError(reader, c_write) = reader.reunite(c_write).unwrap_err();
+        assert!(c_read.reunite(c_write).is_ok());
+        drop(reader);
+    });
+}
+
+fn is_cloexec(fd: &impl AsRawFd) -> bool {
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) };
+    flags & libc::FD_CLOEXEC != 0
+}
+
+#[test]
+fn stream_passes_fds() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("stream_passes_fds")?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(tmp_dir.path().join("file"))?;
+    file.write_all(THE_WINTERS_TALE)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    executor::block_on(async {
+        let (mut a, mut b) = UnixStream::pair()?;
+        let (_reader, writer) = StdStream::pair()?;
+
+        let fds = [file.as_raw_fd(), writer.as_raw_fd()];
+        assert_eq!(await!(a.send_with_fds(b"files", &fds))?, 5);
+        drop(file);
+
+        let mut buf = [0; 16];
+        let (n, mut fds, truncated) = await!(b.recv_with_fds(&mut buf))?;
+        assert!(!truncated);
+        assert_eq!(&buf[..n], b"files");
+        assert_eq!(fds.len(), 2);
+        assert!(fds.iter().all(is_cloexec));
+
+        drop(fds.pop());
+        let mut file = unsafe { File::from_raw_fd(fds.pop().unwrap().into_raw_fd()) };
+        let mut contents = vec![];
+        file.read_to_end(&mut contents)?;
+        assert_eq!(contents, THE_WINTERS_TALE);
+
+        // data without file descriptors
+        await!(a.write_all(b"more"))?;
+        let (n, fds, _) = await!(b.recv_with_fds(&mut buf))?;
+        assert_eq!(&buf[..n], b"more");
+        assert!(fds.is_empty());
+        Ok(())
+    })
+}
+
+#[test]
+fn datagram_passes_fds() -> Result<(), Error> {
+    drop(env_logger::try_init());
+
+    executor::block_on(async {
+        let (mut a, mut b) = UnixDatagram::pair()?;
+        let (mut reader, writer) = StdStream::pair()?;
+
+        let fds = [writer.as_raw_fd()];
+        await!(a.send_with_fds(b"first", &fds))?;
+        await!(a.send_with_fds(b"second", &[]))?;
+        drop(writer);
+
+        let mut buf = [0; 16];
+        let (n, fds, _) = await!(b.recv_with_fds(&mut buf))?;
+        assert_eq!(&buf[..n], b"first");
+        assert_eq!(fds.len(), 1);
+
+        let mut writer = unsafe { StdStream::from_raw_fd(fds[0].try_clone()?.into_raw_fd()) };
+        writer.write_all(b"passed")?;
+        drop(writer);
+        drop(fds);
+
+        let mut received = vec![];
+        reader.read_to_end(&mut received)?;
+        assert_eq!(received, b"passed");
+
+        let (n, fds, _) = await!(b.recv_with_fds(&mut buf))?;
+        assert_eq!(&buf[..n], b"second");
+        assert!(fds.is_empty());
+        Ok(())
+    })
+}
+
+// Lowering the limit of open files affects the whole process, so the test
+// runs `recv_with_fds_truncated_child` in a child process.
+#[test]
+fn recv_with_fds_truncated() -> Result<(), Error> {
+    let output = Command::new(env::current_exe()?)
+        .args(&["--exact", "recv_with_fds_truncated_child", "--ignored"])
+        .output()?;
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn recv_with_fds_truncated_child() -> Result<(), Error> {
+    drop(env_logger::try_init());
+
+    executor::block_on(async {
+        let (mut a, mut b) = UnixStream::pair()?;
+        let (_reader, writer) = StdStream::pair()?;
+
+        // register the sockets with the reactor while it can open files
+        let mut buf = [0; 16];
+        await!(a.write_all(b"start"))?;
+        await!(b.read_exact(&mut buf[..5]))?;
+
+        // allow at most two more file descriptors
+        unsafe {
+            let next = libc::dup(writer.as_raw_fd());
+            libc::close(next);
+
+            let mut limit: libc::rlimit = std::mem::zeroed();
+            assert_eq!(libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit), 0);
+            limit.rlim_cur = next as libc::rlim_t + 2;
+            assert_eq!(libc::setrlimit(libc::RLIMIT_NOFILE, &limit), 0);
+        }
+
+        let fds = [writer.as_raw_fd(); 4];
+        await!(a.send_with_fds(b"files", &fds))?;
+
+        // the data is still received, along with the file descriptors which
+        // fit
+        let (n, fds, truncated) = await!(b.recv_with_fds(&mut buf))?;
+        assert_eq!(&buf[..n], b"files");
+        assert!(fds.len() < 4);
+        assert!(truncated);
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn datagram_passes_credentials() -> Result<(), Error> {
+    drop(env_logger::try_init());
+
+    executor::block_on(async {
+        let (mut a, mut b) = UnixDatagram::pair()?;
+        let mut buf = [0; 16];
+
+        await!(a.send_with_cred(b"unseen"))?;
+        let (n, cred) = await!(b.recv_with_cred(&mut buf))?;
+        assert_eq!(&buf[..n], b"unseen");
+        assert_eq!(cred, None);
+
+        b.set_passcred(true)?;
+        assert!(b.passcred()?);
+
+        // credentials are attached by the kernel when they aren't sent
+        await!(a.send_with_fds(b"implicit", &[]))?;
+        await!(a.send_with_cred(b"explicit"))?;
+
+        for expected in &[&b"implicit"[..], &b"explicit"[..]] {
+            let (n, cred) = await!(b.recv_with_cred(&mut buf))?;
+            assert_eq!(&buf[..n], *expected);
+
+            let cred = cred.unwrap();
+            assert_eq!(cred.pid(), Some(std::process::id() as libc::pid_t));
+            assert_eq!(cred.uid, unsafe { libc::getuid() });
+            assert_eq!(cred.gid, unsafe { libc::getgid() });
+        }
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn abstract_namespace() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let name = format!("romio-abstract-{}", std::process::id());
+    let name = name.as_bytes();
+
+    let listener = UnixListener::bind_abstract(name)?;
+    let addr = listener.local_addr()?;
+    assert_eq!(addr.as_abstract_name(), Some(name));
+    assert_eq!(addr.as_pathname(), None);
+    assert!(format!("{:?}", addr).ends_with("(abstract)"));
+
+    let err = UnixListener::bind_abstract(name).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    let err = UnixListener::bind_abstract(&[b'x'; 108]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    executor::block_on(async {
+        let mut client = await!(UnixStream::connect_abstract(name))?;
+        assert_eq!(client.peer_addr()?.as_abstract_name(), Some(name));
+        assert!(client.local_addr()?.is_unnamed());
+
+        let mut incoming = listener.incoming();
+        let mut stream = await!(incoming.next()).unwrap()?;
+        await!(client.write_all(THE_WINTERS_TALE))?;
+
+        let mut buf = vec![0; THE_WINTERS_TALE.len()];
+        await!(stream.read_exact(&mut buf))?;
+        assert_eq!(buf, THE_WINTERS_TALE);
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn abstract_namespace_datagram() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let server_name = format!("romio-abstract-server-{}", std::process::id());
+    let client_name = format!("romio-abstract-client-{}", std::process::id());
+
+    let server = UnixDatagram::bind_abstract(server_name.as_bytes())?;
+    let client = UnixDatagram::bind_abstract(client_name.as_bytes())?;
+    let server_addr = server.local_addr()?;
+
+    executor::block_on(async {
+        let mut buf = [0; 16];
+        await!(poll_fn(|lw| client.poll_send_to_addr(lw, b"ping", &server_addr)))?;
+        let (n, addr) = await!(poll_fn(|lw| server.poll_recv_from(lw, &mut buf)))?;
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(addr.as_abstract_name(), Some(client_name.as_bytes()));
+
+        await!(poll_fn(|lw| server.poll_send_to_addr(lw, b"pong", &addr)))?;
+        let (n, addr) = await!(poll_fn(|lw| client.poll_recv_from(lw, &mut buf)))?;
+        assert_eq!(&buf[..n], b"pong");
+        assert_eq!(addr.as_abstract_name(), Some(server_name.as_bytes()));
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn seqpacket_preserves_boundaries() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("seqpacket_preserves_boundaries")?;
+    let file_path = tmp_dir.path().join("sock");
+
+    let listener = UnixSeqpacketListener::bind(&file_path)?;
+    assert_eq!(listener.local_addr()?.as_pathname(), Some(&*file_path));
+
+    executor::block_on(async {
+        let mut client = await!(UnixSeqpacket::connect(&file_path))?;
+        let mut incoming = listener.incoming();
+        let mut server = await!(incoming.next()).unwrap()?;
+
+        let cred = server.peer_cred()?;
+        assert_eq!(cred.pid(), Some(std::process::id() as libc::pid_t));
+        assert_eq!(cred.uid, unsafe { libc::getuid() });
+
+        await!(client.send(b"first"))?;
+        await!(client.send(b""))?;
+        await!(client.send(b"second"))?;
+        await!(client.send(THE_WINTERS_TALE))?;
+
+        let mut buf = [0; 16];
+        let n = await!(server.recv(&mut buf))?;
+        assert_eq!(&buf[..n], b"first");
+        let n = await!(server.recv(&mut buf))?;
+        assert_eq!(n, 0);
+        let n = await!(server.recv(&mut buf))?;
+        assert_eq!(&buf[..n], b"second");
+
+        // the rest of a message which doesn't fit is discarded
+        let err = await!(server.recv(&mut buf)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(&buf[..], &THE_WINTERS_TALE[..16]);
+
+        drop(client);
+        let n = await!(server.recv(&mut buf))?;
+        assert_eq!(n, 0);
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn seqpacket_pair_and_abstract() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let name = format!("romio-seqpacket-{}", std::process::id());
+    let name = name.as_bytes();
+
+    executor::block_on(async {
+        let (mut a, mut b) = UnixSeqpacket::pair()?;
+        assert!(a.peer_addr()?.is_unnamed());
+        await!(a.send(b"ping"))?;
+        let mut buf = [0; 4];
+        let n = await!(b.recv(&mut buf))?;
+        assert_eq!(&buf[..n], b"ping");
+
+        let listener = UnixSeqpacketListener::bind_abstract(name)?;
+        let mut client = await!(UnixSeqpacket::connect_abstract(name))?;
+        assert_eq!(client.peer_addr()?.as_abstract_name(), Some(name));
+
+        let mut incoming = listener.incoming();
+        let mut server = await!(incoming.next()).unwrap()?;
+        await!(server.send(b"pong"))?;
+        let n = await!(client.recv(&mut buf))?;
+        assert_eq!(&buf[..n], b"pong");
+
+        // connecting a stream socket to a seqpacket listener fails
+        let err = await!(UnixStream::connect_abstract(name)).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ECONNREFUSED));
+        Ok(())
+    })
+}
+
+#[test]
+fn bind_options_remove_stale_socket() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("bind_options_remove_stale_socket")?;
+    let file_path = tmp_dir.path().join("sock");
+
+    // a listener which is dropped leaves its socket behind
+    drop(UnixListener::bind(&file_path)?);
+    assert!(UnixListener::bind(&file_path).is_err());
+
+    let listener = BindOptions::new()
+        .remove_stale(true)
+        .unlink_on_drop(true)
+        .mode(0o600)
+        .bind(&file_path)?;
+    let metadata = std::fs::symlink_metadata(&file_path)?;
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+    // a socket which is listened on isn't stale
+    let err = BindOptions::new().remove_stale(true).bind(&file_path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    executor::block_on(async {
+        let mut client = await!(UnixStream::connect(&file_path))?;
+        let mut incoming = listener.incoming();
+
+        // the connection probing the socket is accepted first
+        let mut probe = await!(incoming.next()).unwrap()?;
+        let mut buf = vec![];
+        await!(probe.read_to_end(&mut buf))?;
+        assert!(buf.is_empty());
+
+        let mut stream = await!(incoming.next()).unwrap()?;
+        await!(client.write_all(b"still here"))?;
+        let mut buf = [0; 10];
+        await!(stream.read_exact(&mut buf))?;
+        assert_eq!(&buf, b"still here");
+
+        drop(incoming);
+        assert!(!file_path.exists());
+        Ok::<_, Error>(())
+    })?;
+
+    // files which aren't sockets are left alone
+    File::create(&file_path)?;
+    let err = BindOptions::new().remove_stale(true).bind(&file_path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    assert!(file_path.is_file());
+    Ok(())
+}
+
+#[test]
+fn bind_options_unlink_only_own_socket() -> Result<(), Error> {
+    drop(env_logger::try_init());
+    let tmp_dir = TempDir::new("bind_options_unlink_only_own_socket")?;
+    let file_path = tmp_dir.path().join("sock");
+
+    let listener = BindOptions::new().unlink_on_drop(true).bind(&file_path)?;
+
+    // another listener takes the path over
+    std::fs::remove_file(&file_path)?;
+    let other = UnixListener::bind(&file_path)?;
+
+    drop(listener);
+    assert!(file_path.exists());
+    drop(other);
+    Ok(())
+}